@@ -0,0 +1,66 @@
+//! Controls the pomodoro block of a running i3status-rs over D-Bus. The block
+//! must set `ipc_name`, see the pomodoro section of blocks.md.
+//!
+//! Exit codes: 0 on success, 1 on invalid arguments, 2 if no running
+//! pomodoro block has the given name, 3 on any other D-Bus error.
+
+use std::process;
+use std::time::Duration;
+
+use clap::{crate_authors, crate_version, App, Arg};
+use dbus::blocking::Connection;
+
+/// Keep in sync with the pomodoro block.
+const CONTROL_NAME_PREFIX: &str = "i3.status.rs.pomodoro";
+const CONTROL_PATH: &str = "/pomodoro";
+const CONTROL_INTERFACE: &str = "i3.status.rs.Pomodoro";
+
+const EXIT_NOT_RUNNING: i32 = 2;
+const EXIT_DBUS_ERROR: i32 = 3;
+
+fn main() {
+    // Invalid arguments make clap exit with 1
+    let matches = App::new("i3status-rs-pomodoro")
+        .version(crate_version!())
+        .author(crate_authors!())
+        .about("Controls the pomodoro block of a running i3status-rs")
+        .arg(
+            Arg::with_name("name")
+                .help("The ipc_name of the pomodoro block")
+                .long("name")
+                .short("n")
+                .takes_value(true)
+                .default_value("pomodoro"),
+        )
+        .arg(
+            Arg::with_name("command")
+                .help("The command to send")
+                .required(true)
+                .possible_values(&["start", "pause", "toggle", "skip", "stop", "reset"])
+                .index(1),
+        )
+        .get_matches();
+
+    let name = matches.value_of("name").unwrap();
+    let command = matches.value_of("command").unwrap();
+
+    let result: Result<(), dbus::Error> = Connection::new_session().and_then(|c| {
+        let destination = format!("{}.{}", CONTROL_NAME_PREFIX, name);
+        c.with_proxy(destination, CONTROL_PATH, Duration::from_secs(5))
+            .method_call(CONTROL_INTERFACE, "Command", (command,))
+    });
+
+    if let Err(e) = result {
+        match e.name() {
+            Some("org.freedesktop.DBus.Error.ServiceUnknown")
+            | Some("org.freedesktop.DBus.Error.NameHasNoOwner") => {
+                eprintln!("no running pomodoro block is named '{}'", name);
+                process::exit(EXIT_NOT_RUNNING);
+            }
+            _ => {
+                eprintln!("failed to send the command: {}", e);
+                process::exit(EXIT_DBUS_ERROR);
+            }
+        }
+    }
+}
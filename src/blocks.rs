@@ -1,6 +1,9 @@
+pub mod ac_adapter;
 pub mod backlight;
 pub mod battery;
+pub mod binding_mode;
 pub mod bluetooth;
+pub mod compose;
 pub mod cpu;
 pub mod custom;
 pub mod custom_dbus;
@@ -8,6 +11,7 @@ pub mod disk_space;
 pub mod docker;
 pub mod focused_window;
 pub mod github;
+pub mod gpu;
 pub mod hueshift;
 pub mod ibus;
 pub mod kdeconnect;
@@ -24,8 +28,10 @@ pub mod notmuch;
 pub mod nvidia_gpu;
 pub mod pacman;
 pub mod pomodoro;
+pub mod rapl;
 pub mod sound;
 pub mod speedtest;
+pub mod systemd;
 pub mod taskwarrior;
 pub mod temperature;
 pub mod template;
@@ -35,10 +41,14 @@ pub mod uptime;
 pub mod watson;
 pub mod weather;
 pub mod xrandr;
+pub mod xrun;
 
+use self::ac_adapter::*;
 use self::backlight::*;
 use self::battery::*;
+use self::binding_mode::*;
 use self::bluetooth::*;
+use self::compose::*;
 use self::cpu::*;
 use self::custom::*;
 use self::custom_dbus::*;
@@ -46,6 +56,7 @@ use self::disk_space::*;
 use self::docker::*;
 use self::focused_window::*;
 use self::github::*;
+use self::gpu::*;
 use self::hueshift::*;
 use self::ibus::*;
 use self::kdeconnect::*;
@@ -62,8 +73,10 @@ use self::notmuch::*;
 use self::nvidia_gpu::*;
 use self::pacman::*;
 use self::pomodoro::*;
+use self::rapl::*;
 use self::sound::*;
 use self::speedtest::*;
+use self::systemd::*;
 use self::taskwarrior::*;
 use self::temperature::*;
 use self::template::*;
@@ -73,22 +86,29 @@ use self::uptime::*;
 use self::watson::*;
 use self::weather::*;
 use self::xrandr::*;
+use self::xrun::*;
 
-use std::time::Duration;
+use std::fs;
+use std::path::PathBuf;
+use std::time::{Duration, Instant};
 
 use crossbeam_channel::Sender;
 use serde::de::Deserialize;
 use toml::value::Value;
 
-use crate::config::Config;
+use crate::config::{CommonBlockConfig, Config};
 use crate::errors::*;
-use crate::input::I3BarEvent;
+use crate::input::{I3BarEvent, MouseButton};
 use crate::scheduler::Task;
+use crate::util::MarkupEscape;
 use crate::widget::I3BarWidget;
 
 #[derive(Clone, Debug, PartialEq)]
 pub enum Update {
     Every(Duration),
+    /// Update at the given time, e.g. one returned by `scheduler::next_boundary`
+    /// so that updates follow the wall clock without drifting.
+    At(Instant),
     Once,
 }
 
@@ -127,6 +147,141 @@ pub trait Block {
     fn click(&mut self, _event: &I3BarEvent) -> Result<()> {
         Ok(())
     }
+
+    /// Replaces the block's format string. Blocks implementing this support
+    /// the shared `formats` option.
+    fn set_format(&mut self, _format: &str) -> Result<()> {
+        Err(ConfigurationError(
+            "This block does not support the `formats` option".to_string(),
+            (String::new(), String::new()),
+        ))
+    }
+}
+
+/// Wraps a block configured with the shared `formats` option, switching the
+/// block to the next format on every left click. Left clicks on the block
+/// are not passed on to it.
+struct FormatCycle {
+    block: Box<dyn Block>,
+    formats: Vec<String>,
+    index: usize,
+    /// File the index is persisted to, if any
+    index_file: Option<PathBuf>,
+}
+
+impl FormatCycle {
+    fn new(
+        mut block: Box<dyn Block>,
+        formats: Vec<String>,
+        index_file: Option<PathBuf>,
+    ) -> Result<Self> {
+        let index = index_file
+            .as_ref()
+            .and_then(|path| fs::read_to_string(path).ok())
+            .and_then(|index| index.trim().parse().ok())
+            .filter(|&index| index < formats.len())
+            .unwrap_or(0);
+        block.set_format(&formats[index])?;
+
+        Ok(FormatCycle {
+            block,
+            formats,
+            index,
+            index_file,
+        })
+    }
+
+    fn is_on_block(&self, event: &I3BarEvent) -> bool {
+        let name = match event.name {
+            Some(ref name) => name,
+            None => return false,
+        };
+        name == self.block.id()
+            || self
+                .block
+                .view()
+                .iter()
+                .any(|widget| widget.get_rendered()["name"].as_str() == Some(name))
+    }
+}
+
+impl Block for FormatCycle {
+    fn id(&self) -> &str {
+        self.block.id()
+    }
+
+    fn view(&self) -> Vec<&dyn I3BarWidget> {
+        self.block.view()
+    }
+
+    fn update(&mut self) -> Result<Option<Update>> {
+        self.block.update()
+    }
+
+    fn signal(&mut self, signal: i32) -> Result<()> {
+        self.block.signal(signal)
+    }
+
+    fn click(&mut self, event: &I3BarEvent) -> Result<()> {
+        if event.button == MouseButton::Left && self.is_on_block(event) {
+            self.index = (self.index + 1) % self.formats.len();
+            self.block.set_format(&self.formats[self.index])?;
+            if let Some(ref path) = self.index_file {
+                fs::write(path, self.index.to_string())
+                    .block_error("formats", "failed to write formats_file")?;
+            }
+            self.block.update()?;
+            return Ok(());
+        }
+        self.block.click(event)
+    }
+
+    fn set_format(&mut self, format: &str) -> Result<()> {
+        self.block.set_format(format)
+    }
+}
+
+/// Wraps every block to apply the common options that concern the block as a
+/// whole: the `markup_escape` of the values it renders, and `notify_match`,
+/// which is checked against the text of its widgets once after every update.
+struct CommonOptions {
+    block: Box<dyn Block>,
+    common: CommonBlockConfig,
+    markup_escape: MarkupEscape,
+}
+
+impl Block for CommonOptions {
+    fn id(&self) -> &str {
+        self.block.id()
+    }
+
+    fn view(&self) -> Vec<&dyn I3BarWidget> {
+        self.block.view()
+    }
+
+    fn update(&mut self) -> Result<Option<Update>> {
+        let block = &mut self.block;
+        let update = self.markup_escape.scope(|| block.update())?;
+        for widget in self.block.view() {
+            self.common.check_notify(widget.formatted_text());
+        }
+        Ok(update)
+    }
+
+    fn signal(&mut self, signal: i32) -> Result<()> {
+        let block = &mut self.block;
+        self.markup_escape.scope(|| block.signal(signal))
+    }
+
+    fn click(&mut self, event: &I3BarEvent) -> Result<()> {
+        let block = &mut self.block;
+        self.markup_escape.scope(|| block.click(event))
+    }
+
+    fn set_format(&mut self, format: &str) -> Result<()> {
+        let block = &mut self.block;
+        self.markup_escape.scope(|| block.set_format(format))
+    }
 }
 
 pub trait ConfigBlock: Block {
@@ -153,15 +308,30 @@ macro_rules! block {
 
 pub fn create_block(
     name: &str,
-    block_config: Value,
-    config: Config,
+    mut block_config: Value,
+    mut config: Config,
     update_request: Sender<Task>,
 ) -> Result<Box<dyn Block>> {
-    match name {
+    config.block = CommonBlockConfig::extract(&mut block_config)?;
+    if let Some(rtl) = config.block.rtl {
+        config.rtl = rtl;
+    }
+    if let Some(markup_escape) = config.block.markup_escape {
+        config.markup_escape = markup_escape;
+    }
+    let formats = config.block.formats.clone();
+    let formats_file = config.block.formats_file.clone();
+    let common = config.block.clone();
+    let markup_escape = config.markup_escape;
+
+    let block = markup_escape.scope(|| match name {
         // Please keep these in alphabetical order.
+        "ac_adapter" => block!(AcAdapter, block_config, config, update_request),
         "backlight" => block!(Backlight, block_config, config, update_request),
         "battery" => block!(Battery, block_config, config, update_request),
+        "binding_mode" => block!(BindingMode, block_config, config, update_request),
         "bluetooth" => block!(Bluetooth, block_config, config, update_request),
+        "compose" => block!(Compose, block_config, config, update_request),
         "cpu" => block!(Cpu, block_config, config, update_request),
         "custom" => block!(Custom, block_config, config, update_request),
         "custom_dbus" => block!(CustomDBus, block_config, config, update_request),
@@ -169,6 +339,7 @@ pub fn create_block(
         "docker" => block!(Docker, block_config, config, update_request),
         "focused_window" => block!(FocusedWindow, block_config, config, update_request),
         "github" => block!(Github, block_config, config, update_request),
+        "gpu" => block!(Gpu, block_config, config, update_request),
         "ibus" => block!(IBus, block_config, config, update_request),
         "kdeconnect" => block!(KDEConnect, block_config, config, update_request),
         "keyboard_layout" => block!(KeyboardLayout, block_config, config, update_request),
@@ -184,8 +355,10 @@ pub fn create_block(
         "nvidia_gpu" => block!(NvidiaGpu, block_config, config, update_request),
         "pacman" => block!(Pacman, block_config, config, update_request),
         "pomodoro" => block!(Pomodoro, block_config, config, update_request),
+        "rapl" => block!(Rapl, block_config, config, update_request),
         "sound" => block!(Sound, block_config, config, update_request),
         "speedtest" => block!(SpeedTest, block_config, config, update_request),
+        "systemd" => block!(Systemd, block_config, config, update_request),
         "taskwarrior" => block!(Taskwarrior, block_config, config, update_request),
         "temperature" => block!(Temperature, block_config, config, update_request),
         "template" => block!(Template, block_config, config, update_request),
@@ -195,7 +368,18 @@ pub fn create_block(
         "watson" => block!(Watson, block_config, config, update_request),
         "weather" => block!(Weather, block_config, config, update_request),
         "xrandr" => block!(Xrandr, block_config, config, update_request),
+        "xrun" => block!(Xrun, block_config, config, update_request),
         "hueshift" => block!(Hueshift, block_config, config, update_request),
         other => Err(BlockError(other.to_string(), "Unknown block!".to_string())),
-    }
+    })?;
+
+    let block: Box<dyn Block> = match formats {
+        Some(formats) => Box::new(FormatCycle::new(block, formats, formats_file)?),
+        None => block,
+    };
+    Ok(Box::new(CommonOptions {
+        block,
+        common,
+        markup_escape,
+    }))
 }
@@ -0,0 +1,136 @@
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::time::Duration;
+
+use crossbeam_channel::Sender;
+use serde_derive::Deserialize;
+use uuid::Uuid;
+
+use crate::blocks::{Block, ConfigBlock, Update};
+use crate::config::Config;
+use crate::de::deserialize_duration;
+use crate::errors::*;
+use crate::scheduler::Task;
+use crate::util::{read_sysfs, FormatTemplate};
+use crate::watch::watch_device;
+use crate::widget::{I3BarWidget, State};
+use crate::widgets::text::TextWidget;
+
+const POWER_SUPPLY_PATH: &str = "/sys/class/power_supply";
+
+pub struct AcAdapter {
+    id: String,
+    output: TextWidget,
+    device: PathBuf,
+    format: FormatTemplate,
+    update_interval: Duration,
+}
+
+#[derive(Deserialize, Debug, Default, Clone)]
+#[serde(deny_unknown_fields)]
+pub struct AcAdapterConfig {
+    /// Update interval in seconds, in addition to the updates on plug events
+    #[serde(
+        default = "AcAdapterConfig::default_interval",
+        deserialize_with = "deserialize_duration"
+    )]
+    pub interval: Duration,
+
+    /// Name of the power supply, the first mains supply by default
+    #[serde(default)]
+    pub device: Option<String>,
+
+    /// Format override
+    #[serde(default = "AcAdapterConfig::default_format")]
+    pub format: String,
+}
+
+impl AcAdapterConfig {
+    fn default_interval() -> Duration {
+        Duration::from_secs(60)
+    }
+
+    fn default_format() -> String {
+        "".to_owned()
+    }
+}
+
+/// Finds the first power supply of type "Mains", such as AC or ADP1.
+fn find_mains() -> Result<PathBuf> {
+    let mut supplies: Vec<PathBuf> = fs::read_dir(POWER_SUPPLY_PATH)
+        .block_error(
+            "ac_adapter",
+            &format!("failed to read {}", POWER_SUPPLY_PATH),
+        )?
+        .filter_map(|entry| entry.ok())
+        .map(|entry| entry.path())
+        .collect();
+    supplies.sort();
+    supplies
+        .into_iter()
+        .find(|path| {
+            read_sysfs::<String>("ac_adapter", &path.join("type"))
+                .map(|kind| kind == "Mains")
+                .unwrap_or(false)
+        })
+        .block_error("ac_adapter", "no AC adapter found")
+}
+
+impl ConfigBlock for AcAdapter {
+    type Config = AcAdapterConfig;
+
+    fn new(
+        block_config: Self::Config,
+        config: Config,
+        tx_update_request: Sender<Task>,
+    ) -> Result<Self> {
+        let id = Uuid::new_v4().to_simple().to_string();
+        let device = match block_config.device {
+            Some(device) => Path::new(POWER_SUPPLY_PATH).join(device),
+            None => find_mains()?,
+        };
+        watch_device(&device, &id, &tx_update_request);
+
+        Ok(AcAdapter {
+            id,
+            output: TextWidget::new(config),
+            device,
+            format: FormatTemplate::from_string(&block_config.format)
+                .block_error("ac_adapter", "Invalid format specified")?,
+            update_interval: block_config.interval,
+        })
+    }
+}
+
+impl Block for AcAdapter {
+    fn update(&mut self) -> Result<Option<Update>> {
+        let online = read_sysfs::<u8>("ac_adapter", &self.device.join("online"))? == 1;
+
+        let values = map!("{online}" => if online { "on" } else { "off" });
+        self.output
+            .set_text(self.format.render_static_str(&values)?);
+        if online {
+            self.output.set_icon("ac_online");
+            self.output.set_state(State::Good);
+        } else {
+            self.output.set_icon("ac_offline");
+            self.output.set_state(State::Idle);
+        }
+
+        Ok(Some(self.update_interval.into()))
+    }
+
+    fn view(&self) -> Vec<&dyn I3BarWidget> {
+        vec![&self.output]
+    }
+
+    fn set_format(&mut self, format: &str) -> Result<()> {
+        self.format = FormatTemplate::from_string(format)
+            .block_error("ac_adapter", "Invalid format specified")?;
+        Ok(())
+    }
+
+    fn id(&self) -> &str {
+        &self.id
+    }
+}
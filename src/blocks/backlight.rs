@@ -10,11 +10,9 @@
 use std::fs::OpenOptions;
 use std::io::prelude::*;
 use std::path::{Path, PathBuf};
-use std::thread;
-use std::time::{Duration, Instant};
+use std::time::Duration;
 
 use crossbeam_channel::Sender;
-use inotify::{EventMask, Inotify, WatchMask};
 use serde_derive::Deserialize;
 use uuid::Uuid;
 
@@ -24,23 +22,14 @@ use crate::config::{Config, LogicalDirection, Scrolling};
 use crate::errors::*;
 use crate::input::I3BarEvent;
 use crate::scheduler::Task;
+use crate::util::read_sysfs;
+use crate::watch::{watch_device, watch_file};
 use crate::widget::I3BarWidget;
 use crate::widgets::button::ButtonWidget;
 
 /// Read a brightness value from the given path.
 fn read_brightness(device_file: &Path) -> Result<u64> {
-    let mut file = OpenOptions::new()
-        .read(true)
-        .open(device_file)
-        .block_error("backlight", "Failed to open brightness file")?;
-    let mut content = String::new();
-    file.read_to_string(&mut content)
-        .block_error("backlight", "Failed to read brightness file")?;
-    // Removes trailing newline.
-    content.pop();
-    content
-        .parse::<u64>()
-        .block_error("backlight", "Failed to read value from brightness file")
+    read_sysfs("backlight", device_file)
 }
 
 /// Represents a physical backlit device whose brightness level can be queried.
@@ -246,48 +235,26 @@ impl ConfigBlock for Backlight {
         }?;
 
         let id = Uuid::new_v4().to_simple().to_string();
-        let brightness_file = device.brightness_file();
+        // Brightness changes made by the firmware, e.g. on hotkeys, are
+        // announced as uevents, while writes by other programs are only
+        // noticed by watching the brightness file.
+        watch_device(&device.device_path, &id, &tx_update_request);
+        watch_file(
+            device.brightness_file(),
+            id.clone(),
+            tx_update_request,
+            Duration::from_millis(250),
+        )?;
 
         let scrolling = config.scrolling;
         let backlight = Backlight {
             output: ButtonWidget::new(config, &id),
-            id: id.clone(),
+            id,
             device,
             step_width: block_config.step_width,
             scrolling,
         };
 
-        // Spin up a thread to watch for changes to the brightness file for the
-        // device, and schedule an update if needed.
-        thread::Builder::new()
-            .name("backlight".into())
-            .spawn(move || {
-                let mut notify = Inotify::init().expect("Failed to start inotify");
-                notify
-                    .add_watch(brightness_file, WatchMask::MODIFY)
-                    .expect("Failed to watch brightness file");
-
-                let mut buffer = [0; 1024];
-                loop {
-                    let mut events = notify
-                        .read_events_blocking(&mut buffer)
-                        .expect("Error while reading inotify events");
-
-                    if events.any(|event| event.mask.contains(EventMask::MODIFY)) {
-                        tx_update_request
-                            .send(Task {
-                                id: id.clone(),
-                                update_time: Instant::now(),
-                            })
-                            .unwrap();
-                    }
-
-                    // Avoid update spam.
-                    thread::sleep(Duration::from_millis(250))
-                }
-            })
-            .unwrap();
-
         Ok(backlight)
     }
 }
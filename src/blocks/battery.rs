@@ -4,7 +4,10 @@
 //! display the status, capacity, and time remaining for (dis)charge for an
 //! internal power supply.
 
+use std::collections::HashMap;
+use std::fs;
 use std::path::{Path, PathBuf};
+use std::process::{Command, Stdio};
 use std::thread;
 use std::time::{Duration, Instant};
 
@@ -19,10 +22,14 @@ use crate::blocks::{Block, ConfigBlock};
 use crate::config::Config;
 use crate::de::deserialize_duration;
 use crate::errors::*;
+use crate::input::{I3BarEvent, MouseButton};
 use crate::scheduler::Task;
-use crate::util::{battery_level_to_icon, format_percent_bar, read_file, FormatTemplate};
+use crate::util::{
+    battery_level_to_icon, format_percent_bar, read_file, read_sysfs, FormatTemplate,
+};
+use crate::watch::watch_device;
 use crate::widget::{I3BarWidget, State};
-use crate::widgets::text::TextWidget;
+use crate::widgets::button::ButtonWidget;
 
 /// A battery device can be queried for a few properties relevant to the user.
 pub trait BatteryDevice {
@@ -104,22 +111,20 @@ impl BatteryDevice for PowerSupplyDevice {
 
         // Read charge_full exactly once, if it exists, units are µAh
         self.charge_full = if self.device_path.join("charge_full").exists() {
-            Some(
-                read_file("battery", &self.device_path.join("charge_full"))?
-                    .parse::<u64>()
-                    .block_error("battery", "failed to parse charge_full")?,
-            )
+            Some(read_sysfs::<u64>(
+                "battery",
+                &self.device_path.join("charge_full"),
+            )?)
         } else {
             None
         };
 
         // Read energy_full exactly once, if it exists. Units are µWh.
         self.energy_full = if self.device_path.join("energy_full").exists() {
-            Some(
-                read_file("battery", &self.device_path.join("energy_full"))?
-                    .parse::<u64>()
-                    .block_error("battery", "failed to parse energy_full")?,
-            )
+            Some(read_sysfs::<u64>(
+                "battery",
+                &self.device_path.join("energy_full"),
+            )?)
         } else {
             None
         };
@@ -137,18 +142,12 @@ impl BatteryDevice for PowerSupplyDevice {
         let energy_path = self.device_path.join("energy_now");
 
         let capacity = if capacity_path.exists() {
-            read_file("battery", &capacity_path)?
-                .parse::<u64>()
-                .block_error("battery", "failed to parse capacity")?
+            read_sysfs::<u64>("battery", &capacity_path)?
         } else if charge_path.exists() && self.charge_full.is_some() {
-            let charge = read_file("battery", &charge_path)?
-                .parse::<u64>()
-                .block_error("battery", "failed to parse charge_now")?;
+            let charge = read_sysfs::<u64>("battery", &charge_path)?;
             ((charge as f64 / self.charge_full.unwrap() as f64) * 100.0) as u64
         } else if energy_path.exists() && self.energy_full.is_some() {
-            let charge = read_file("battery", &energy_path)?
-                .parse::<u64>()
-                .block_error("battery", "failed to parse energy_now")?;
+            let charge = read_sysfs::<u64>("battery", &energy_path)?;
             ((charge as f64 / self.energy_full.unwrap() as f64) * 100.0) as u64
         } else {
             return Err(BlockError(
@@ -169,9 +168,7 @@ impl BatteryDevice for PowerSupplyDevice {
     fn time_remaining(&self) -> Result<u64> {
         let time_to_empty_now_path = self.device_path.join("time_to_empty_now");
         let time_to_empty = if time_to_empty_now_path.exists() {
-            read_file("battery", &time_to_empty_now_path)?
-                .parse::<u64>()
-                .block_error("battery", "failed to parse time to empty")
+            read_sysfs::<u64>("battery", &time_to_empty_now_path)
         } else {
             Err(BlockError(
                 "battery".to_string(),
@@ -180,9 +177,7 @@ impl BatteryDevice for PowerSupplyDevice {
         };
         let time_to_full_now_path = self.device_path.join("time_to_full_now");
         let time_to_full = if time_to_full_now_path.exists() {
-            read_file("battery", &time_to_full_now_path)?
-                .parse::<u64>()
-                .block_error("battery", "failed to parse time to full")
+            read_sysfs::<u64>("battery", &time_to_full_now_path)
         } else {
             Err(BlockError(
                 "battery".to_string(),
@@ -203,13 +198,9 @@ impl BatteryDevice for PowerSupplyDevice {
         let energy_path = self.device_path.join("energy_now");
         let charge_path = self.device_path.join("charge_now");
         let fill = if energy_path.exists() {
-            read_file("battery", &energy_path)?
-                .parse::<f64>()
-                .block_error("battery", "failed to parse energy_now")
+            read_sysfs::<f64>("battery", &energy_path)
         } else if charge_path.exists() {
-            read_file("battery", &charge_path)?
-                .parse::<f64>()
-                .block_error("battery", "failed to parse charge_now")
+            read_sysfs::<f64>("battery", &charge_path)
         } else {
             Err(BlockError(
                 "battery".to_string(),
@@ -220,13 +211,9 @@ impl BatteryDevice for PowerSupplyDevice {
         let power_path = self.device_path.join("power_now");
         let current_path = self.device_path.join("current_now");
         let usage = if power_path.exists() {
-            read_file("battery", &power_path)?
-                .parse::<f64>()
-                .block_error("battery", "failed to parse power_now")
+            read_sysfs::<f64>("battery", &power_path)
         } else if current_path.exists() {
-            read_file("battery", &current_path)?
-                .parse::<f64>()
-                .block_error("battery", "failed to parse current_now")
+            read_sysfs::<f64>("battery", &current_path)
         } else {
             Err(BlockError(
                 "battery".to_string(),
@@ -285,16 +272,10 @@ impl BatteryDevice for PowerSupplyDevice {
         let voltage_path = self.device_path.join("voltage_now");
 
         if power_path.exists() {
-            Ok(read_file("battery", &power_path)?
-                .parse::<u64>()
-                .block_error("battery", "failed to parse power_now")?)
+            read_sysfs::<u64>("battery", &power_path)
         } else if current_path.exists() && voltage_path.exists() {
-            let current = read_file("battery", &current_path)?
-                .parse::<u64>()
-                .block_error("battery", "failed to parse current_now")?;
-            let voltage = read_file("battery", &voltage_path)?
-                .parse::<u64>()
-                .block_error("battery", "failed to parse voltage_now")?;
+            let current = read_sysfs::<u64>("battery", &current_path)?;
+            let voltage = read_sysfs::<u64>("battery", &voltage_path)?;
             Ok((current * voltage) / 1_000_000)
         } else {
             Err(BlockError(
@@ -333,10 +314,14 @@ impl UpowerDevice {
                 "EnumerateDevices",
             )
             .unwrap();
-            let dbus_reply = con.send_with_reply_and_block(msg, 2000).unwrap();
+            let dbus_reply = con
+                .send_with_reply_and_block(msg, 2000)
+                .block_error("battery", "Failed to enumerate UPower devices.")?;
 
             // EnumerateDevices returns one argument, which is an array of ObjectPaths (not dbus::tree:ObjectPath).
-            let mut paths: Array<dbus::Path, _> = dbus_reply.get1().unwrap();
+            let mut paths: Array<dbus::Path, _> = dbus_reply
+                .get1()
+                .block_error("battery", "Failed to read UPower devices.")?;
             let path = paths.find(|entry| entry.ends_with(device));
             if path.is_none() {
                 return Err(BlockError(
@@ -362,6 +347,15 @@ impl UpowerDevice {
         Ok(UpowerDevice { device_path, con })
     }
 
+    /// The model name of the device, e.g. of a Bluetooth headset.
+    pub fn model(&self) -> Option<String> {
+        self.con
+            .with_path("org.freedesktop.UPower", &self.device_path, 1000)
+            .get::<String>("org.freedesktop.UPower.Device", "Model")
+            .ok()
+            .filter(|model| !model.is_empty())
+    }
+
     /// Monitor UPower property changes in a separate thread and send updates
     /// via the `update_request` channel.
     pub fn monitor(&self, id: String, update_request: Sender<Task>) {
@@ -473,12 +467,103 @@ impl BatteryDevice for UpowerDevice {
     }
 }
 
+/// The sysfs knob limiting how far a battery is charged, to extend its lifespan.
+pub enum ConservationControl {
+    /// `charge_control_end_threshold` of the power supply (e.g. ThinkPads),
+    /// conservation is active when charging stops below 100%.
+    EndThreshold(PathBuf),
+    /// `conservation_mode` of the `ideapad_acpi` driver, either `0` or `1`.
+    IdeapadMode(PathBuf),
+}
+
+impl ConservationControl {
+    /// Find the conservation control for the power supply `device`, if the
+    /// hardware supports one.
+    pub fn find(device: &str) -> Option<Self> {
+        let threshold_path = Path::new("/sys/class/power_supply")
+            .join(device)
+            .join("charge_control_end_threshold");
+        if threshold_path.exists() {
+            return Some(ConservationControl::EndThreshold(threshold_path));
+        }
+
+        fs::read_dir("/sys/bus/platform/drivers/ideapad_acpi")
+            .ok()?
+            .filter_map(|entry| entry.ok())
+            .map(|entry| entry.path().join("conservation_mode"))
+            .find(|path| path.exists())
+            .map(ConservationControl::IdeapadMode)
+    }
+
+    fn path(&self) -> &Path {
+        match self {
+            ConservationControl::EndThreshold(path) => path,
+            ConservationControl::IdeapadMode(path) => path,
+        }
+    }
+
+    /// Query whether charge conservation is currently active.
+    pub fn is_active(&self) -> Result<bool> {
+        let value = read_sysfs::<u64>("battery", self.path())?;
+        Ok(match self {
+            ConservationControl::EndThreshold(_) => value < 100,
+            ConservationControl::IdeapadMode(_) => value == 1,
+        })
+    }
+
+    /// Write the new conservation state through `helper` (e.g. `pkexec`),
+    /// since the sysfs files are usually only writable by root. The helper may
+    /// wait for a password, so it is waited for in the background, after
+    /// which the block `id` is updated to show the new state.
+    pub fn set_active(
+        &self,
+        active: bool,
+        threshold: u64,
+        helper: &str,
+        id: String,
+        update_request: Sender<Task>,
+    ) -> Result<()> {
+        let value = match self {
+            ConservationControl::EndThreshold(_) if active => threshold,
+            ConservationControl::EndThreshold(_) => 100,
+            ConservationControl::IdeapadMode(_) => active as u64,
+        };
+        let write_cmd = format!("echo {} > {}", value, self.path().to_string_lossy());
+
+        let mut args: Vec<&str> = helper.split_whitespace().collect();
+        args.extend(&["sh", "-c", &write_cmd]);
+        let mut child = Command::new(args[0])
+            .args(&args[1..])
+            .stdin(Stdio::null())
+            .stdout(Stdio::null())
+            .spawn()
+            .block_error("battery", "failed to spawn charge conservation helper")?;
+        thread::Builder::new()
+            .name("battery".into())
+            .spawn(move || {
+                child.wait().ok();
+                update_request
+                    .send(Task {
+                        id,
+                        update_time: Instant::now(),
+                    })
+                    .ok();
+            })
+            .unwrap();
+        Ok(())
+    }
+}
+
 /// A block for displaying information about an internal power supply.
 pub struct Battery {
-    output: TextWidget,
+    output: ButtonWidget,
     id: String,
     update_interval: Duration,
-    device: Box<dyn BatteryDevice>,
+    /// The configured devices and their names, only `active` is displayed
+    devices: Vec<(String, Box<dyn BatteryDevice>)>,
+    active: usize,
+    /// UPower devices that only raise the state, connected when they appear
+    also_warn_devices: Vec<(String, Option<UpowerDevice>)>,
     format: FormatTemplate,
     full_format: FormatTemplate,
     missing_format: FormatTemplate,
@@ -489,6 +574,12 @@ pub struct Battery {
     info: u64,
     warning: u64,
     critical: u64,
+    conservation: Option<ConservationControl>,
+    conservation_threshold: u64,
+    conservation_helper: String,
+    conservation_icon: String,
+    icons: HashMap<String, String>,
+    update_request: Sender<Task>,
 }
 
 #[derive(Deserialize, Debug, Clone)]
@@ -520,6 +611,15 @@ pub struct BatteryConfig {
     #[serde(default = "BatteryConfig::default_device")]
     pub device: String,
 
+    /// Several devices to cycle through on click, replacing `device`.
+    #[serde(default)]
+    pub devices: Vec<String>,
+
+    /// UPower devices, e.g. Bluetooth peripherals, that are not displayed but
+    /// raise the state of the block when their charge is low.
+    #[serde(default)]
+    pub also_warn_devices: Vec<String>,
+
     /// (DEPRECATED) Options for displaying battery information.
     #[serde()]
     pub show: Option<String>,
@@ -569,6 +669,19 @@ pub struct BatteryConfig {
     /// If the battery device cannot be found, completely hide this block.
     #[serde(default = "BatteryConfig::default_hide_missing")]
     pub hide_missing: bool,
+
+    /// Show the charge conservation state and toggle it on click.
+    #[serde(default = "BatteryConfig::default_conservation")]
+    pub conservation: bool,
+
+    /// The charge level charging stops at while conservation is active
+    /// (`charge_control_end_threshold` only).
+    #[serde(default = "BatteryConfig::default_conservation_threshold")]
+    pub conservation_threshold: u64,
+
+    /// Command prefix used to gain the privileges to toggle conservation.
+    #[serde(default = "BatteryConfig::default_conservation_helper")]
+    pub conservation_helper: String,
 }
 
 impl BatteryConfig {
@@ -619,6 +732,18 @@ impl BatteryConfig {
     fn default_hide_missing() -> bool {
         false
     }
+
+    fn default_conservation() -> bool {
+        false
+    }
+
+    fn default_conservation_threshold() -> u64 {
+        80
+    }
+
+    fn default_conservation_helper() -> String {
+        "pkexec".to_string()
+    }
 }
 
 impl ConfigBlock for Battery {
@@ -650,23 +775,65 @@ impl ConfigBlock for Battery {
         };
 
         let id = Uuid::new_v4().to_simple().to_string();
-        let device: Box<dyn BatteryDevice> = match driver {
-            BatteryDriver::Upower => {
-                let out = UpowerDevice::from_device(&block_config.device)?;
-                out.monitor(id.clone(), update_request);
-                Box::new(out)
-            }
-            BatteryDriver::Sysfs => Box::new(PowerSupplyDevice::from_device(
-                &block_config.device,
-                block_config.allow_missing,
-            )?),
+        let names = if block_config.devices.is_empty() {
+            vec![block_config.device.clone()]
+        } else {
+            block_config.devices
         };
+        let mut devices: Vec<(String, Box<dyn BatteryDevice>)> = Vec::new();
+        for name in names {
+            let device: Box<dyn BatteryDevice> = match driver {
+                BatteryDriver::Upower => {
+                    let out = UpowerDevice::from_device(&name)?;
+                    out.monitor(id.clone(), update_request.clone());
+                    Box::new(out)
+                }
+                BatteryDriver::Sysfs => {
+                    let out = PowerSupplyDevice::from_device(&name, block_config.allow_missing)?;
+                    // Update right away when the status changes, e.g. on plugging in
+                    watch_device(&out.device_path, &id, &update_request);
+                    Box::new(out)
+                }
+            };
+            devices.push((name, device));
+        }
+
+        if block_config.conservation_threshold == 0 || block_config.conservation_threshold > 100 {
+            return Err(ConfigurationError(
+                "battery".to_string(),
+                (
+                    "conservation_threshold must be between 1 and 100".to_string(),
+                    "invalid conservation_threshold".to_string(),
+                ),
+            ));
+        }
+
+        let conservation = if block_config.conservation {
+            Some(
+                ConservationControl::find(&devices[0].0)
+                    .block_error("battery", "Device does not support charge conservation")?,
+            )
+        } else {
+            None
+        };
+        let conservation_icon = config
+            .icons
+            .get("bat_conservation")
+            .map(|icon| icon.trim().to_string())
+            .unwrap_or_default();
 
         Ok(Battery {
+            icons: config.icons.clone(),
+            output: ButtonWidget::new(config, &id),
             id,
             update_interval: block_config.interval,
-            output: TextWidget::new(config),
-            device,
+            devices,
+            active: 0,
+            also_warn_devices: block_config
+                .also_warn_devices
+                .into_iter()
+                .map(|name| (name, None))
+                .collect(),
             format: FormatTemplate::from_string(&format)?,
             full_format: FormatTemplate::from_string(&block_config.full_format)?,
             missing_format: FormatTemplate::from_string(&block_config.missing_format)?,
@@ -677,17 +844,127 @@ impl ConfigBlock for Battery {
             info: block_config.info,
             warning: block_config.warning,
             critical: block_config.critical,
+            conservation,
+            conservation_threshold: block_config.conservation_threshold,
+            conservation_helper: block_config.conservation_helper,
+            conservation_icon,
+            update_request,
         })
     }
 }
 
+impl Battery {
+    fn device(&self) -> &dyn BatteryDevice {
+        self.devices[self.active].1.as_ref()
+    }
+
+    /// The state of a device with the given status and capacity.
+    fn state(&self, status: &str, capacity: &Result<u64>) -> State {
+        match status {
+            "Full" | "Not charging" | "Charging" => State::Good,
+            _ => match capacity {
+                Ok(capacity) => {
+                    if *capacity <= self.critical {
+                        State::Critical
+                    } else if *capacity <= self.warning {
+                        State::Warning
+                    } else if *capacity <= self.info {
+                        State::Info
+                    } else if *capacity > self.good {
+                        State::Good
+                    } else {
+                        State::Idle
+                    }
+                }
+                Err(_) => State::Warning,
+            },
+        }
+    }
+
+    /// The most severe warning among the devices that are not displayed, so
+    /// that a dying mouse is noticed while the laptop battery is shown, along
+    /// with the name of the device that raised it. The other `devices` are
+    /// only read with the sysfs driver, under UPower the devices to warn about
+    /// are listed in `also_warn_devices`.
+    fn background_state(&mut self) -> Option<(State, String)> {
+        let mut readings = Vec::new();
+        let inactive_devices = match self.driver {
+            BatteryDriver::Sysfs => &mut self.devices[..],
+            BatteryDriver::Upower => &mut [],
+        };
+        for (index, (name, device)) in inactive_devices.iter_mut().enumerate() {
+            if index == self.active
+                || !device.is_available()
+                || device.refresh_device_info().is_err()
+            {
+                continue;
+            }
+            if let Ok(status) = device.status() {
+                readings.push((name.clone(), status, device.capacity()));
+            }
+        }
+
+        for (name, slot) in self.also_warn_devices.iter_mut() {
+            // Peripherals come and go, look them up again while they are away
+            if slot.is_none() {
+                *slot = UpowerDevice::from_device(name).ok();
+            }
+            let device = match slot {
+                Some(device) => device,
+                None => continue,
+            };
+            match device.status() {
+                Ok(status) => {
+                    let label = device.model().unwrap_or_else(|| name.clone());
+                    readings.push((label, status, device.capacity()));
+                }
+                Err(_) => *slot = None,
+            }
+        }
+
+        let mut worst = None;
+        for (name, status, capacity) in readings {
+            match self.state(&status, &capacity) {
+                State::Critical => return Some((State::Critical, name)),
+                State::Warning if worst.is_none() => worst = Some((State::Warning, name)),
+                _ => {}
+            }
+        }
+        worst
+    }
+
+    /// Picks the icon for the battery status and level. While the battery is
+    /// plugged in, an icon combining the level with the charging, plugged or
+    /// full overlay is used if the icon set has one, e.g. `bat_charging_half`
+    /// or `bat_full_three_quarters`. Otherwise this falls back to the plain
+    /// status icons.
+    fn icon(&self, status: &str, level: &str) -> String {
+        let (overlay, fallback) = match status {
+            "Charging" => ("bat_charging", "bat_charging"),
+            "Not charging" => ("bat_plugged", "bat_full"),
+            // Reported below the full level when a charge threshold stopped
+            // charging early, `bat_full_full` is just `bat_full`
+            "Full" => ("bat_full", "bat_full"),
+            _ => return level.to_string(),
+        };
+
+        let combined = level.replacen("bat", overlay, 1);
+        if self.icons.contains_key(&combined) {
+            combined
+        } else {
+            fallback.to_string()
+        }
+    }
+}
+
 impl Block for Battery {
     fn update(&mut self) -> Result<Option<Update>> {
         // TODO: Maybe use dbus to immediately signal when the battery state changes.
 
         // Exit early, if the battery device went missing, but the user
         // allows this device to go missing.
-        if !self.device.is_available() && self.allow_missing {
+        let name = self.devices[self.active].0.clone();
+        if !self.device().is_available() && self.allow_missing {
             // Respect the original format string, even if the battery
             // cannot be found right now.
             let empty_percent_bar = format_percent_bar(0.0);
@@ -695,7 +972,10 @@ impl Block for Battery {
                 "{percentage}" => "X",
                 "{bar}" => &empty_percent_bar,
                 "{time}" => "xx:xx",
-                "{power}" => "N/A"
+                "{power}" => "N/A",
+                "{conservation}" => "",
+                "{device}" => &name,
+                "{warn_device}" => ""
             );
 
             self.output.set_icon("bat_not_available");
@@ -711,10 +991,10 @@ impl Block for Battery {
 
         // The device may have gone missing
         // It may be a different battery now, thereby refresh the device specs.
-        self.device.refresh_device_info()?;
+        self.devices[self.active].1.refresh_device_info()?;
 
-        let status = self.device.status()?;
-        let capacity = self.device.capacity();
+        let status = self.device().status()?;
+        let capacity = self.device().capacity();
         let percentage = match capacity {
             Ok(capacity) => format!("{}", capacity),
             Err(_) => "×".into(),
@@ -723,7 +1003,7 @@ impl Block for Battery {
             Ok(capacity) => format_percent_bar(capacity as f32),
             Err(_) => "×".into(),
         };
-        let time = match self.device.time_remaining() {
+        let time = match self.device().time_remaining() {
             Ok(time) => match time {
                 0 => "".into(),
                 _ => format!("{}:{:02}", time / 60, time % 60),
@@ -731,72 +1011,95 @@ impl Block for Battery {
             Err(_) => "×".into(),
         };
         // convert µW to W for display
-        let power = match self.device.power_consumption() {
+        let power = match self.device().power_consumption() {
             Ok(power) => format!("{:.2}", power as f64 / 1000.0 / 1000.0),
             Err(_) => "×".into(),
         };
+        let conservation = match self.conservation {
+            Some(ref conservation) if conservation.is_active()? => self.conservation_icon.clone(),
+            _ => "".into(),
+        };
+        let state = self.state(&status, &capacity);
+        let background_state = self.background_state();
+        let warn_device = match background_state {
+            Some((_, ref device)) => device.clone(),
+            None => "".into(),
+        };
         let values = map!("{percentage}" => percentage,
                             "{bar}" => bar,
                             "{time}" => time,
-                            "{power}" => power);
+                            "{power}" => power,
+                            "{conservation}" => conservation,
+                            "{device}" => name,
+                            "{warn_device}" => warn_device);
 
         if status == "Full" || status == "Not charging" {
-            self.output.set_icon("bat_full");
             self.output
                 .set_text(self.full_format.render_static_str(&values)?);
-            self.output.set_state(State::Good);
         } else {
             self.output
                 .set_text(self.format.render_static_str(&values)?);
+        }
 
-            // Check if the battery is in charging mode and change the state to Good.
-            // Otherwise, adjust the state depeding the power percentance.
-            match status.as_str() {
-                "Charging" => {
-                    self.output.set_state(State::Good);
-                }
-                _ => {
-                    self.output.set_state(match capacity {
-                        Ok(capacity) => {
-                            if capacity <= self.critical {
-                                State::Critical
-                            } else if capacity <= self.warning {
-                                State::Warning
-                            } else if capacity <= self.info {
-                                State::Info
-                            } else if capacity > self.good {
-                                State::Good
-                            } else {
-                                State::Idle
-                            }
-                        }
-                        Err(_) => State::Warning,
-                    });
-                }
-            }
+        self.output.set_state(match (background_state, state) {
+            (Some((State::Critical, _)), _) | (_, State::Critical) => State::Critical,
+            (Some((State::Warning, _)), _) => State::Warning,
+            (_, state) => state,
+        });
 
-            self.output.set_icon(match status.as_str() {
-                "Discharging" => battery_level_to_icon(capacity),
-                "Charging" => "bat_charging",
-                _ => battery_level_to_icon(capacity),
-            });
-        }
+        let icon = self.icon(&status, battery_level_to_icon(capacity));
+        self.output.set_icon(&icon);
 
         match self.driver {
-            BatteryDriver::Sysfs => Ok(Some(self.update_interval.into())),
-            BatteryDriver::Upower => Ok(None),
+            BatteryDriver::Upower if self.also_warn_devices.is_empty() => Ok(None),
+            // The peripherals are polled, they are not monitored
+            _ => Ok(Some(self.update_interval.into())),
         }
     }
 
     fn view(&self) -> Vec<&dyn I3BarWidget> {
         // Don't display the block at all, if it's configured to be hidden on missing batteries
-        if !self.device.is_available() && self.hide_missing {
+        if !self.device().is_available() && self.hide_missing {
             return Vec::new();
         }
 
         vec![&self.output]
     }
 
+    fn set_format(&mut self, format: &str) -> Result<()> {
+        self.format = FormatTemplate::from_string(format)?;
+        Ok(())
+    }
+
+    fn click(&mut self, event: &I3BarEvent) -> Result<()> {
+        if !event.matches_name(&self.id) {
+            return Ok(());
+        }
+
+        // Conservation moves to the right button when the left one cycles
+        let conservation_button = if self.devices.len() > 1 {
+            MouseButton::Right
+        } else {
+            MouseButton::Left
+        };
+        if self.devices.len() > 1 && event.button == MouseButton::Left {
+            self.active = (self.active + 1) % self.devices.len();
+            self.update()?;
+        } else if event.button == conservation_button {
+            if let Some(ref conservation) = self.conservation {
+                conservation.set_active(
+                    !conservation.is_active()?,
+                    self.conservation_threshold,
+                    &self.conservation_helper,
+                    self.id.clone(),
+                    self.update_request.clone(),
+                )?;
+            }
+        }
+
+        Ok(())
+    }
+
     fn id(&self) -> &str {
         &self.id
     }
@@ -0,0 +1,152 @@
+use std::sync::{Arc, Mutex};
+use std::thread;
+use std::time::Instant;
+
+use crossbeam_channel::Sender;
+use serde_derive::Deserialize;
+use swayipc::reply::Event;
+use swayipc::{Connection, EventType};
+use uuid::Uuid;
+
+use crate::blocks::{Block, ConfigBlock, Update};
+use crate::config::Config;
+use crate::errors::*;
+use crate::scheduler::Task;
+use crate::util::FormatTemplate;
+use crate::widget::{I3BarWidget, State};
+use crate::widgets::text::TextWidget;
+
+/// Name of the mode the window manager is in when no binding mode is active.
+const DEFAULT_MODE: &str = "default";
+
+pub struct BindingMode {
+    id: String,
+    text: TextWidget,
+    mode: Arc<Mutex<String>>,
+    format: FormatTemplate,
+    warning_modes: Vec<String>,
+}
+
+#[derive(Deserialize, Debug, Default, Clone)]
+#[serde(deny_unknown_fields)]
+pub struct BindingModeConfig {
+    /// Format override
+    #[serde(default = "BindingModeConfig::default_format")]
+    pub format: String,
+
+    /// Modes to show in the warning state instead of the info state
+    #[serde(default)]
+    pub warning_modes: Vec<String>,
+}
+
+impl BindingModeConfig {
+    fn default_format() -> String {
+        "{mode}".to_owned()
+    }
+}
+
+impl ConfigBlock for BindingMode {
+    type Config = BindingModeConfig;
+
+    fn new(block_config: Self::Config, config: Config, tx: Sender<Task>) -> Result<Self> {
+        let id = Uuid::new_v4().to_simple().to_string();
+        let id_clone = id.clone();
+
+        let mode = Arc::new(Mutex::new(DEFAULT_MODE.to_string()));
+        let update_mode = {
+            let mode = mode.clone();
+
+            move |new_mode: String| {
+                let mut mode = mode
+                    .lock()
+                    .expect("lock has been poisoned in `binding_mode` block");
+
+                let changed = *mode != new_mode;
+                *mode = new_mode;
+                changed
+            }
+        };
+
+        let _test_conn =
+            Connection::new().block_error("binding_mode", "failed to acquire connect to IPC")?;
+
+        thread::Builder::new()
+            .name("binding_mode".into())
+            .spawn(move || {
+                let conn = Connection::new().expect("failed to open connection with swayipc");
+
+                let events = conn
+                    .subscribe(&[EventType::Mode])
+                    .expect("could not subscribe to mode events");
+
+                for event in events {
+                    let updated = match event.expect("could not read event in `binding_mode` block")
+                    {
+                        Event::Mode(e) => update_mode(e.change),
+                        _ => false,
+                    };
+
+                    if updated {
+                        tx.send(Task {
+                            id: id_clone.clone(),
+                            update_time: Instant::now(),
+                        })
+                        .expect("could not communicate with channel in `binding_mode` block");
+                    }
+                }
+            })
+            .expect("failed to start watching thread for `binding_mode` block");
+
+        Ok(BindingMode {
+            id,
+            text: TextWidget::new(config),
+            mode,
+            format: FormatTemplate::from_string(&block_config.format)
+                .block_error("binding_mode", "Invalid format specified")?,
+            warning_modes: block_config.warning_modes,
+        })
+    }
+}
+
+impl Block for BindingMode {
+    fn update(&mut self) -> Result<Option<Update>> {
+        let mode = (*self
+            .mode
+            .lock()
+            .block_error("binding_mode", "failed to acquire lock")?)
+        .clone();
+
+        self.text.set_state(if self.warning_modes.contains(&mode) {
+            State::Warning
+        } else {
+            State::Info
+        });
+        let values = map!("{mode}" => mode);
+        self.text.set_text(self.format.render_static_str(&values)?);
+
+        Ok(None)
+    }
+
+    fn view(&self) -> Vec<&dyn I3BarWidget> {
+        let mode = &*self
+            .mode
+            .lock()
+            .expect("lock has been poisoned in `binding_mode` block");
+
+        if mode == DEFAULT_MODE {
+            vec![]
+        } else {
+            vec![&self.text]
+        }
+    }
+
+    fn set_format(&mut self, format: &str) -> Result<()> {
+        self.format = FormatTemplate::from_string(format)
+            .block_error("binding_mode", "Invalid format specified")?;
+        Ok(())
+    }
+
+    fn id(&self) -> &str {
+        &self.id
+    }
+}
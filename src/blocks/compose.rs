@@ -0,0 +1,153 @@
+use std::fs;
+use std::path::PathBuf;
+use std::time::{Duration, Instant};
+
+use crossbeam_channel::Sender;
+use serde_derive::Deserialize;
+use uuid::Uuid;
+
+use crate::blocks::{Block, ConfigBlock, Update};
+use crate::config::Config;
+use crate::de::deserialize_duration;
+use crate::errors::*;
+use crate::scheduler::Task;
+use crate::signals::convert_to_valid_signal;
+use crate::util::read_sysfs;
+use crate::widget::{I3BarWidget, State};
+use crate::widgets::text::TextWidget;
+
+const LEDS_PATH: &str = "/sys/class/leds";
+
+pub struct Compose {
+    id: String,
+    output: TextWidget,
+    led: String,
+    update_interval: Duration,
+    text: String,
+    hide_inactive: bool,
+    active: bool,
+    signal: Option<i32>,
+    tx_update_request: Sender<Task>,
+}
+
+#[derive(Deserialize, Debug, Default, Clone)]
+#[serde(deny_unknown_fields)]
+pub struct ComposeConfig {
+    /// Update interval in seconds
+    #[serde(
+        default = "ComposeConfig::default_interval",
+        deserialize_with = "deserialize_duration"
+    )]
+    pub interval: Duration,
+
+    /// Keyboard LED that indicates the latch, e.g. "compose" or "kana"
+    #[serde(default = "ComposeConfig::default_led")]
+    pub led: String,
+
+    /// Text to show next to the icon
+    #[serde(default)]
+    pub text: String,
+
+    /// Hide the block while no keyboard has the LED lit
+    #[serde(default)]
+    pub hide_inactive: bool,
+
+    /// Signal that updates the block right away, e.g. from the binding of the
+    /// latching key
+    #[serde(default)]
+    pub signal: Option<i32>,
+}
+
+impl ComposeConfig {
+    fn default_interval() -> Duration {
+        Duration::from_secs(1)
+    }
+
+    fn default_led() -> String {
+        "compose".to_owned()
+    }
+}
+
+impl ConfigBlock for Compose {
+    type Config = ComposeConfig;
+
+    fn new(
+        block_config: Self::Config,
+        config: Config,
+        tx_update_request: Sender<Task>,
+    ) -> Result<Self> {
+        let signal = match block_config.signal {
+            Some(signal) => Some(convert_to_valid_signal(signal)?),
+            None => None,
+        };
+
+        Ok(Compose {
+            id: Uuid::new_v4().to_simple().to_string(),
+            output: TextWidget::new(config)
+                .with_icon("keyboard")
+                .with_text(&block_config.text),
+            led: block_config.led,
+            update_interval: block_config.interval,
+            text: block_config.text,
+            hide_inactive: block_config.hide_inactive,
+            active: false,
+            signal,
+            tx_update_request,
+        })
+    }
+}
+
+impl Compose {
+    /// Finds the brightness files of the LED on every keyboard, such as
+    /// /sys/class/leds/input3::compose/brightness. They are looked up on each
+    /// update since keyboards come and go.
+    fn led_paths(&self) -> Result<Vec<PathBuf>> {
+        let suffix = format!("::{}", self.led);
+        Ok(fs::read_dir(LEDS_PATH)
+            .block_error("compose", &format!("failed to read {}", LEDS_PATH))?
+            .filter_map(|entry| entry.ok())
+            .filter(|entry| entry.file_name().to_string_lossy().ends_with(&suffix))
+            .map(|entry| entry.path().join("brightness"))
+            .collect())
+    }
+}
+
+impl Block for Compose {
+    fn update(&mut self) -> Result<Option<Update>> {
+        self.active = self
+            .led_paths()?
+            .iter()
+            .any(|path| read_sysfs::<u64>("compose", path).unwrap_or(0) > 0);
+
+        self.output.set_text(self.text.clone());
+        self.output.set_state(if self.active {
+            State::Warning
+        } else {
+            State::Idle
+        });
+
+        Ok(Some(self.update_interval.into()))
+    }
+
+    fn view(&self) -> Vec<&dyn I3BarWidget> {
+        if !self.active && self.hide_inactive {
+            vec![]
+        } else {
+            vec![&self.output]
+        }
+    }
+
+    fn signal(&mut self, signal: i32) -> Result<()> {
+        if self.signal == Some(signal) {
+            self.tx_update_request.send(Task {
+                id: self.id.clone(),
+                update_time: Instant::now(),
+            })?;
+        }
+        Ok(())
+    }
+
+    fn id(&self) -> &str {
+        &self.id
+    }
+}
@@ -1,7 +1,8 @@
 use std::fs::File;
 use std::io::prelude::*;
 use std::io::BufReader;
-use std::time::Duration;
+use std::path::{Path, PathBuf};
+use std::time::{Duration, Instant};
 
 use crossbeam_channel::Sender;
 use serde_derive::Deserialize;
@@ -15,13 +16,150 @@ use crate::errors::*;
 use crate::input::{I3BarEvent, MouseButton};
 use crate::scheduler::Task;
 use crate::subprocess::spawn_child_async;
-use crate::util::{format_percent_bar, FormatTemplate};
+use crate::util::{
+    check_smoothing, ewma, format_percent_bar, read_file, read_sysfs, FormatTemplate,
+};
 use crate::widget::{I3BarWidget, State};
 use crate::widgets::button::ButtonWidget;
 
 /// Maximum number of CPUs we support.
 const MAX_CPUS: usize = 32;
 
+/// Root of the cgroup v2 hierarchy.
+const CGROUP_ROOT: &str = "/sys/fs/cgroup";
+
+/// CPU usage of a cgroup, measured against the CPU time the cgroup may use.
+struct CgroupCpu {
+    path: PathBuf,
+    /// Last reading of `usage_usec` from `cpu.stat`
+    prev_usage: Option<(u64, Instant)>,
+}
+
+impl CgroupCpu {
+    fn new(cgroup: &str) -> Result<Self> {
+        let path = Path::new(CGROUP_ROOT).join(cgroup.trim_start_matches('/'));
+        if !path.join("cpu.stat").exists() {
+            return Err(ConfigurationError(
+                "cpu".to_string(),
+                (
+                    format!("{} is not a cgroup v2 directory", path.display()),
+                    "invalid cgroup".to_string(),
+                ),
+            ));
+        }
+        Ok(CgroupCpu {
+            path,
+            prev_usage: None,
+        })
+    }
+
+    /// Number of CPUs the cgroup can keep busy: its quota from `cpu.max` if it
+    /// has one, otherwise the number of CPUs it may run on.
+    fn capacity(&self) -> Result<f64> {
+        let max = read_file("cpu", &self.path.join("cpu.max")).unwrap_or_default();
+        let mut fields = max.split_whitespace();
+        if let (Some(Ok(quota)), Some(Ok(period))) = (
+            fields.next().map(str::parse::<f64>),
+            fields.next().map(str::parse::<f64>),
+        ) {
+            if period > 0. {
+                return Ok(quota / period);
+            }
+        }
+
+        let cpus = read_file("cpu", &self.path.join("cpuset.cpus.effective"))
+            .map(|list| count_cpus(&list))
+            .unwrap_or(0);
+        if cpus > 0 {
+            return Ok(cpus as f64);
+        }
+
+        // Without the cpuset controller, the cgroup may use all CPUs
+        let stat = read_file("cpu", Path::new("/proc/stat"))?;
+        Ok(stat
+            .lines()
+            .filter(|line| line.starts_with("cpu") && !line.starts_with("cpu "))
+            .count()
+            .max(1) as f64)
+    }
+
+    /// Utilization since the previous call, between 0 and 1.
+    fn utilization(&mut self) -> Result<f64> {
+        let stat = read_file("cpu", &self.path.join("cpu.stat"))?;
+        let usage = stat
+            .lines()
+            .find_map(|line| line.strip_prefix("usage_usec "))
+            .and_then(|usage| usage.trim().parse::<u64>().ok())
+            .block_error("cpu", "failed to read usage_usec from cpu.stat")?;
+        let now = Instant::now();
+
+        let utilization = match self.prev_usage {
+            Some((prev, prev_time)) if prev <= usage => {
+                let elapsed = now.duration_since(prev_time).as_micros() as f64;
+                let available = elapsed * self.capacity()?;
+                if available > 0. {
+                    ((usage - prev) as f64 / available).clamp(0., 1.)
+                } else {
+                    0.
+                }
+            }
+            _ => 0.,
+        };
+        self.prev_usage = Some((usage, now));
+        Ok(utilization)
+    }
+}
+
+/// Reads the current frequency of each CPU in MHz into `freqs` and returns
+/// the number of CPUs read. Falls back to `/proc/cpuinfo` without cpufreq,
+/// e.g. in virtual machines.
+fn read_frequencies(freqs: &mut [f32; MAX_CPUS]) -> Result<usize> {
+    let mut n_cpu = 0;
+    for cpu in 0..MAX_CPUS {
+        let path = PathBuf::from(format!(
+            "/sys/devices/system/cpu/cpu{}/cpufreq/scaling_cur_freq",
+            cpu
+        ));
+        // Offline CPUs have no cpufreq
+        if path.exists() {
+            // In kHz
+            freqs[n_cpu] = read_sysfs::<f32>("cpu", &path)? / 1000.;
+            n_cpu += 1;
+        }
+    }
+    if n_cpu > 0 {
+        return Ok(n_cpu);
+    }
+
+    let cpuinfo = read_file("cpu", Path::new("/proc/cpuinfo"))?;
+    for line in cpuinfo.lines().filter(|line| line.starts_with("cpu MHz")) {
+        let mhz = line.split(':').nth(1).unwrap_or_default().trim();
+        freqs[n_cpu] = mhz
+            .parse::<f32>()
+            .block_error("cpu", "failed to parse cpu frequency in /proc/cpuinfo")?;
+        n_cpu += 1;
+        if n_cpu >= MAX_CPUS {
+            break;
+        }
+    }
+    Ok(n_cpu)
+}
+
+/// Counts the CPUs in a list such as `0-3,6`.
+fn count_cpus(list: &str) -> usize {
+    list.trim()
+        .split(',')
+        .filter(|range| !range.is_empty())
+        .map(|range| match range.split_once('-') {
+            Some((first, last)) => match (first.parse::<usize>(), last.parse::<usize>()) {
+                (Ok(first), Ok(last)) if first <= last => last - first + 1,
+                _ => 0,
+            },
+            None => 1,
+        })
+        .sum()
+}
+
 pub struct Cpu {
     output: ButtonWidget,
     prev_idles: [u64; MAX_CPUS],
@@ -36,6 +174,9 @@ pub struct Cpu {
     has_barchart: bool,
     has_frequency: bool,
     per_core: bool,
+    cgroup: Option<CgroupCpu>,
+    smoothing: f64,
+    smoothed_utilizations: Option<[f64; MAX_CPUS]>,
 }
 
 #[derive(Deserialize, Debug, Default, Clone)]
@@ -74,6 +215,16 @@ pub struct CpuConfig {
     /// Compute the metrics (utilization and frequency) per core.
     #[serde(default)]
     pub per_core: bool,
+
+    /// Measure the utilization of this cgroup v2, relative to /sys/fs/cgroup,
+    /// against its quota instead of the whole machine.
+    #[serde(default)]
+    pub cgroup: Option<String>,
+
+    /// Weight of the previous utilization in an exponential moving average,
+    /// from 0 (no smoothing) up to but excluding 1.
+    #[serde(default = "CpuConfig::default_smoothing")]
+    pub smoothing: f64,
 }
 
 impl CpuConfig {
@@ -104,6 +255,10 @@ impl CpuConfig {
     fn default_on_click() -> Option<String> {
         None
     }
+
+    fn default_smoothing() -> f64 {
+        0.
+    }
 }
 
 impl ConfigBlock for Cpu {
@@ -139,85 +294,85 @@ impl ConfigBlock for Cpu {
             has_barchart: format.contains("{barchart}"),
             per_core: block_config.per_core,
             on_click: block_config.on_click,
+            cgroup: match block_config.cgroup {
+                Some(ref cgroup) => Some(CgroupCpu::new(cgroup)?),
+                None => None,
+            },
+            smoothing: check_smoothing("cpu", block_config.smoothing)?,
+            smoothed_utilizations: None,
         })
     }
 }
 
 impl Block for Cpu {
     fn update(&mut self) -> Result<Option<Update>> {
-        let f = File::open("/proc/stat")
-            .block_error("cpu", "Your system doesn't support /proc/stat")?;
-        let f = BufReader::new(f);
-
         let mut cpu_freqs: [f32; MAX_CPUS] = [0.0; MAX_CPUS];
         let mut n_cpu = 0;
         if self.has_frequency {
-            let freq_file =
-                File::open("/proc/cpuinfo").block_error("cpu", "failed to read /proc/cpuinfo")?;
-            let freq_file_content = BufReader::new(freq_file);
-            // read frequency of each cpu and calculate the average which we will display
-            for line in freq_file_content.lines().scan((), |_, x| x.ok()) {
-                if line.starts_with("cpu MHz") {
-                    let words = line.split(' ');
-                    let last = words
-                        .last()
-                        .expect("failed to get last word of line while getting cpu frequency");
-                    let numb = last
-                        .parse::<f32>()
-                        .expect("failed to parse String to f32 while getting cpu frequency");
-                    cpu_freqs[n_cpu] = numb;
-                    n_cpu += 1;
-                    if n_cpu >= MAX_CPUS {
-                        break;
-                    };
-                }
-            }
+            n_cpu = read_frequencies(&mut cpu_freqs)?;
         }
 
         let mut cpu_utilizations: [f64; MAX_CPUS] = [0.0; MAX_CPUS];
         let mut cpu_i = 0;
-        for line in f.lines().scan((), |_, x| x.ok()) {
-            if line.starts_with("cpu") {
-                let data: Vec<u64> = (&line)
-                    .split(' ')
-                    .collect::<Vec<&str>>()
-                    .iter()
-                    .skip(if cpu_i == 0 { 2 } else { 1 })
-                    .filter_map(|x| x.parse::<u64>().ok())
-                    .collect::<Vec<_>>();
-
-                // idle = idle + iowait
-                let idle = data[3] + data[4];
-                let non_idle = data[0] + // user
-                                data[1] + // nice
-                                data[2] + // system
-                                data[5] + // irq
-                                data[6] + // softirq
-                                data[7]; // steal
-
-                let prev_total = self.prev_idles[cpu_i] + self.prev_non_idles[cpu_i];
-                let total = idle + non_idle;
-
-                // This check is needed because the new values may be reset, for
-                // example after hibernation.
-
-                let (total_delta, idle_delta) =
-                    if prev_total < total && self.prev_idles[cpu_i] <= idle {
-                        (total - prev_total, idle - self.prev_idles[cpu_i])
-                    } else {
-                        (1, 1)
+        if let Some(ref mut cgroup) = self.cgroup {
+            cpu_utilizations[0] = cgroup.utilization()?;
+            cpu_i = 1;
+        } else {
+            let f = File::open("/proc/stat")
+                .block_error("cpu", "Your system doesn't support /proc/stat")?;
+            let f = BufReader::new(f);
+
+            for line in f.lines().scan((), |_, x| x.ok()) {
+                if line.starts_with("cpu") {
+                    let data: Vec<u64> = (&line)
+                        .split(' ')
+                        .collect::<Vec<&str>>()
+                        .iter()
+                        .skip(if cpu_i == 0 { 2 } else { 1 })
+                        .filter_map(|x| x.parse::<u64>().ok())
+                        .collect::<Vec<_>>();
+
+                    // idle = idle + iowait
+                    let idle = data[3] + data[4];
+                    let non_idle = data[0] + // user
+                                    data[1] + // nice
+                                    data[2] + // system
+                                    data[5] + // irq
+                                    data[6] + // softirq
+                                    data[7]; // steal
+
+                    let prev_total = self.prev_idles[cpu_i] + self.prev_non_idles[cpu_i];
+                    let total = idle + non_idle;
+
+                    // This check is needed because the new values may be reset, for
+                    // example after hibernation.
+
+                    let (total_delta, idle_delta) =
+                        if prev_total < total && self.prev_idles[cpu_i] <= idle {
+                            (total - prev_total, idle - self.prev_idles[cpu_i])
+                        } else {
+                            (1, 1)
+                        };
+
+                    cpu_utilizations[cpu_i] =
+                        (total_delta - idle_delta) as f64 / total_delta as f64;
+
+                    self.prev_idles[cpu_i] = idle;
+                    self.prev_non_idles[cpu_i] = non_idle;
+                    cpu_i += 1;
+                    if cpu_i >= MAX_CPUS {
+                        break;
                     };
+                }
+            }
+        }
 
-                cpu_utilizations[cpu_i] = (total_delta - idle_delta) as f64 / total_delta as f64;
-
-                self.prev_idles[cpu_i] = idle;
-                self.prev_non_idles[cpu_i] = non_idle;
-                cpu_i += 1;
-                if cpu_i >= MAX_CPUS {
-                    break;
-                };
+        if let Some(previous) = self.smoothed_utilizations {
+            for (utilization, previous) in cpu_utilizations.iter_mut().zip(&previous) {
+                *utilization = ewma(*previous, *utilization, self.smoothing);
             }
         }
+        self.smoothed_utilizations = Some(cpu_utilizations);
 
         let avg_utilization = (100.0 * cpu_utilizations[0]) as u64;
 
@@ -269,6 +424,14 @@ impl Block for Cpu {
         Ok(())
     }
 
+    fn set_format(&mut self, format: &str) -> Result<()> {
+        self.format = FormatTemplate::from_string(format)
+            .block_error("cpu", "Invalid format specified for cpu")?;
+        self.has_frequency = format.contains("{frequency}");
+        self.has_barchart = format.contains("{barchart}");
+        Ok(())
+    }
+
     fn id(&self) -> &str {
         &self.id
     }
@@ -1,29 +1,118 @@
+use std::cmp::min;
+use std::collections::HashSet;
 use std::env;
+use std::io::{BufRead, BufReader, Write};
 use std::iter::{Cycle, Peekable};
-use std::process::Command;
+use std::process::{ChildStdin, Command, Stdio};
+use std::sync::{Arc, Mutex};
+use std::thread;
 use std::time::{Duration, Instant};
 use std::vec;
 
 use crossbeam_channel::Sender;
 use serde_derive::Deserialize;
+use serde_json::value::{Map, Value};
 use uuid::Uuid;
 
 use crate::blocks::{Block, ConfigBlock, Update};
 use crate::config::Config;
-use crate::de::deserialize_update;
+use crate::de::{deserialize_opt_duration, deserialize_update};
 use crate::errors::*;
 use crate::input::I3BarEvent;
 use crate::scheduler::Task;
 use crate::signals::convert_to_valid_signal;
-use crate::subprocess::spawn_child_async;
+use crate::subprocess::{output_with_timeout, spawn_child_async};
+use crate::util::MarkupEscape;
 use crate::widget::{I3BarWidget, State};
 use crate::widgets::button::ButtonWidget;
 
+/// Delay before restarting a persistent command that exited, doubled on every
+/// consecutive failure up to `PERSISTENT_MAX_BACKOFF`.
+const PERSISTENT_MIN_BACKOFF: Duration = Duration::from_secs(1);
+const PERSISTENT_MAX_BACKOFF: Duration = Duration::from_secs(60);
+
+/// A long-running command whose stdout lines update the block and whose stdin
+/// receives click events.
+struct PersistentCommand {
+    stdin: Arc<Mutex<Option<ChildStdin>>>,
+    /// The latest line of output, `None` until the command printed one
+    last_line: Arc<Mutex<Option<String>>>,
+}
+
+impl PersistentCommand {
+    fn spawn(shell: String, command: String, id: String, tx_update_request: Sender<Task>) -> Self {
+        let stdin = Arc::new(Mutex::new(None));
+        let last_line = Arc::new(Mutex::new(None));
+        let persistent = PersistentCommand {
+            stdin: stdin.clone(),
+            last_line: last_line.clone(),
+        };
+
+        thread::Builder::new()
+            .name("custom_persistent".into())
+            .spawn(move || {
+                let mut backoff = PERSISTENT_MIN_BACKOFF;
+                loop {
+                    let child = Command::new(&shell)
+                        .arg("-c")
+                        .arg(&command)
+                        .stdin(Stdio::piped())
+                        .stdout(Stdio::piped())
+                        .spawn();
+
+                    if let Ok(mut child) = child {
+                        *stdin.lock().unwrap() = child.stdin.take();
+                        if let Some(stdout) = child.stdout.take() {
+                            for line in BufReader::new(stdout).lines() {
+                                let line = match line {
+                                    Ok(line) => line,
+                                    Err(_) => break,
+                                };
+                                *last_line.lock().unwrap() = Some(line);
+                                backoff = PERSISTENT_MIN_BACKOFF;
+                                if tx_update_request
+                                    .send(Task {
+                                        id: id.clone(),
+                                        update_time: Instant::now(),
+                                    })
+                                    .is_err()
+                                {
+                                    return;
+                                }
+                            }
+                        }
+                        *stdin.lock().unwrap() = None;
+                        child.wait().ok();
+                    }
+
+                    thread::sleep(backoff);
+                    backoff = min(backoff * 2, PERSISTENT_MAX_BACKOFF);
+                }
+            })
+            .unwrap();
+
+        persistent
+    }
+
+    fn send_click(&self, event: &I3BarEvent) {
+        if let Some(ref mut stdin) = *self.stdin.lock().unwrap() {
+            let click = json!({
+                "button": event.button,
+                "x": event.x,
+                "y": event.y,
+            });
+            // The command may have exited in the meantime, it will be restarted.
+            writeln!(stdin, "{}", click).ok();
+        }
+    }
+}
+
 pub struct Custom {
     id: String,
     update_interval: Update,
     output: ButtonWidget,
     command: Option<String>,
+    persistent: Option<PersistentCommand>,
     on_click: Option<String>,
     cycle: Option<Peekable<Cycle<vec::IntoIter<String>>>>,
     signal: Option<i32>,
@@ -31,7 +120,10 @@ pub struct Custom {
     pub json: bool,
     hide_when_empty: bool,
     is_empty: bool,
+    /// Unknown fields of the JSON output that were already warned about
+    warned_fields: HashSet<String>,
     shell: String,
+    command_timeout: Option<Duration>,
 }
 
 #[derive(Deserialize, Debug, Default, Clone)]
@@ -47,6 +139,10 @@ pub struct CustomConfig {
     /// Shell Command to execute & display
     pub command: Option<String>,
 
+    /// Keep `command` running and display each line it prints
+    #[serde(default = "CustomConfig::default_persistent")]
+    pub persistent: bool,
+
     /// Command to execute when the button is clicked
     pub on_click: Option<String>,
 
@@ -64,6 +160,10 @@ pub struct CustomConfig {
     pub hide_when_empty: bool,
 
     pub shell: Option<String>,
+
+    /// Kill the command if it runs for longer than this
+    #[serde(default, deserialize_with = "deserialize_opt_duration")]
+    pub command_timeout: Option<Duration>,
 }
 
 impl CustomConfig {
@@ -75,6 +175,10 @@ impl CustomConfig {
         false
     }
 
+    fn default_persistent() -> bool {
+        false
+    }
+
     fn hide_when_empty() -> bool {
         false
     }
@@ -89,6 +193,7 @@ impl ConfigBlock for Custom {
             update_interval: block_config.interval,
             output: ButtonWidget::new(config.clone(), ""),
             command: None,
+            persistent: None,
             on_click: None,
             cycle: None,
             signal: None,
@@ -96,6 +201,8 @@ impl ConfigBlock for Custom {
             json: block_config.json,
             hide_when_empty: block_config.hide_when_empty,
             is_empty: true,
+            warned_fields: HashSet::new(),
+            command_timeout: block_config.command_timeout,
             shell: if let Some(s) = block_config.shell {
                 s
             } else {
@@ -121,10 +228,29 @@ impl ConfigBlock for Custom {
         }
 
         if let Some(cycle) = block_config.cycle {
+            if block_config.persistent {
+                return Err(BlockError(
+                    "custom".to_string(),
+                    "`persistent` requires `command` instead of `cycle`".to_string(),
+                ));
+            }
             custom.cycle = Some(cycle.into_iter().cycle().peekable());
             return Ok(custom);
         };
 
+        if block_config.persistent {
+            let command = block_config
+                .command
+                .block_error("custom", "`persistent` requires a `command` to run")?;
+            custom.persistent = Some(PersistentCommand::spawn(
+                custom.shell.clone(),
+                command,
+                custom.id.clone(),
+                custom.tx_update_request.clone(),
+            ));
+            return Ok(custom);
+        }
+
         if let Some(command) = block_config.command {
             custom.command = Some(command)
         };
@@ -133,55 +259,164 @@ impl ConfigBlock for Custom {
     }
 }
 
-fn default_icon() -> String {
-    String::from("")
-}
-
-fn default_state() -> State {
-    State::Idle
-}
+/// Fields of the JSON output, documented in the custom section of blocks.md.
+const OUTPUT_FIELDS: &[&str] = &["text", "short_text", "icon", "state", "color", "background"];
 
-#[derive(Deserialize)]
+#[derive(Debug, Default, PartialEq)]
 struct Output {
-    #[serde(default = "default_icon")]
+    text: String,
+    short_text: Option<String>,
     icon: String,
-    #[serde(default = "default_state")]
     state: State,
-    text: String,
+    color: Option<String>,
+    background: Option<String>,
+}
+
+fn string_field(
+    fields: &Map<String, Value>,
+    name: &str,
+) -> ::std::result::Result<Option<String>, String> {
+    match fields.get(name) {
+        None => Ok(None),
+        Some(Value::String(value)) => Ok(Some(value.clone())),
+        Some(value) => Err(format!("field `{}` must be a string, not {}", name, value)),
+    }
+}
+
+fn color_field(
+    fields: &Map<String, Value>,
+    name: &str,
+) -> ::std::result::Result<Option<String>, String> {
+    let color = string_field(fields, name)?;
+    if let Some(ref color) = color {
+        let is_hex = color.starts_with('#')
+            && (color.len() == 7 || color.len() == 9)
+            && color[1..].chars().all(|c| c.is_ascii_hexdigit());
+        if !is_hex {
+            return Err(format!(
+                "field `{}` must be a color like #RRGGBB or #RRGGBBAA, not \"{}\"",
+                name, color
+            ));
+        }
+    }
+    Ok(color)
+}
+
+/// Parses a line of JSON output of the command. Returns the output and the
+/// unknown fields, which are ignored, or an error naming the invalid field.
+fn parse_output(raw: &str) -> ::std::result::Result<(Output, Vec<String>), String> {
+    let fields = match serde_json::from_str(raw) {
+        Ok(Value::Object(fields)) => fields,
+        Ok(_) => return Err("the output must be a JSON object".to_string()),
+        Err(e) => return Err(format!("invalid JSON: {}", e)),
+    };
+    let unknown = fields
+        .keys()
+        .filter(|name| !OUTPUT_FIELDS.contains(&name.as_str()))
+        .cloned()
+        .collect();
+
+    let state = match string_field(&fields, "state")? {
+        Some(state) => state.parse().map_err(|_| {
+            format!(
+                "field `state` must be one of Idle, Info, Good, Warning or Critical, not \"{}\"",
+                state
+            )
+        })?,
+        None => State::Idle,
+    };
+    let output = Output {
+        text: string_field(&fields, "text")?.ok_or_else(|| "missing field `text`".to_string())?,
+        short_text: string_field(&fields, "short_text")?,
+        icon: string_field(&fields, "icon")?.unwrap_or_default(),
+        state,
+        color: color_field(&fields, "color")?,
+        background: color_field(&fields, "background")?,
+    };
+    Ok((output, unknown))
 }
 
 impl Block for Custom {
     fn update(&mut self) -> Result<Option<Update>> {
-        let command_str = self
-            .cycle
-            .as_mut()
-            .map(|c| c.peek().cloned().unwrap_or_else(|| "".to_owned()))
-            .or_else(|| self.command.clone())
-            .unwrap_or_else(|| "".to_owned());
-
-        let raw_output = Command::new(&self.shell)
-            .args(&["-c", &command_str])
-            .output()
-            .map(|o| String::from_utf8_lossy(&o.stdout).trim().to_owned())
-            .unwrap_or_else(|e| e.to_string());
+        let raw_output = if let Some(ref persistent) = self.persistent {
+            match *persistent.last_line.lock().unwrap() {
+                Some(ref line) => line.trim().to_owned(),
+                // Keep the block empty until the command printed its first
+                // line, which would not parse as JSON
+                None => return Ok(None),
+            }
+        } else {
+            let command_str = self
+                .cycle
+                .as_mut()
+                .map(|c| c.peek().cloned().unwrap_or_else(|| "".to_owned()))
+                .or_else(|| self.command.clone())
+                .unwrap_or_else(|| "".to_owned());
+
+            let mut command = Command::new(&self.shell);
+            command.args(&["-c", &command_str]);
+            let output = match self.command_timeout {
+                Some(timeout) => output_with_timeout(&mut command, timeout),
+                None => command.output().map(Some),
+            };
+            match output {
+                Ok(Some(o)) => String::from_utf8_lossy(&o.stdout).trim().to_owned(),
+                Ok(None) => {
+                    self.is_empty = false;
+                    self.output.set_text("timed out".to_string());
+                    self.output.set_state(State::Critical);
+                    return Ok(Some(self.update_interval.clone()));
+                }
+                Err(e) => e.to_string(),
+            }
+        };
 
         if self.json {
-            let output: Output = match serde_json::from_str(&*raw_output) {
+            match parse_output(&raw_output) {
+                Ok((output, unknown)) => {
+                    for name in unknown {
+                        if self.warned_fields.insert(name.clone()) {
+                            eprintln!(
+                                "custom: ignoring unknown field `{}` in the JSON output, \
+                                 expected one of {}",
+                                name,
+                                OUTPUT_FIELDS.join(", ")
+                            );
+                        }
+                    }
+                    self.output.set_icon(&output.icon);
+                    self.output.set_state(output.state);
+                    self.output.set_colors(output.color, output.background);
+                    self.output.set_short_text(
+                        output
+                            .short_text
+                            .map(|text| MarkupEscape::current().apply(&text)),
+                    );
+                    self.is_empty = output.text.is_empty();
+                    self.output
+                        .set_text(MarkupEscape::current().apply(&output.text));
+                }
                 Err(e) => {
-                    return Err(BlockError(
-                        "custom".to_string(),
-                        format!("Error parsing JSON: {}", e),
-                    ));
+                    // Show the error in the block instead of stopping the bar
+                    eprintln!("custom: {}: {}", e, raw_output);
+                    self.output.set_icon("");
+                    self.output.set_state(State::Critical);
+                    self.output.set_colors(None, None);
+                    self.output.set_short_text(None);
+                    self.is_empty = false;
+                    self.output.set_text(e);
                 }
-                Ok(s) => s,
-            };
-            self.output.set_icon(&output.icon);
-            self.output.set_state(output.state);
-            self.is_empty = output.text.is_empty();
-            self.output.set_text(output.text);
+            }
         } else {
             self.is_empty = raw_output.is_empty();
-            self.output.set_text(raw_output);
+            self.output
+                .set_text(MarkupEscape::current().apply(&raw_output));
+            self.output.set_state(State::Idle);
+        }
+
+        if self.persistent.is_some() {
+            // The persistent command pushes its own updates
+            return Ok(None);
         }
 
         Ok(Some(self.update_interval.clone()))
@@ -218,6 +453,10 @@ impl Block for Custom {
 
         let mut update = false;
 
+        if let Some(ref persistent) = self.persistent {
+            persistent.send_click(event);
+        }
+
         if let Some(ref on_click) = self.on_click {
             spawn_child_async(&self.shell, &["-c", on_click]).ok();
             update = true;
@@ -242,3 +481,45 @@ impl Block for Custom {
         &self.id
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use crate::blocks::custom::{parse_output, Output};
+    use crate::widget::State;
+
+    #[test]
+    fn test_parse_output() {
+        assert_eq!(
+            parse_output(r##"{"text": "hi", "state": "Warning", "color": "#FF0000", "x": 1}"##),
+            Ok((
+                Output {
+                    text: "hi".to_string(),
+                    state: State::Warning,
+                    color: Some("#FF0000".to_string()),
+                    ..Default::default()
+                },
+                vec!["x".to_string()]
+            ))
+        );
+        assert_eq!(
+            parse_output(r#"{"text": "hi", "short_text": "h", "icon": "cpu"}"#).map(|o| o.0),
+            Ok(Output {
+                text: "hi".to_string(),
+                short_text: Some("h".to_string()),
+                icon: "cpu".to_string(),
+                ..Default::default()
+            })
+        );
+    }
+
+    #[test]
+    fn test_parse_output_errors() {
+        let error = |raw| parse_output(raw).unwrap_err();
+        assert_eq!(error(r#"{"icon": "cpu"}"#), "missing field `text`");
+        assert!(error(r#"{"text": "hi", "state": "Bad"}"#).starts_with("field `state`"));
+        assert!(error(r#"{"text": 3}"#).starts_with("field `text`"));
+        assert!(error(r#"{"text": "hi", "background": "red"}"#).starts_with("field `background`"));
+        assert!(error(r#"["hi"]"#).contains("JSON object"));
+        assert!(error("hi").starts_with("invalid JSON"));
+    }
+}
@@ -1,5 +1,7 @@
-use std::path::Path;
-use std::time::Duration;
+use std::collections::VecDeque;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
 
 use crossbeam_channel::Sender;
 use nix::sys::statvfs::statvfs;
@@ -9,12 +11,109 @@ use uuid::Uuid;
 use crate::blocks::Update;
 use crate::blocks::{Block, ConfigBlock};
 use crate::config::Config;
-use crate::de::deserialize_duration;
+use crate::de::{deserialize_duration, deserialize_opt_duration, deserialize_threshold};
 use crate::errors::*;
 use crate::scheduler::Task;
-use crate::util::{format_percent_bar, FormatTemplate};
+use crate::util::{format_percent_bar, read_file, xdg_cache_home, FormatTemplate, Threshold};
 use crate::widget::{I3BarWidget, State};
-use crate::widgets::text::TextWidget;
+use crate::widgets::button::ButtonWidget;
+
+const DISK_BY_UUID_PATH: &str = "/dev/disk/by-uuid";
+const DISK_BY_LABEL_PATH: &str = "/dev/disk/by-label";
+const MOUNTINFO_PATH: &str = "/proc/self/mountinfo";
+
+/// What the disk space is read from.
+enum Target {
+    Path(String),
+    /// Link to the device of a filesystem in /dev/disk, which exists while the
+    /// device is present, mounted or not.
+    Device(PathBuf),
+}
+
+impl Target {
+    /// The path to read the statistics of, `None` if the filesystem is not
+    /// mounted.
+    fn mountpoint(&self) -> Result<Option<String>> {
+        match self {
+            Target::Path(path) => Ok(Some(path.clone())),
+            Target::Device(link) => {
+                let device = match fs::canonicalize(link) {
+                    Ok(device) => device,
+                    Err(_) => return Ok(None),
+                };
+                let mountinfo = read_file("disk_space", Path::new(MOUNTINFO_PATH))?;
+                Ok(find_mountpoint(&mountinfo, &device))
+            }
+        }
+    }
+}
+
+/// Finds where `device` is mounted in the content of /proc/self/mountinfo,
+/// preferring mounts of the whole filesystem over bind mounts of a directory.
+fn find_mountpoint(mountinfo: &str, device: &Path) -> Option<String> {
+    let mut bind_mount = None;
+    for line in mountinfo.lines() {
+        let fields: Vec<&str> = line.split(' ').collect();
+        // The optional fields end with a "-", followed by the type and the source
+        let source = match fields.iter().skip(6).position(|field| *field == "-") {
+            Some(separator) => match fields.get(6 + separator + 2) {
+                Some(source) => unescape_mountinfo(source),
+                None => continue,
+            },
+            None => continue,
+        };
+        // Sources may be links too, e.g. /dev/mapper/root
+        let source = fs::canonicalize(&source).unwrap_or_else(|_| PathBuf::from(source));
+        if source != device || fields.len() < 5 {
+            continue;
+        }
+        let mountpoint = unescape_mountinfo(fields[4]);
+        if fields[3] == "/" {
+            return Some(mountpoint);
+        }
+        bind_mount.get_or_insert(mountpoint);
+    }
+    bind_mount
+}
+
+/// Undoes the octal escapes of spaces, tabs, newlines and backslashes in
+/// /proc/self/mountinfo, e.g. `\040` for a space.
+fn unescape_mountinfo(field: &str) -> String {
+    let mut unescaped = String::new();
+    let mut rest = field;
+    while let Some(start) = rest.find('\\') {
+        unescaped.push_str(&rest[..start]);
+        let code = rest
+            .get(start + 1..start + 4)
+            .and_then(|code| u8::from_str_radix(code, 8).ok());
+        match code {
+            Some(code) => {
+                unescaped.push(code as char);
+                rest = &rest[start + 4..];
+            }
+            None => {
+                unescaped.push('\\');
+                rest = &rest[start + 1..];
+            }
+        }
+    }
+    unescaped.push_str(rest);
+    unescaped
+}
+
+/// Escapes a filesystem label like udev does for the links in
+/// /dev/disk/by-label, e.g. `My\x20Disk` for "My Disk".
+fn escape_label(label: &str) -> String {
+    let mut escaped = String::new();
+    for c in label.chars() {
+        if c.is_ascii_alphanumeric() || "#+-.:=@_".contains(c) || !c.is_ascii() {
+            escaped.push(c);
+        } else {
+            escaped.push_str(&format!("\\x{:02x}", c as u32));
+        }
+    }
+    escaped
+}
 
 #[derive(Copy, Clone, Debug, Deserialize, PartialEq, Eq)]
 pub enum Unit {
@@ -29,14 +128,18 @@ pub enum Unit {
 
 impl Unit {
     fn bytes_in_unit(unit: Unit, bytes: u64) -> f64 {
-        match unit {
-            Unit::MB => bytes as f64 / 1000. / 1000.,
-            Unit::GB => bytes as f64 / 1000. / 1000. / 1000.,
-            Unit::TB => bytes as f64 / 1000. / 1000. / 1000. / 1000.,
-            Unit::MiB => bytes as f64 / 1024. / 1024.,
-            Unit::GiB => bytes as f64 / 1024. / 1024. / 1024.,
-            Unit::TiB => bytes as f64 / 1024. / 1024. / 1024. / 1024.,
-            Unit::Percent => bytes as f64,
+        bytes as f64 / unit.bytes_per_unit()
+    }
+
+    fn bytes_per_unit(self) -> f64 {
+        match self {
+            Unit::MB => 1000. * 1000.,
+            Unit::GB => 1000. * 1000. * 1000.,
+            Unit::TB => 1000. * 1000. * 1000. * 1000.,
+            Unit::MiB => 1024. * 1024.,
+            Unit::GiB => 1024. * 1024. * 1024.,
+            Unit::TiB => 1024. * 1024. * 1024. * 1024.,
+            Unit::Percent => 1.,
         }
     }
 }
@@ -50,21 +153,129 @@ pub enum InfoType {
     Used,
 }
 
+/// Samples of the available space, used to project when the disk fills up.
+/// They are kept in a file so that a restart does not lose the trend.
+struct Trend {
+    /// Unix time in seconds and available bytes
+    samples: VecDeque<(u64, u64)>,
+    window: Duration,
+    file: PathBuf,
+}
+
+impl Trend {
+    fn load(file: PathBuf, window: Duration) -> Self {
+        let samples = fs::read_to_string(&file)
+            .map(|content| {
+                content
+                    .lines()
+                    .filter_map(|line| {
+                        let mut fields = line.split_whitespace();
+                        Some((fields.next()?.parse().ok()?, fields.next()?.parse().ok()?))
+                    })
+                    .collect()
+            })
+            .unwrap_or_default();
+        Trend {
+            samples,
+            window,
+            file,
+        }
+    }
+
+    /// Records a sample, keeping at most about 128 per window so that the
+    /// file stays small.
+    fn record(&mut self, now: u64, available: u64) -> Result<()> {
+        let start = now.saturating_sub(self.window.as_secs());
+        while let Some(&(time, _)) = self.samples.front() {
+            if time >= start {
+                break;
+            }
+            self.samples.pop_front();
+        }
+        let spacing = (self.window.as_secs() / 128).max(1);
+        if let Some(&(last, _)) = self.samples.back() {
+            if now < last + spacing {
+                return Ok(());
+            }
+        }
+        self.samples.push_back((now, available));
+
+        if let Some(parent) = self.file.parent() {
+            fs::create_dir_all(parent)
+                .block_error("disk_space", "failed to create the samples directory")?;
+        }
+        let content: String = self
+            .samples
+            .iter()
+            .map(|(time, available)| format!("{} {}\n", time, available))
+            .collect();
+        fs::write(&self.file, content).block_error("disk_space", "failed to write samples_file")
+    }
+
+    /// Seconds until the disk is full at the current rate, from a least
+    /// squares fit of the samples. `None` unless the samples cover a sixteenth
+    /// of the window and the disk loses more than 0.1% of `total` per window.
+    fn full_in(&self, total: u64) -> Option<u64> {
+        let (first, _) = *self.samples.front()?;
+        let (last, available) = *self.samples.back()?;
+        if self.samples.len() < 3 || last - first < self.window.as_secs() / 16 {
+            return None;
+        }
+
+        let n = self.samples.len() as f64;
+        let mean_time = self
+            .samples
+            .iter()
+            .map(|s| (s.0 - first) as f64)
+            .sum::<f64>()
+            / n;
+        let mean_available = self.samples.iter().map(|s| s.1 as f64).sum::<f64>() / n;
+        let (mut covariance, mut variance) = (0., 0.);
+        for &(time, available) in &self.samples {
+            let dt = (time - first) as f64 - mean_time;
+            covariance += dt * (available as f64 - mean_available);
+            variance += dt * dt;
+        }
+        // Bytes per second
+        let slope = covariance / variance;
+
+        if -slope * self.window.as_secs_f64() < total as f64 / 1000. {
+            return None;
+        }
+        Some((available as f64 / -slope) as u64)
+    }
+}
+
+fn format_full_in(seconds: u64) -> String {
+    let (count, unit) = match seconds {
+        s if s < 60 * 60 => ((s / 60).max(1), "min"),
+        s if s < 2 * 24 * 60 * 60 => (s / (60 * 60), "hour"),
+        s => (s / (24 * 60 * 60), "day"),
+    };
+    if count == 1 || unit == "min" {
+        format!("~{} {}", count, unit)
+    } else {
+        format!("~{} {}s", count, unit)
+    }
+}
+
 pub struct DiskSpace {
-    disk_space: TextWidget,
+    disk_space: ButtonWidget,
     id: String,
     update_interval: Duration,
     alias: String,
-    path: String,
+    target: Target,
     unit: Unit,
     info_type: InfoType,
-    warning: f64,
-    alert: f64,
+    warning: Threshold,
+    alert: Threshold,
     show_percentage: bool,
     show_bar: bool,
-    alert_absolute: bool,
     format: FormatTemplate,
+    unmounted_format: FormatTemplate,
     icon: String,
+    trend: Option<Trend>,
+    full_in_warning: Option<Duration>,
 }
 
 #[derive(Deserialize, Debug, Clone)]
@@ -74,6 +285,14 @@ pub struct DiskSpaceConfig {
     #[serde(default = "DiskSpaceConfig::default_path")]
     pub path: String,
 
+    /// UUID of the filesystem to collect information from, in place of `path`
+    #[serde(default)]
+    pub uuid: Option<String>,
+
+    /// Label of the filesystem to collect information from, in place of `path`
+    #[serde(default)]
+    pub label: Option<String>,
+
     /// Alias that is displayed for path
     #[serde(default = "DiskSpaceConfig::default_alias")]
     pub alias: String,
@@ -86,10 +305,16 @@ pub struct DiskSpaceConfig {
 
     /// Format string for output
     /// placeholders: {percentage}, {bar}, {path}, {alias}, {available}, {free}, {total}, {used},
-    ///               {unit}
+    ///               {unit}, {mountpoint}
     #[serde(default = "DiskSpaceConfig::default_format")]
     pub format: String,
 
+    /// Format string for output while the filesystem given by `uuid` or `label`
+    /// is not mounted
+    /// placeholders: {alias}, {icon}
+    #[serde(default = "DiskSpaceConfig::default_unmounted_format")]
+    pub unmounted_format: String,
+
     /// Unit that is used to display disk space. Options are MB, MiB, GB, GiB, TB and TiB
     #[serde(default = "DiskSpaceConfig::default_unit")]
     pub unit: Unit,
@@ -101,13 +326,20 @@ pub struct DiskSpaceConfig {
     )]
     pub interval: Duration,
 
-    /// Diskspace warning in GiB (yellow)
-    #[serde(default = "DiskSpaceConfig::default_warning")]
-    pub warning: f64,
+    /// Diskspace warning (yellow), e.g. "10 GiB" or "20%". Bare numbers are a
+    /// percentage, or an amount in `unit` with `alert_absolute`.
+    #[serde(
+        default = "DiskSpaceConfig::default_warning",
+        deserialize_with = "deserialize_threshold"
+    )]
+    pub warning: Threshold,
 
-    /// Diskspace alert in GiB (red)
-    #[serde(default = "DiskSpaceConfig::default_alert")]
-    pub alert: f64,
+    /// Diskspace alert (red), same format as `warning`
+    #[serde(
+        default = "DiskSpaceConfig::default_alert",
+        deserialize_with = "deserialize_threshold"
+    )]
+    pub alert: Threshold,
 
     /// Show percentage - deprecated for format string, kept for previous configs
     #[serde(default = "DiskSpaceConfig::default_show_percentage")]
@@ -120,6 +352,21 @@ pub struct DiskSpaceConfig {
     /// use absolute (unit) values for disk space alerts
     #[serde(default = "DiskSpaceConfig::default_alert_absolute")]
     pub alert_absolute: bool,
+
+    /// Period of the samples that the fill rate is computed from
+    #[serde(
+        default = "DiskSpaceConfig::default_trend_window",
+        deserialize_with = "deserialize_duration"
+    )]
+    pub trend_window: Duration,
+
+    /// Warn when the disk is projected to be full within this time
+    #[serde(default, deserialize_with = "deserialize_opt_duration")]
+    pub full_in_warning: Option<Duration>,
+
+    /// File to keep the samples in across restarts
+    #[serde(default)]
+    pub samples_file: Option<PathBuf>,
 }
 
 impl DiskSpaceConfig {
@@ -139,6 +386,10 @@ impl DiskSpaceConfig {
         String::from("{alias} {available} {unit}")
     }
 
+    fn default_unmounted_format() -> String {
+        String::from("{alias} unmounted")
+    }
+
     fn default_unit() -> Unit {
         Unit::GB
     }
@@ -147,12 +398,12 @@ impl DiskSpaceConfig {
         Duration::from_secs(20)
     }
 
-    fn default_warning() -> f64 {
-        20.
+    fn default_warning() -> Threshold {
+        Threshold::Unitless(20.)
     }
 
-    fn default_alert() -> f64 {
-        10.
+    fn default_alert() -> Threshold {
+        Threshold::Unitless(10.)
     }
 
     // Deprecated with format string, kept for previous config support
@@ -168,6 +419,10 @@ impl DiskSpaceConfig {
     fn default_alert_absolute() -> bool {
         false
     }
+
+    fn default_trend_window() -> Duration {
+        Duration::from_secs(24 * 60 * 60)
+    }
 }
 
 enum AlertType {
@@ -214,28 +469,96 @@ impl ConfigBlock for DiskSpace {
             .cloned()
             .expect("Could not find disk drive icon");
 
+        // Bare numbers keep their historical meaning
+        let (alert_absolute, unit) = (block_config.alert_absolute, block_config.unit);
+        let normalize = |threshold| match threshold {
+            Threshold::Unitless(value) if alert_absolute => {
+                Threshold::Bytes(value * unit.bytes_per_unit())
+            }
+            Threshold::Unitless(value) => Threshold::Percent(value),
+            threshold => threshold,
+        };
+
+        let (target, name) = match (block_config.uuid, block_config.label) {
+            (Some(_), Some(_)) => {
+                return Err(ConfigurationError(
+                    "disk_space".to_string(),
+                    (
+                        "only one of uuid and label can be set".to_string(),
+                        "both uuid and label set".to_string(),
+                    ),
+                ))
+            }
+            (Some(uuid), None) => (
+                Target::Device(Path::new(DISK_BY_UUID_PATH).join(&uuid)),
+                format!("uuid_{}", uuid),
+            ),
+            (None, Some(label)) => (
+                Target::Device(Path::new(DISK_BY_LABEL_PATH).join(escape_label(&label))),
+                format!("label_{}", label.replace('/', "_")),
+            ),
+            (None, None) => {
+                let name = match block_config.path.trim_matches('/') {
+                    "" => "root".to_string(),
+                    path => path.replace('/', "_"),
+                };
+                (Target::Path(block_config.path), name)
+            }
+        };
+
+        // Only sample when the projection is used
+        let trend = if block_config.format.contains("{full_in}")
+            || block_config.full_in_warning.is_some()
+        {
+            let file = block_config.samples_file.unwrap_or_else(|| {
+                xdg_cache_home()
+                    .join("i3status-rust")
+                    .join(format!("disk_space_{}", name))
+            });
+            Some(Trend::load(file, block_config.trend_window))
+        } else {
+            None
+        };
+
+        let id = Uuid::new_v4().to_simple().to_string();
+
         Ok(DiskSpace {
-            id: Uuid::new_v4().to_simple().to_string(),
+            disk_space: ButtonWidget::new(config, &id),
+            id,
             update_interval: block_config.interval,
-            disk_space: TextWidget::new(config),
             alias: block_config.alias,
-            path: block_config.path,
+            target,
             format: FormatTemplate::from_string(&block_config.format)?,
+            unmounted_format: FormatTemplate::from_string(&block_config.unmounted_format)?,
             info_type: block_config.info_type,
             unit: block_config.unit,
-            warning: block_config.warning,
-            alert: block_config.alert,
+            warning: normalize(block_config.warning),
+            alert: normalize(block_config.alert),
             show_percentage: block_config.show_percentage,
             show_bar: block_config.show_bar,
-            alert_absolute: block_config.alert_absolute,
             icon,
+            trend,
+            full_in_warning: block_config.full_in_warning,
         })
     }
 }
 
 impl Block for DiskSpace {
     fn update(&mut self) -> Result<Option<Update>> {
-        let statvfs = statvfs(Path::new(self.path.as_str()))
+        let mountpoint = match self.target.mountpoint()? {
+            Some(mountpoint) => mountpoint,
+            None => {
+                let values = map!("{alias}" => self.alias.clone(),
+                "{icon}" => self.icon.to_string()
+                );
+                self.disk_space
+                    .set_text(self.unmounted_format.render_static_str(&values)?);
+                self.disk_space.set_state(State::Info);
+                return Ok(Some(self.update_interval.into()));
+            }
+        };
+
+        let statvfs = statvfs(Path::new(mountpoint.as_str()))
             .block_error("disk_space", "failed to retrieve statvfs")?;
 
         let result;
@@ -276,30 +599,49 @@ impl Block for DiskSpace {
             self.format = FormatTemplate::from_string("{alias} {result} {unit} {bar}")?;
         }
 
+        let full_in = match self.trend {
+            Some(ref mut trend) => {
+                let now = SystemTime::now()
+                    .duration_since(UNIX_EPOCH)
+                    .internal_error("disk_space", "system time is before the epoch")?
+                    .as_secs();
+                trend.record(now, available)?;
+                trend.full_in(total)
+            }
+            None => None,
+        };
+
         let values = map!("{percentage}" => format!("{:.2}%", percentage),
         "{bar}" => format_percent_bar(percentage),
         "{alias}" => self.alias.clone(),
         "{unit}" => format!("{:?}", self.unit),
-        "{path}" => self.path.clone(),
+        "{path}" => mountpoint.clone(),
+        "{mountpoint}" => mountpoint,
         "{total}" => format!("{:.2}", Unit::bytes_in_unit(self.unit, total)),
         "{used}" => format!("{:.2}", Unit::bytes_in_unit(self.unit, used)),
         "{available}" => format!("{:.2}", Unit::bytes_in_unit(self.unit, available)),
         "{free}" => format!("{:.2}", Unit::bytes_in_unit(self.unit, free)),
         "{icon}" => self.icon.to_string(),
-        "{result}" => format!("{:.2}", result)
+        "{result}" => format!("{:.2}", result),
+        "{full_in}" => full_in.map(format_full_in).unwrap_or_default()
         );
         self.disk_space
             .set_text(self.format.render_static_str(&values)?);
 
-        // Send percentage to alert check if we don't want absolute alerts
-        let alert_val = if !self.alert_absolute {
-            percentage as f64
-        } else {
-            Unit::bytes_in_unit(self.unit, result)
+        let state = self.compute_state(
+            result as f64,
+            self.warning.to_bytes(total),
+            self.alert.to_bytes(total),
+            alert_type,
+        );
+        let filling_up = match (full_in, self.full_in_warning) {
+            (Some(full_in), Some(warning)) => full_in <= warning.as_secs(),
+            _ => false,
         };
-
-        let state = self.compute_state(alert_val, self.warning, self.alert, alert_type);
-        self.disk_space.set_state(state);
+        self.disk_space.set_state(match state {
+            State::Idle if filling_up => State::Warning,
+            state => state,
+        });
 
         Ok(Some(self.update_interval.into()))
     }
@@ -308,6 +650,11 @@ impl Block for DiskSpace {
         vec![&self.disk_space]
     }
 
+    fn set_format(&mut self, format: &str) -> Result<()> {
+        self.format = FormatTemplate::from_string(format)?;
+        Ok(())
+    }
+
     fn id(&self) -> &str {
         &self.id
     }
@@ -12,6 +12,7 @@ use crate::blocks::{Block, ConfigBlock, Update};
 use crate::config::Config;
 use crate::errors::*;
 use crate::scheduler::Task;
+use crate::util::MarkupEscape;
 use crate::widget::I3BarWidget;
 use crate::widgets::text::TextWidget;
 
@@ -213,7 +214,7 @@ impl Block for FocusedWindow {
                 }
             }
         };
-        self.text.set_text(out_str);
+        self.text.set_text(MarkupEscape::current().apply(&out_str));
 
         Ok(None)
     }
@@ -0,0 +1,340 @@
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::process::Command;
+use std::time::{Duration, Instant};
+
+use crossbeam_channel::Sender;
+use serde_derive::Deserialize;
+use uuid::Uuid;
+
+use crate::blocks::{Block, ConfigBlock, Update};
+use crate::config::Config;
+use crate::de::deserialize_duration;
+use crate::errors::*;
+use crate::scheduler::Task;
+use crate::util::{read_file, read_sysfs, FormatTemplate};
+use crate::widget::{I3BarWidget, State};
+use crate::widgets::button::ButtonWidget;
+
+const DRM_PATH: &str = "/sys/class/drm";
+
+const VENDOR_NVIDIA: &str = "0x10de";
+const VENDOR_AMD: &str = "0x1002";
+const VENDOR_INTEL: &str = "0x8086";
+
+#[derive(Deserialize, Debug, Copy, Clone, PartialEq, Eq, Default)]
+#[serde(rename_all = "lowercase")]
+pub enum GpuBackend {
+    #[default]
+    Auto,
+    Nvidia,
+    Amdgpu,
+    I915,
+}
+
+/// A single sample of the GPU statistics. Backends leave the fields they
+/// cannot provide as `None`.
+#[derive(Debug, Default)]
+struct GpuStats {
+    /// Utilization in percent
+    util: Option<f64>,
+    /// Temperature in degrees C
+    temp: Option<f64>,
+    /// Used VRAM in MiB
+    vram: Option<f64>,
+    /// Power draw in watts
+    power: Option<f64>,
+    /// Current graphics clock in MHz
+    freq: Option<f64>,
+}
+
+enum Device {
+    /// Queried through `nvidia-smi`, which is a frontend to NVML.
+    Nvidia { index: usize },
+    Amdgpu {
+        device: PathBuf,
+        hwmon: Option<PathBuf>,
+    },
+    I915 {
+        /// The /sys/class/drm/cardN directory, which holds the frequencies
+        card: PathBuf,
+        hwmon: Option<PathBuf>,
+        /// Last energy reading in microjoules, used to derive the power draw
+        last_energy: Option<(u64, Instant)>,
+    },
+}
+
+impl Device {
+    /// Finds the `index`-th GPU under /sys/class/drm, optionally restricted to
+    /// one backend.
+    fn find(backend: GpuBackend, index: usize) -> Result<Self> {
+        let mut cards: Vec<(usize, PathBuf)> = fs::read_dir(DRM_PATH)
+            .block_error("gpu", &format!("failed to read {}", DRM_PATH))?
+            .filter_map(|entry| entry.ok())
+            .filter_map(|entry| {
+                let name = entry.file_name().to_string_lossy().into_owned();
+                // Skip connectors such as card0-DP-1
+                let number = name.strip_prefix("card")?.parse::<usize>().ok()?;
+                Some((number, entry.path().join("device")))
+            })
+            .collect();
+        cards.sort_by_key(|(number, _)| *number);
+
+        let mut nvidia_index = 0;
+        let mut candidates = Vec::new();
+        for (_, device) in cards {
+            let vendor = read_file("gpu", &device.join("vendor")).unwrap_or_default();
+            let (card_backend, nvidia) = match vendor.as_str() {
+                VENDOR_NVIDIA => {
+                    nvidia_index += 1;
+                    (GpuBackend::Nvidia, Some(nvidia_index - 1))
+                }
+                VENDOR_AMD => (GpuBackend::Amdgpu, None),
+                VENDOR_INTEL => (GpuBackend::I915, None),
+                _ => continue,
+            };
+            if backend == GpuBackend::Auto || backend == card_backend {
+                candidates.push((card_backend, device, nvidia));
+            }
+        }
+
+        let (backend, device, nvidia) = candidates.into_iter().nth(index).block_error(
+            "gpu",
+            &format!("no supported GPU found with device index {}", index),
+        )?;
+        let hwmon = find_hwmon(&device);
+        Ok(match backend {
+            GpuBackend::Nvidia => Device::Nvidia {
+                index: nvidia.unwrap_or(0),
+            },
+            GpuBackend::Amdgpu => Device::Amdgpu { device, hwmon },
+            _ => Device::I915 {
+                card: device.parent().map(Path::to_path_buf).unwrap_or_default(),
+                hwmon,
+                last_energy: None,
+            },
+        })
+    }
+
+    /// Format using only the values the backend can provide.
+    fn default_format(&self) -> &'static str {
+        match self {
+            Device::I915 { .. } => "{freq}",
+            _ => "{util} {temp}",
+        }
+    }
+
+    fn stats(&mut self) -> Result<GpuStats> {
+        match self {
+            Device::Nvidia { index } => nvidia_stats(*index),
+            Device::Amdgpu { device, hwmon } => Ok(GpuStats {
+                util: read_number(&device.join("gpu_busy_percent")),
+                temp: hwmon
+                    .as_ref()
+                    .and_then(|hwmon| read_number(&hwmon.join("temp1_input")))
+                    .map(|temp| temp / 1000.),
+                vram: read_number(&device.join("mem_info_vram_used"))
+                    .map(|used| used / 1024. / 1024.),
+                power: hwmon
+                    .as_ref()
+                    .and_then(|hwmon| {
+                        read_number(&hwmon.join("power1_average"))
+                            .or_else(|| read_number(&hwmon.join("power1_input")))
+                    })
+                    .map(|power| power / 1_000_000.),
+                freq: None,
+            }),
+            Device::I915 {
+                card,
+                hwmon,
+                last_energy,
+            } => {
+                let energy = hwmon
+                    .as_ref()
+                    .and_then(|hwmon| read_number(&hwmon.join("energy1_input")))
+                    .map(|energy| (energy as u64, Instant::now()));
+                let power = match (*last_energy, energy) {
+                    (Some((last, last_time)), Some((now, now_time))) if now >= last => {
+                        let elapsed = now_time.duration_since(last_time).as_secs_f64();
+                        if elapsed > 0. {
+                            Some((now - last) as f64 / 1_000_000. / elapsed)
+                        } else {
+                            None
+                        }
+                    }
+                    _ => None,
+                };
+                *last_energy = energy;
+                Ok(GpuStats {
+                    power,
+                    // Xe graphics only have the per-GT attribute
+                    freq: read_number(&card.join("gt_act_freq_mhz"))
+                        .or_else(|| read_number(&card.join("gt/gt0/rps_act_freq_mhz"))),
+                    ..GpuStats::default()
+                })
+            }
+        }
+    }
+}
+
+fn find_hwmon(device: &Path) -> Option<PathBuf> {
+    fs::read_dir(device.join("hwmon"))
+        .ok()?
+        .filter_map(|entry| entry.ok())
+        .map(|entry| entry.path())
+        .next()
+}
+
+fn read_number(path: &Path) -> Option<f64> {
+    read_sysfs("gpu", path).ok()
+}
+
+fn nvidia_stats(index: usize) -> Result<GpuStats> {
+    let output = Command::new("nvidia-smi")
+        .arg("-i")
+        .arg(index.to_string())
+        .arg("--query-gpu=utilization.gpu,temperature.gpu,memory.used,power.draw,clocks.gr")
+        .arg("--format=csv,noheader,nounits")
+        .output()
+        .block_error("gpu", "failed to execute nvidia-smi")?;
+    let output = String::from_utf8(output.stdout)
+        .block_error("gpu", "nvidia-smi produced non-UTF8 output")?;
+    // Unsupported fields are reported as "[N/A]" or "[Not Supported]"
+    let mut fields = output.trim().split(", ").map(|f| f.parse::<f64>().ok());
+    Ok(GpuStats {
+        util: fields.next().flatten(),
+        temp: fields.next().flatten(),
+        vram: fields.next().flatten(),
+        power: fields.next().flatten(),
+        freq: fields.next().flatten(),
+    })
+}
+
+pub struct Gpu {
+    id: String,
+    text: ButtonWidget,
+    device: Device,
+    format: FormatTemplate,
+    update_interval: Duration,
+    warning: f64,
+    critical: f64,
+}
+
+#[derive(Deserialize, Debug, Default, Clone)]
+#[serde(deny_unknown_fields)]
+pub struct GpuConfig {
+    /// Update interval in seconds
+    #[serde(
+        default = "GpuConfig::default_interval",
+        deserialize_with = "deserialize_duration"
+    )]
+    pub interval: Duration,
+
+    /// Backend to read the GPU statistics from
+    #[serde(default)]
+    pub backend: GpuBackend,
+
+    /// Index of the GPU among the ones supported by the backend
+    #[serde(default = "GpuConfig::default_device")]
+    pub device: usize,
+
+    /// Format override, the default depends on the backend
+    #[serde(default)]
+    pub format: Option<String>,
+
+    /// Minimum temperature, where state is set to warning
+    #[serde(default = "GpuConfig::default_warning")]
+    pub warning: f64,
+
+    /// Minimum temperature, where state is set to critical
+    #[serde(default = "GpuConfig::default_critical")]
+    pub critical: f64,
+}
+
+impl GpuConfig {
+    fn default_interval() -> Duration {
+        Duration::from_secs(3)
+    }
+
+    fn default_device() -> usize {
+        0
+    }
+
+    fn default_warning() -> f64 {
+        75.
+    }
+
+    fn default_critical() -> f64 {
+        85.
+    }
+}
+
+impl ConfigBlock for Gpu {
+    type Config = GpuConfig;
+
+    fn new(
+        block_config: Self::Config,
+        config: Config,
+        _tx_update_request: Sender<Task>,
+    ) -> Result<Self> {
+        let id = Uuid::new_v4().to_simple().to_string();
+        let device = Device::find(block_config.backend, block_config.device)?;
+        let format = match block_config.format {
+            Some(format) => format,
+            None => device.default_format().to_string(),
+        };
+
+        Ok(Gpu {
+            text: ButtonWidget::new(config, &id).with_icon("gpu"),
+            id,
+            device,
+            format: FormatTemplate::from_string(&format)
+                .block_error("gpu", "Invalid format specified for gpu")?,
+            update_interval: block_config.interval,
+            warning: block_config.warning,
+            critical: block_config.critical,
+        })
+    }
+}
+
+/// Renders a value, or an empty string if the backend does not provide it.
+fn or_empty(value: Option<f64>, render: impl Fn(f64) -> String) -> String {
+    value.map(render).unwrap_or_default()
+}
+
+impl Block for Gpu {
+    fn update(&mut self) -> Result<Option<Update>> {
+        let stats = self.device.stats()?;
+
+        let values = map!(
+            "{util}" => or_empty(stats.util, |util| format!("{:02}%", util as u64)),
+            "{temp}" => or_empty(stats.temp, |temp| format!("{:02}°C", temp as i64)),
+            "{vram}" => or_empty(stats.vram, |vram| format!("{}MiB", vram as u64)),
+            "{power}" => or_empty(stats.power, |power| format!("{:.1}W", power)),
+            "{freq}" => or_empty(stats.freq, |freq| format!("{}MHz", freq as u64))
+        );
+
+        self.text.set_state(match stats.temp {
+            Some(temp) if temp >= self.critical => State::Critical,
+            Some(temp) if temp >= self.warning => State::Warning,
+            _ => State::Idle,
+        });
+        self.text.set_text(self.format.render_static_str(&values)?);
+
+        Ok(Some(self.update_interval.into()))
+    }
+
+    fn view(&self) -> Vec<&dyn I3BarWidget> {
+        vec![&self.text]
+    }
+
+    fn set_format(&mut self, format: &str) -> Result<()> {
+        self.format = FormatTemplate::from_string(format)
+            .block_error("gpu", "Invalid format specified for gpu")?;
+        Ok(())
+    }
+
+    fn id(&self) -> &str {
+        &self.id
+    }
+}
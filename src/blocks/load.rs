@@ -13,10 +13,10 @@ use crate::errors::*;
 use crate::scheduler::Task;
 use crate::util::FormatTemplate;
 use crate::widget::{I3BarWidget, State};
-use crate::widgets::text::TextWidget;
+use crate::widgets::button::ButtonWidget;
 
 pub struct Load {
-    text: TextWidget,
+    text: ButtonWidget,
     logical_cores: u32,
     format: FormatTemplate,
     id: String,
@@ -80,7 +80,8 @@ impl ConfigBlock for Load {
         config: Config,
         _tx_update_request: Sender<Task>,
     ) -> Result<Self> {
-        let text = TextWidget::new(config)
+        let id = Uuid::new_v4().to_simple().to_string();
+        let text = ButtonWidget::new(config, &id)
             .with_icon("cogs")
             .with_state(State::Info);
 
@@ -93,7 +94,7 @@ impl ConfigBlock for Load {
             .count() as u32;
 
         Ok(Load {
-            id: Uuid::new_v4().to_simple().to_string(),
+            id,
             logical_cores,
             update_interval: block_config.interval,
             minimum_info: block_config.info,
@@ -146,6 +147,12 @@ impl Block for Load {
         vec![&self.text]
     }
 
+    fn set_format(&mut self, format: &str) -> Result<()> {
+        self.format = FormatTemplate::from_string(format)
+            .block_error("load", "Invalid format specified for load")?;
+        Ok(())
+    }
+
     fn id(&self) -> &str {
         &self.id
     }
@@ -74,6 +74,14 @@ impl Unit {
         }
     }
 
+    /// Formats the value with the largest fitting unit, e.g. `512MiB` or `3.2GiB`.
+    fn human(&self) -> String {
+        match self.gib() {
+            Unit::GiB(n) if n >= 1. => format!("{:.1}GiB", n),
+            _ => format!("{}MiB", self.mib()),
+        }
+    }
+
     fn percent(&self, reference: Unit) -> f32 {
         if reference.n() < 1 {
             100f32
@@ -88,6 +96,7 @@ impl Unit {
 struct Memstate {
     mem_total: (u64, bool),
     mem_free: (u64, bool),
+    mem_available: (u64, bool),
     buffers: (u64, bool),
     cached: (u64, bool),
     s_reclaimable: (u64, bool),
@@ -105,6 +114,19 @@ impl Memstate {
         self.mem_free.0
     }
 
+    /// Memory that can be allocated without swapping, as estimated by the
+    /// kernel. Kernels before 3.14 do not report it, then it is estimated as
+    /// the free memory plus the buffers and the reclaimable caches.
+    fn mem_available(&self) -> u64 {
+        if self.mem_available.1 {
+            self.mem_available.0
+        } else {
+            (self.mem_free() + self.buffers() + self.cached() + self.s_reclaimable())
+                .saturating_sub(self.shmem())
+                .min(self.mem_total())
+        }
+    }
+
     fn buffers(&self) -> u64 {
         self.buffers.0
     }
@@ -133,6 +155,7 @@ impl Memstate {
         Memstate {
             mem_total: (0, false),
             mem_free: (0, false),
+            mem_available: (0, false),
             buffers: (0, false),
             cached: (0, false),
             s_reclaimable: (0, false),
@@ -145,6 +168,7 @@ impl Memstate {
     fn done(&self) -> bool {
         self.mem_total.1
             && self.mem_free.1
+            && self.mem_available.1
             && self.buffers.1
             && self.cached.1
             && self.s_reclaimable.1
@@ -152,6 +176,27 @@ impl Memstate {
             && self.swap_total.1
             && self.swap_free.1
     }
+
+    /// Averages every value with the previous, already smoothed, state.
+    fn smoothed(&self, previous: &Memstate, smoothing: f64) -> Memstate {
+        let smooth = |previous: (u64, bool), sample: (u64, bool)| {
+            (
+                ewma(previous.0 as f64, sample.0 as f64, smoothing).round() as u64,
+                sample.1,
+            )
+        };
+        Memstate {
+            mem_total: smooth(previous.mem_total, self.mem_total),
+            mem_free: smooth(previous.mem_free, self.mem_free),
+            mem_available: smooth(previous.mem_available, self.mem_available),
+            buffers: smooth(previous.buffers, self.buffers),
+            cached: smooth(previous.cached, self.cached),
+            s_reclaimable: smooth(previous.s_reclaimable, self.s_reclaimable),
+            shmem: smooth(previous.shmem, self.shmem),
+            swap_total: smooth(previous.swap_total, self.swap_total),
+            swap_free: smooth(previous.swap_free, self.swap_free),
+        }
+    }
 }
 
 #[derive(Clone, Debug)]
@@ -165,6 +210,9 @@ pub struct Memory {
     tx_update_request: Sender<Task>,
     warning: (f64, f64),
     critical: (f64, f64),
+    smoothing: f64,
+    smoothed: Option<Memstate>,
+    use_available: bool,
 }
 
 #[derive(Deserialize, Debug, Clone)]
@@ -211,6 +259,17 @@ pub struct MemoryConfig {
     /// Percentage of swap usage, where state is set to critical
     #[serde(default = "MemoryConfig::default_critical_swap")]
     pub critical_swap: f64,
+
+    /// Weight of the previous values in an exponential moving average, from 0
+    /// (no smoothing) up to but excluding 1.
+    #[serde(default = "MemoryConfig::default_smoothing")]
+    pub smoothing: f64,
+
+    /// Whether the used memory and the state are based on the available memory
+    /// reported by the kernel, like `free` and `top` do, rather than on the free
+    /// memory, buffers and caches.
+    #[serde(default = "MemoryConfig::default_use_available")]
+    pub use_available: bool,
 }
 
 impl MemoryConfig {
@@ -253,6 +312,14 @@ impl MemoryConfig {
     fn default_critical_swap() -> f64 {
         95.0
     }
+
+    fn default_smoothing() -> f64 {
+        0.0
+    }
+
+    fn default_use_available() -> bool {
+        false
+    }
 }
 
 impl Memory {
@@ -265,7 +332,13 @@ impl Memory {
         let mem_total_used = Unit::KiB(mem_total.n() - mem_free.n());
         let buffers = Unit::KiB(mem_state.buffers());
         let cached = Unit::KiB(mem_state.cached() + mem_state.s_reclaimable() - mem_state.shmem());
-        let mem_used = Unit::KiB(mem_total_used.n() - (buffers.n() + cached.n()));
+        let mem_available = Unit::KiB(mem_state.mem_available());
+        let mem_used = if self.use_available {
+            Unit::KiB(mem_total.n().saturating_sub(mem_available.n()))
+        } else {
+            // Smoothed values are rounded separately and may not add up exactly
+            Unit::KiB(mem_total_used.n().saturating_sub(buffers.n() + cached.n()))
+        };
         let mem_avail = Unit::KiB(mem_total.n() - mem_used.n());
 
         let values = map!(
@@ -312,7 +385,9 @@ impl Memory {
             "{Cm}" => format!("{}", cached.mib()),
             "{Cp}" => format!("{:.2}", cached.percent(mem_total)),
             "{Cpi}" => format!("{:02}", cached.percent(mem_total) as i32),
-            "{Cpb}" => format_percent_bar(cached.percent(mem_total)));
+            "{Cpb}" => format_percent_bar(cached.percent(mem_total)),
+            "{free}" => mem_free.human(),
+            "{available}" => mem_available.human());
 
         match self.memtype {
             Memtype::Memory => self.output.0.set_state(match mem_used.percent(mem_total) {
@@ -371,6 +446,9 @@ impl ConfigBlock for Memory {
             tx_update_request: tx,
             warning: (block_config.warning_mem, block_config.warning_swap),
             critical: (block_config.critical_mem, block_config.critical_swap),
+            smoothing: check_smoothing("memory", block_config.smoothing)?,
+            smoothed: None,
+            use_available: block_config.use_available,
         })
     }
 }
@@ -415,6 +493,14 @@ impl Block for Memory {
                     );
                     continue;
                 }
+                Some(&"MemAvailable:") => {
+                    mem_state.mem_available = (
+                        u64::from_str(line[1])
+                            .block_error("memory", "failed to parse mem_available")?,
+                        true,
+                    );
+                    continue;
+                }
                 Some(&"Buffers:") => {
                     mem_state.buffers = (
                         u64::from_str(line[1]).block_error("memory", "failed to parse buffers")?,
@@ -466,6 +552,11 @@ impl Block for Memory {
             }
         }
 
+        if let Some(ref previous) = self.smoothed {
+            mem_state = mem_state.smoothed(previous, self.smoothing);
+        }
+        self.smoothed = Some(mem_state);
+
         // Now, create the string to be shown
         let output_text = self.format_insert_values(mem_state)?;
 
@@ -499,3 +590,30 @@ impl Block for Memory {
         }]
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use crate::blocks::memory::{Memstate, Unit};
+
+    #[test]
+    fn test_mem_available() {
+        let mut state = Memstate::new();
+        state.mem_total = (16_000_000, true);
+        state.mem_free = (1_000_000, true);
+        state.buffers = (500_000, true);
+        state.cached = (6_000_000, true);
+        state.s_reclaimable = (700_000, true);
+        state.shmem = (200_000, true);
+        // Old kernels do not report MemAvailable
+        assert_eq!(state.mem_available(), 8_000_000);
+
+        state.mem_available = (7_500_000, true);
+        assert_eq!(state.mem_available(), 7_500_000);
+    }
+
+    #[test]
+    fn test_unit_human() {
+        assert_eq!(Unit::KiB(524_288).human(), "512MiB");
+        assert_eq!(Unit::KiB(3_355_443).human(), "3.2GiB");
+    }
+}
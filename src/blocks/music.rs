@@ -23,6 +23,7 @@ use crate::errors::*;
 use crate::input::{I3BarEvent, MouseButton};
 use crate::scheduler::Task;
 use crate::subprocess::spawn_child_async;
+use crate::util::MarkupEscape;
 use crate::widget::{I3BarWidget, State};
 use crate::widgets::button::ButtonWidget;
 use crate::widgets::rotatingtext::RotatingTextWidget;
@@ -33,6 +34,9 @@ pub struct Music {
     prev: Option<ButtonWidget>,
     play: Option<ButtonWidget>,
     next: Option<ButtonWidget>,
+    volume_widget: Option<ButtonWidget>,
+    volume_id: String,
+    volume: Option<f64>,
     on_collapsed_click_widget: ButtonWidget,
     on_collapsed_click: Option<String>,
     on_click: Option<String>,
@@ -45,11 +49,21 @@ pub struct Music {
     max_width: usize,
     separator: String,
     seek_step: i64,
+    scroll_action: MusicScrollAction,
+    volume_step: f64,
     config: Config,
     interface_name_exclude_regexps: Vec<Regex>,
     hide_when_empty: bool,
 }
 
+#[derive(Deserialize, Debug, Copy, Clone, PartialEq, Eq, Default)]
+#[serde(rename_all = "lowercase")]
+pub enum MusicScrollAction {
+    #[default]
+    Seek,
+    Volume,
+}
+
 #[derive(Deserialize, Debug, Default, Clone)]
 #[serde(deny_unknown_fields)]
 pub struct MusicConfig {
@@ -106,6 +120,18 @@ pub struct MusicConfig {
     #[serde(default = "MusicConfig::default_seek_step")]
     pub seek_step: i64,
 
+    /// Whether scrolling over the song seeks or changes the player's volume.
+    #[serde(default)]
+    pub scroll_action: MusicScrollAction,
+
+    /// Step in percent by which scrolling changes the player's volume.
+    #[serde(default = "MusicConfig::default_volume_step")]
+    pub volume_step: f64,
+
+    /// Show the player's own volume, scrolling over it changes the volume.
+    #[serde(default = "MusicConfig::default_show_volume")]
+    pub show_volume: bool,
+
     /// MPRIS interface name regex patterns to ignore.
     #[serde(default = "MusicConfig::default_interface_name_exclude_patterns")]
     pub interface_name_exclude: Vec<String>,
@@ -159,6 +185,14 @@ impl MusicConfig {
         1000
     }
 
+    fn default_volume_step() -> f64 {
+        5.0
+    }
+
+    fn default_show_volume() -> bool {
+        false
+    }
+
     fn default_interface_name_exclude_patterns() -> Vec<String> {
         vec![]
     }
@@ -228,6 +262,13 @@ impl ConfigBlock for Music {
             };
         }
 
+        let volume_id = format!("{}_volume", id_copy);
+        let volume_widget = if block_config.show_volume {
+            Some(ButtonWidget::new(config.clone(), &volume_id).with_state(State::Info))
+        } else {
+            None
+        };
+
         fn compile_regexps(patterns: Vec<String>) -> result::Result<Vec<Regex>, regex::Error> {
             patterns.iter().map(|p| Regex::new(&p)).collect()
         }
@@ -247,6 +288,9 @@ impl ConfigBlock for Music {
             prev,
             play,
             next,
+            volume_widget,
+            volume_id,
+            volume: None,
             on_click: block_config.on_click,
             on_collapsed_click_widget: ButtonWidget::new(config.clone(), "on_collapsed_click")
                 .with_icon("music")
@@ -269,6 +313,8 @@ impl ConfigBlock for Music {
             max_width: block_config.max_width,
             separator: block_config.separator,
             seek_step: block_config.seek_step,
+            scroll_action: block_config.scroll_action,
+            volume_step: block_config.volume_step / 100.0,
             config,
             interface_name_exclude_regexps: compile_regexps(block_config.interface_name_exclude)
                 .block_error("music", "failed to parse exclude patterns")?,
@@ -277,6 +323,24 @@ impl ConfigBlock for Music {
     }
 }
 
+impl Music {
+    /// Changes the player's own volume by `step`, clamped to 0.0-1.0.
+    /// Does nothing for players without a Volume property.
+    fn step_volume(&mut self, step: f64) -> Result<()> {
+        let (player, volume) = match (&self.player, self.volume) {
+            (Some(player), Some(volume)) => (player.clone(), volume),
+            _ => return Ok(()),
+        };
+        let volume = (volume + step).clamp(0.0, 1.0);
+        self.dbus_conn
+            .with_path(player, "/org/mpris/MediaPlayer2", 1000)
+            .set("org.mpris.MediaPlayer2.Player", "Volume", volume)
+            .block_error("music", "failed to set the player's volume")?;
+        self.volume = Some(volume);
+        Ok(())
+    }
+}
+
 impl Block for Music {
     fn id(&self) -> &str {
         &self.id
@@ -301,6 +365,8 @@ impl Block for Music {
             let data = c.get("org.mpris.MediaPlayer2.Player", "Metadata");
 
             if let Ok(metadata) = data {
+                // Only the song is escaped, `separator` is part of the format
+                let escape = |text: &str| MarkupEscape::current().apply(text);
                 let (mut title, mut artist) =
                     extract_from_metadata(&metadata).unwrap_or((String::new(), String::new()));
 
@@ -314,33 +380,28 @@ impl Block for Music {
                         + self.separator.chars().count()
                         + artist.chars().count();
                     if textlen < self.max_width || !self.smart_trim {
-                        self.current_song
-                            .set_text(format!("{}{}{}", title, self.separator, artist));
+                        self.current_song.set_text(format!(
+                            "{}{}{}",
+                            escape(&title),
+                            self.separator,
+                            escape(&artist)
+                        ));
                     } else if title.is_empty() {
                         // Only display artist, truncated appropriately
-                        self.current_song.set_text({
-                            match artist.char_indices().nth(self.max_width) {
-                                None => artist.to_string(),
-                                Some((i, _)) => {
-                                    artist.truncate(i);
-                                    artist.to_string()
-                                }
-                            }
-                        });
+                        if let Some((i, _)) = artist.char_indices().nth(self.max_width) {
+                            artist.truncate(i);
+                        }
+                        self.current_song.set_text(escape(&artist));
                     } else if artist.is_empty() {
                         // Only display title, truncated appropriately
-                        self.current_song.set_text({
-                            match title.char_indices().nth(self.max_width) {
-                                None => title.to_string(),
-                                Some((i, _)) => {
-                                    title.truncate(i);
-                                    title.to_string()
-                                }
-                            }
-                        });
+                        if let Some((i, _)) = title.char_indices().nth(self.max_width) {
+                            title.truncate(i);
+                        }
+                        self.current_song.set_text(escape(&title));
                     } else {
                         // Below code is by https://github.com/jgbyrne
-                        let text = format!("{}{}{}", title, self.separator, artist);
+                        let text =
+                            format!("{}{}{}", escape(&title), self.separator, escape(&artist));
                         if textlen > self.max_width {
                             // overshoot: # of chars we need to trim
                             // substance: # of chars available for trimming
@@ -395,8 +456,12 @@ impl Block for Music {
                             artist.truncate(aidx);
 
                             // Produce final formatted string
-                            self.current_song
-                                .set_text(format!("{}{}{}", title, self.separator, artist));
+                            self.current_song.set_text(format!(
+                                "{}{}{}",
+                                escape(&title),
+                                self.separator,
+                                escape(&artist)
+                            ));
                         } else {
                             self.current_song.set_text(text);
                         }
@@ -410,6 +475,20 @@ impl Block for Music {
                 }
             }
 
+            // Players that do not implement the Volume property are left alone
+            self.volume = c
+                .get("org.mpris.MediaPlayer2.Player", "Volume")
+                .ok()
+                .and_then(|volume: Box<dyn RefArg>| volume.as_f64());
+            if let (Some(ref mut widget), Some(volume)) = (&mut self.volume_widget, self.volume) {
+                widget.set_icon(match volume {
+                    v if v <= 0.0 => "volume_empty",
+                    v if v < 0.5 => "volume_half",
+                    _ => "volume_full",
+                });
+                widget.set_text(format!("{:02}%", (volume * 100.0).round() as i64));
+            }
+
             if let Some(ref mut play) = self.play {
                 let data = c.get("org.mpris.MediaPlayer2.Player", "PlaybackStatus");
                 match data {
@@ -468,7 +547,15 @@ impl Block for Music {
                     }
                 }
                 _ => {
-                    if name.as_str() == self.id {
+                    if *name == self.volume_id
+                        || (*name == self.id && self.scroll_action == MusicScrollAction::Volume)
+                    {
+                        match self.config.scrolling.to_logical_direction(event.button) {
+                            Some(LogicalDirection::Up) => self.step_volume(self.volume_step)?,
+                            Some(LogicalDirection::Down) => self.step_volume(-self.volume_step)?,
+                            None => {}
+                        }
+                    } else if name.as_str() == self.id {
                         let m = Message::new_method_call(
                             self.player.as_ref().unwrap(),
                             "/org/mpris/MediaPlayer2",
@@ -513,6 +600,9 @@ impl Block for Music {
             if let Some(ref next) = self.next {
                 elements.push(next);
             }
+            if let (Some(ref volume), Some(_)) = (&self.volume_widget, self.volume) {
+                elements.push(volume);
+            }
             elements
         } else if self.current_song.is_empty() {
             vec![&self.on_collapsed_click_widget]
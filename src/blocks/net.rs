@@ -1,6 +1,7 @@
+use std::collections::VecDeque;
 use std::ffi::OsStr;
 use std::fmt;
-use std::fs::{read_to_string, OpenOptions};
+use std::fs::{read_dir, read_to_string, OpenOptions};
 use std::io::prelude::*;
 use std::io::ErrorKind;
 use std::path::{Path, PathBuf};
@@ -22,7 +23,8 @@ use crate::input::{I3BarEvent, MouseButton};
 use crate::scheduler::Task;
 use crate::subprocess::spawn_child_async;
 use crate::util::{
-    escape_pango_text, format_percent_bar, format_speed, format_vec_to_bar_graph, FormatTemplate,
+    format_percent_bar, format_speed, format_speed_fixed, format_vec_to_bar_graph, read_sysfs,
+    FormatTemplate,
 };
 use crate::widget::{I3BarWidget, Spacing};
 use crate::widgets::button::ButtonWidget;
@@ -141,16 +143,59 @@ impl NetworkDevice {
 
     /// Query the device for the current `tx_bytes` statistic.
     pub fn tx_bytes(&self) -> Result<u64> {
-        read_file(&self.device_path.join("statistics/tx_bytes"))?
-            .parse::<u64>()
-            .block_error("net", "Failed to parse tx_bytes")
+        self.statistic("tx_bytes")
     }
 
     /// Query the device for the current `rx_bytes` statistic.
     pub fn rx_bytes(&self) -> Result<u64> {
-        read_file(&self.device_path.join("statistics/rx_bytes"))?
-            .parse::<u64>()
-            .block_error("net", "Failed to parse rx_bytes")
+        self.statistic("rx_bytes")
+    }
+
+    /// Query the members of a bond or bridge for their `tx_bytes` statistic.
+    pub fn members_tx_bytes(&self) -> Result<u64> {
+        self.members_statistic("tx_bytes")
+    }
+
+    /// Query the members of a bond or bridge for their `rx_bytes` statistic.
+    pub fn members_rx_bytes(&self) -> Result<u64> {
+        self.members_statistic("rx_bytes")
+    }
+
+    fn statistic(&self, name: &str) -> Result<u64> {
+        read_sysfs("net", &self.device_path.join("statistics").join(name))
+    }
+
+    /// Sums a statistic over the members of this device, falling back to the
+    /// device itself if it is neither a bond nor a bridge. Each member is read
+    /// from its own counters only, so a bond enslaved to a bridge is counted
+    /// once rather than once more per slave.
+    fn members_statistic(&self, name: &str) -> Result<u64> {
+        let members = self.members();
+        if members.is_empty() {
+            return self.statistic(name);
+        }
+        let mut total = 0;
+        for member in members {
+            total += NetworkDevice::from_device(member).statistic(name)?;
+        }
+        Ok(total)
+    }
+
+    /// Names of the interfaces enslaved to this device if it is a bond or a
+    /// bridge, empty otherwise.
+    pub fn members(&self) -> Vec<String> {
+        if let Ok(slaves) = read_file(&self.device_path.join("bonding/slaves")) {
+            return slaves.split_whitespace().map(String::from).collect();
+        }
+        let mut members: Vec<String> = match read_dir(self.device_path.join("brif")) {
+            Ok(entries) => entries
+                .filter_map(|entry| entry.ok())
+                .map(|entry| entry.file_name().to_string_lossy().into_owned())
+                .collect(),
+            Err(_) => Vec::new(),
+        };
+        members.sort();
+        members
     }
 
     /// Checks whether this device is wireless.
@@ -163,6 +208,17 @@ impl NetworkDevice {
         self.tun || self.wg || self.ppp
     }
 
+    /// Name of the icon for the kind of this device.
+    fn icon(&self) -> &'static str {
+        if self.wireless {
+            "net_wireless"
+        } else if self.is_vpn() {
+            "net_vpn"
+        } else {
+            "net_wired"
+        }
+    }
+
     /// Queries the wireless SSID of this device, if it is connected to one.
     pub fn ssid(&self) -> Result<Option<String>> {
         let up = self.is_up()?;
@@ -350,6 +406,59 @@ impl NetworkDevice {
     }
 }
 
+/// Recent signal strengths of a wireless interface, in percent and oldest
+/// first, to spot a dropping signal.
+struct SignalHistory {
+    /// Interface the samples were taken of
+    device: String,
+    samples: VecDeque<u32>,
+    length: usize,
+}
+
+impl SignalHistory {
+    fn new(length: usize) -> Self {
+        SignalHistory {
+            device: String::new(),
+            samples: VecDeque::with_capacity(length),
+            length,
+        }
+    }
+
+    /// Adds a sample of `device`, starting over if it is another interface.
+    fn push(&mut self, device: &str, strength: u32) {
+        if device != self.device {
+            self.clear();
+            self.device = device.to_string();
+        }
+        if self.samples.len() == self.length {
+            self.samples.pop_front();
+        }
+        self.samples.push_back(strength);
+    }
+
+    fn clear(&mut self) {
+        self.device.clear();
+        self.samples.clear();
+    }
+
+    fn min(&self) -> Option<u32> {
+        self.samples.iter().copied().min()
+    }
+
+    fn avg(&self) -> Option<u32> {
+        if self.samples.is_empty() {
+            return None;
+        }
+        let sum: u32 = self.samples.iter().sum();
+        Some((sum as f64 / self.samples.len() as f64).round() as u32)
+    }
+
+    fn graph(&self) -> String {
+        let samples: Vec<u32> = self.samples.iter().copied().collect();
+        format_vec_to_bar_graph(&samples, Some(0), Some(100))
+    }
+}
+
 pub struct Net {
     format: FormatTemplate,
     output: ButtonWidget,
@@ -357,10 +466,16 @@ pub struct Net {
     network: ButtonWidget,
     ssid: Option<String>,
     max_ssid_width: usize,
+    notify_ssid_change: bool,
+    /// Last SSID seen, kept while disconnected to notice roaming
+    last_ssid: Option<String>,
     signal_strength: Option<String>,
     signal_strength_bar: Option<String>,
+    /// Sampled on every update while the format shows it
+    signal_history: Option<SignalHistory>,
     ip_addr: Option<String>,
     ipv6_addr: Option<String>,
+    members: Option<String>,
     bitrate: Option<String>,
     output_tx: Option<String>,
     graph_tx: Option<String>,
@@ -374,8 +489,10 @@ pub struct Net {
     rx_buff: Vec<u64>,
     tx_bytes: u64,
     rx_bytes: u64,
+    sum_members: bool,
     use_bits: bool,
     speed_min_unit: Unit,
+    speed_fixed_unit: bool,
     speed_digits: usize,
     active: bool,
     exists: bool,
@@ -434,6 +551,10 @@ pub struct NetConfig {
     #[serde(default = "NetConfig::default_max_ssid_width")]
     pub max_ssid_width: usize,
 
+    /// Whether to send a desktop notification when the SSID changes.
+    #[serde(default = "NetConfig::default_notify_ssid_change")]
+    pub notify_ssid_change: bool,
+
     /// Whether to show the signal strength of active wireless networks.
     #[serde(default = "NetConfig::default_signal_strength")]
     pub signal_strength: bool,
@@ -478,6 +599,11 @@ pub struct NetConfig {
     #[serde(default = "NetConfig::default_speed_min_unit")]
     pub speed_min_unit: Unit,
 
+    /// Whether to always display throughput in `speed_min_unit` instead of
+    /// scaling the unit to the speed.
+    #[serde(default = "NetConfig::default_speed_fixed_unit")]
+    pub speed_fixed_unit: bool,
+
     /// Whether to show the download throughput indicator of active networks.
     #[serde(default = "NetConfig::default_speed_down")]
     pub speed_down: bool,
@@ -490,6 +616,15 @@ pub struct NetConfig {
     #[serde(default = "NetConfig::default_graph_down")]
     pub graph_down: bool,
 
+    /// Whether to sum the throughput of the members of a bond or bridge
+    /// instead of reading the counters of the aggregate device.
+    #[serde(default = "NetConfig::default_sum_members")]
+    pub sum_members: bool,
+
+    /// Number of updates `{signal_graph}`, `{signal_avg}` and `{signal_min}` cover.
+    #[serde(default = "NetConfig::default_signal_history_length")]
+    pub signal_history_length: usize,
+
     #[serde(default = "NetConfig::default_on_click")]
     pub on_click: Option<String>,
 }
@@ -530,6 +665,10 @@ impl NetConfig {
         false
     }
 
+    fn default_notify_ssid_change() -> bool {
+        false
+    }
+
     fn default_signal_strength() -> bool {
         false
     }
@@ -574,13 +713,25 @@ impl NetConfig {
         Unit::K
     }
 
+    fn default_speed_fixed_unit() -> bool {
+        false
+    }
+
     fn default_speed_digits() -> usize {
         3
     }
 
+    fn default_sum_members() -> bool {
+        false
+    }
+
     fn default_on_click() -> Option<String> {
         None
     }
+
+    fn default_signal_history_length() -> usize {
+        30
+    }
 }
 
 impl ConfigBlock for Net {
@@ -592,10 +743,14 @@ impl ConfigBlock for Net {
         _tx_update_request: Sender<Task>,
     ) -> Result<Self> {
         let device = NetworkDevice::from_device(block_config.device);
-        let init_rx_bytes = device.rx_bytes().unwrap_or(0);
-        let init_tx_bytes = device.tx_bytes().unwrap_or(0);
+        let (init_rx_bytes, init_tx_bytes) = if block_config.sum_members {
+            (device.members_rx_bytes(), device.members_tx_bytes())
+        } else {
+            (device.rx_bytes(), device.tx_bytes())
+        };
+        let init_rx_bytes = init_rx_bytes.unwrap_or(0);
+        let init_tx_bytes = init_tx_bytes.unwrap_or(0);
         let wireless = device.is_wireless();
-        let vpn = device.is_vpn();
         let id = Uuid::new_v4().to_simple().to_string();
 
         let (_, net_config) = config
@@ -615,6 +770,19 @@ impl ConfigBlock for Net {
             block_config.format
         };
 
+        let uses_signal_history = ["{signal_graph}", "{signal_avg}", "{signal_min}"]
+            .iter()
+            .any(|placeholder| format.contains(placeholder));
+        if uses_signal_history && block_config.signal_history_length == 0 {
+            return Err(ConfigurationError(
+                "net".to_string(),
+                (
+                    "signal_history_length must be at least 1".to_string(),
+                    "invalid signal_history_length".to_string(),
+                ),
+            ));
+        }
+
         Ok(Net {
             id: id.clone(),
             update_interval: block_config.interval,
@@ -626,14 +794,9 @@ impl ConfigBlock for Net {
             config: config.clone(),
             use_bits: block_config.use_bits,
             speed_min_unit: block_config.speed_min_unit,
+            speed_fixed_unit: block_config.speed_fixed_unit,
             speed_digits: block_config.speed_digits,
-            network: ButtonWidget::new(config, &id).with_icon(if wireless {
-                "net_wireless"
-            } else if vpn {
-                "net_vpn"
-            } else {
-                "net_wired"
-            }),
+            network: ButtonWidget::new(config, &id).with_icon(device.icon()),
             // Might want to signal an error if the user wants the SSID of a
             // wired connection instead.
             ssid: if wireless && format.contains("{ssid}") {
@@ -642,6 +805,8 @@ impl ConfigBlock for Net {
                 None
             },
             max_ssid_width: block_config.max_ssid_width,
+            notify_ssid_change: wireless && block_config.notify_ssid_change,
+            last_ssid: None,
             signal_strength: if wireless && format.contains("{signal_strength}") {
                 Some(0.to_string())
             } else {
@@ -652,6 +817,11 @@ impl ConfigBlock for Net {
             } else {
                 None
             },
+            signal_history: if uses_signal_history {
+                Some(SignalHistory::new(block_config.signal_history_length))
+            } else {
+                None
+            },
             // TODO: a better way to deal with this?
             bitrate: if format.contains("{bitrate}") {
                 Some("".to_string())
@@ -668,6 +838,11 @@ impl ConfigBlock for Net {
             } else {
                 None
             },
+            members: if format.contains("{members}") {
+                Some("".to_string())
+            } else {
+                None
+            },
             output_tx: Some("".to_string()),
             output_rx: Some("".to_string()),
             graph_tx: Some("".to_string()),
@@ -678,6 +853,7 @@ impl ConfigBlock for Net {
             tx_buff: vec![0; 10],
             rx_bytes: init_rx_bytes,
             tx_bytes: init_tx_bytes,
+            sum_members: block_config.sum_members,
             active: true,
             exists: true,
             hide_inactive: block_config.hide_inactive,
@@ -736,19 +912,32 @@ fn read_file(path: &Path) -> Result<String> {
     Ok(content)
 }
 
+/// Formats a throughput for both the upload and the download placeholders.
+fn format_rate(bytes: u64, digits: usize, unit: Unit, fixed_unit: bool, use_bits: bool) -> String {
+    if fixed_unit {
+        format_speed_fixed(bytes, digits, &unit.to_string(), use_bits)
+    } else {
+        format_speed(bytes, digits, &unit.to_string(), use_bits)
+    }
+}
+
 impl Net {
     fn update_device(&mut self) {
         if self.auto_device {
             let dev = NetConfig::default_device();
             if self.device.device() != dev {
                 self.device = NetworkDevice::from_device(dev);
-                self.network.set_icon(if self.device.is_wireless() {
-                    "net_wireless"
-                } else if self.device.is_vpn() {
-                    "net_vpn"
-                } else {
-                    "net_wired"
-                });
+                self.network.set_icon(self.device.icon());
+
+                // Compute the next rates from the counters of the new device,
+                // diffing against the old one's would show a bogus spike
+                self.tx_bytes = self.current_tx_bytes().unwrap_or(0);
+                self.rx_bytes = self.current_rx_bytes().unwrap_or(0);
+                self.tx_buff = vec![0; 10];
+                self.rx_buff = vec![0; 10];
+
+                // Refresh the SSID, addresses and members right away
+                self.last_update = Instant::now() - Duration::from_secs(30);
             }
         }
     }
@@ -764,19 +953,50 @@ impl Net {
     }
 
     fn update_ssid(&mut self) -> Result<()> {
+        if self.ssid.is_none() && !self.notify_ssid_change {
+            return Ok(());
+        }
+        // The automatically selected device may have switched to a wired one
+        if !self.device.is_wireless() {
+            if let Some(ref mut ssid_string) = self.ssid {
+                ssid_string.clear();
+            }
+            return Ok(());
+        }
+        let ssid = self.device.ssid()?;
+        if self.notify_ssid_change {
+            if let Some(ref s) = ssid {
+                match self.last_ssid {
+                    Some(ref last) if last != s => {
+                        self.config
+                            .block
+                            .notify(&format!("Wireless network changed from {} to {}", last, s));
+                    }
+                    _ => {}
+                }
+                self.last_ssid = Some(s.clone());
+            }
+        }
         if let Some(ref mut ssid_string) = self.ssid {
-            let ssid = self.device.ssid()?;
             if let Some(s) = ssid {
                 let mut truncated = s;
                 truncated.truncate(self.max_ssid_width);
-                // SSID names can contain chars that need escaping
-                *ssid_string = escape_pango_text(truncated);
+                *ssid_string = truncated;
             }
         }
         Ok(())
     }
 
     fn update_signal_strength(&mut self) -> Result<()> {
+        if !self.device.is_wireless() {
+            if let Some(ref mut signal_strength_string) = self.signal_strength {
+                signal_strength_string.clear();
+            }
+            if let Some(ref mut signal_strength_bar_string) = self.signal_strength_bar {
+                signal_strength_bar_string.clear();
+            }
+            return Ok(());
+        }
         if self.signal_strength.is_some() || self.signal_strength_bar.is_some() {
             let value = self.device.relative_signal_strength()?;
             if let Some(ref mut signal_strength_string) = self.signal_strength {
@@ -794,6 +1014,17 @@ impl Net {
         Ok(())
     }
 
+    fn update_signal_history(&mut self) -> Result<()> {
+        if let Some(ref mut history) = self.signal_history {
+            if !self.device.is_wireless() {
+                history.clear();
+            } else if let Some(strength) = self.device.relative_signal_strength()? {
+                history.push(&self.device.device(), strength);
+            }
+        }
+        Ok(())
+    }
+
     fn update_ip_addr(&mut self) -> Result<()> {
         if let Some(ref mut ip_addr_string) = self.ip_addr {
             let ip_addr = self.device.ip_addr()?;
@@ -810,21 +1041,46 @@ impl Net {
         Ok(())
     }
 
+    fn update_members(&mut self) {
+        if let Some(ref mut members_string) = self.members {
+            *members_string = self.device.members().join(",");
+        }
+    }
+
+    fn current_tx_bytes(&self) -> Result<u64> {
+        if self.sum_members {
+            self.device.members_tx_bytes()
+        } else {
+            self.device.tx_bytes()
+        }
+    }
+
+    fn current_rx_bytes(&self) -> Result<u64> {
+        if self.sum_members {
+            self.device.members_rx_bytes()
+        } else {
+            self.device.rx_bytes()
+        }
+    }
+
     fn update_tx_rx(&mut self) -> Result<()> {
         // TODO: consider using `as_nanos`
         let update_interval = (self.update_interval.as_secs() as f64)
             // Update the throughput/graph widgets if they are enabled
             + (self.update_interval.subsec_nanos() as f64 / 1_000_000_000.0);
         if self.output_tx.is_some() || self.graph_tx.is_some() {
-            let current_tx = self.device.tx_bytes()?;
-            let tx_bytes = ((current_tx - self.tx_bytes) as f64 / update_interval) as u64;
+            let current_tx = self.current_tx_bytes()?;
+            // The sum drops when a member leaves the bond or bridge
+            let tx_bytes =
+                (current_tx.saturating_sub(self.tx_bytes) as f64 / update_interval) as u64;
             self.tx_bytes = current_tx;
 
             if let Some(ref mut tx) = self.output_tx {
-                *tx = format_speed(
+                *tx = format_rate(
                     tx_bytes,
                     self.speed_digits,
-                    &self.speed_min_unit.to_string(),
+                    self.speed_min_unit,
+                    self.speed_fixed_unit,
                     self.use_bits,
                 );
             };
@@ -836,15 +1092,17 @@ impl Net {
             }
         }
         if self.output_rx.is_some() || self.graph_rx.is_some() {
-            let current_rx = self.device.rx_bytes()?;
-            let rx_bytes = ((current_rx - self.rx_bytes) as f64 / update_interval) as u64;
+            let current_rx = self.current_rx_bytes()?;
+            let rx_bytes =
+                (current_rx.saturating_sub(self.rx_bytes) as f64 / update_interval) as u64;
             self.rx_bytes = current_rx;
 
             if let Some(ref mut rx) = self.output_rx {
-                *rx = format_speed(
+                *rx = format_rate(
                     rx_bytes,
                     self.speed_digits,
-                    &self.speed_min_unit.to_string(),
+                    self.speed_min_unit,
+                    self.speed_fixed_unit,
                     self.use_bits,
                 );
             };
@@ -905,11 +1163,27 @@ impl Block for Net {
             self.update_ssid()?;
             self.update_signal_strength()?;
             self.update_ip_addr()?;
+            self.update_members();
             self.last_update = now;
         }
 
+        self.update_signal_history()?;
         self.update_tx_rx()?;
 
+        let percent = |strength: Option<u32>| {
+            strength
+                .map(|strength| format!("{}%", strength))
+                .unwrap_or_default()
+        };
+        let (signal_graph, signal_avg, signal_min) = match self.signal_history {
+            Some(ref history) => (
+                history.graph(),
+                percent(history.avg()),
+                percent(history.min()),
+            ),
+            None => (String::new(), String::new(), String::new()),
+        };
+
         let empty_string = "".to_string();
         let s_up = format!(
             "{} {}",
@@ -934,9 +1208,13 @@ impl Block for Net {
             "{ssid}" => self.ssid.as_ref().unwrap_or(&empty_string),
             "{signal_strength}" => self.signal_strength.as_ref().unwrap_or(&empty_string),
             "{signal_strength_bar}" => self.signal_strength_bar.as_ref().unwrap_or(&empty_string),
+            "{signal_graph}" => &signal_graph,
+            "{signal_avg}" => &signal_avg,
+            "{signal_min}" => &signal_min,
             "{bitrate}" =>  self.bitrate.as_ref().unwrap_or(&empty_string),
             "{ip}" =>  self.ip_addr.as_ref().unwrap_or(&empty_string),
             "{ipv6}" =>  self.ipv6_addr.as_ref().unwrap_or(&empty_string),
+            "{members}" => self.members.as_ref().unwrap_or(&empty_string),
             "{speed_up}" =>  &s_up,
             "{speed_down}" => &s_dn,
             "{graph_up}" =>  self.graph_tx.as_ref().unwrap_or(&empty_string),
@@ -1140,3 +1418,26 @@ fn maybe_ssid_convert(raw: Option<&[u8]>) -> Result<Option<String>> {
         Ok(None)
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use crate::blocks::net::SignalHistory;
+
+    #[test]
+    fn test_signal_history() {
+        let mut history = SignalHistory::new(3);
+        assert_eq!(history.avg(), None);
+        assert_eq!(history.graph(), "");
+
+        for strength in &[100, 80, 50, 20] {
+            history.push("wlan0", *strength);
+        }
+        assert_eq!(history.min(), Some(20));
+        assert_eq!(history.avg(), Some(50));
+        assert_eq!(history.graph().chars().count(), 3);
+
+        history.push("wlan1", 70);
+        assert_eq!(history.min(), Some(70));
+        assert_eq!(history.avg(), Some(70));
+    }
+}
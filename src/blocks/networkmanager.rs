@@ -21,7 +21,7 @@ use crate::config::Config;
 use crate::errors::*;
 use crate::input::{I3BarEvent, MouseButton};
 use crate::scheduler::Task;
-use crate::util::FormatTemplate;
+use crate::util::{FormatTemplate, MarkupEscape};
 use crate::widget::{I3BarWidget, State};
 use crate::widgets::button::ButtonWidget;
 use crate::subprocess::spawn_child_async;
@@ -762,13 +762,16 @@ impl Block for NetworkManager {
                                     }
                                 }
 
+                                // `{ap}` is rendered and escaped already
                                 let values = map!("{icon}" => icon,
                                                   "{typename}" => type_name,
                                                   "{ap}" => ap,
-                                                  "{name}" => name.to_string(), 
+                                                  "{name}" => MarkupEscape::current().apply(&name),
                                                   "{ips}" => ips);
 
-                                if let Ok(s) = self.device_format.render_static_str(&values) {
+                                let device = MarkupEscape::None
+                                    .scope(|| self.device_format.render_static_str(&values));
+                                if let Ok(s) = device {
                                     devicevec.push(s);
                                 } else {
                                     devicevec.push("[invalid device format string]".to_string())
@@ -781,10 +784,13 @@ impl Block for NetworkManager {
                             Err(v) => format!("{:?}", v),
                         };
 
+                        // `{devices}` are rendered and escaped already
                         let values = map!("{devices}" => devicevec.join(" "),
-                                          "{id}" => id);
+                                          "{id}" => MarkupEscape::current().apply(&id));
 
-                        if let Ok(s) = self.connection_format.render_static_str(&values) {
+                        let connection = MarkupEscape::None
+                            .scope(|| self.connection_format.render_static_str(&values));
+                        if let Ok(s) = connection {
                             widget.set_text(s);
                         } else {
                             widget.set_text("[invalid connection format string]");
@@ -1,8 +1,21 @@
-use std::fmt;
+use std::collections::VecDeque;
+use std::fs::{self, OpenOptions};
+use std::io::Write;
+use std::path::{Path, PathBuf};
+use std::process::{self, Command, Stdio};
+use std::str::FromStr;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Arc, Mutex};
+use std::thread::{self, JoinHandle};
 use std::time::{Duration, Instant};
 
-use crossbeam_channel::Sender;
+use chrono::{DateTime, Local, NaiveDate};
+use crossbeam_channel::{Receiver, RecvTimeoutError, Sender};
+use dbus::blocking::stdintf::org_freedesktop_dbus::RequestNameReply;
+use dbus::blocking::LocalConnection;
+use dbus::tree::{Factory, MTFn, MethodErr, ObjectPath};
 use serde_derive::Deserialize;
+use serde_json::json;
 use uuid::Uuid;
 
 use crate::blocks::Update;
@@ -12,6 +25,7 @@ use crate::errors::*;
 use crate::input::{I3BarEvent, MouseButton};
 use crate::scheduler::Task;
 use crate::subprocess::spawn_child_async;
+use crate::util::{format_vec_to_bar_graph, FormatTemplate};
 use crate::widget::{I3BarWidget, State};
 use crate::widgets::button::ButtonWidget;
 
@@ -22,83 +36,853 @@ enum PomodoroState {
     OnBreak(Instant),
 }
 
+/// Type of i3-nagbar shown at the end of a pomodoro or a break.
+#[derive(Deserialize, Debug, Copy, Clone, PartialEq, Eq, Default)]
+#[serde(rename_all = "lowercase")]
+pub enum NagLevel {
+    Error,
+    #[default]
+    Warning,
+}
+
+/// When the count of completed pomodoros is shown.
+#[derive(Deserialize, Debug, Copy, Clone, PartialEq, Eq, Default)]
+#[serde(rename_all = "snake_case")]
+pub enum ShowCount {
+    #[default]
+    Always,
+    WhenNonzero,
+    Never,
+}
+
+impl NagLevel {
+    fn as_str(self) -> &'static str {
+        match self {
+            NagLevel::Error => "error",
+            NagLevel::Warning => "warning",
+        }
+    }
+}
+
 impl PomodoroState {
-    fn elapsed(&self) -> Duration {
+    fn elapsed(&self, now: Instant) -> Duration {
         match self {
-            PomodoroState::Started(start) => Instant::now().duration_since(start.to_owned()),
+            PomodoroState::Started(start) => now.duration_since(start.to_owned()),
             PomodoroState::Stopped => unreachable!(),
             PomodoroState::Paused(duration) => duration.to_owned(),
-            PomodoroState::OnBreak(start) => Instant::now().duration_since(start.to_owned()),
+            PomodoroState::OnBreak(start) => now.duration_since(start.to_owned()),
         }
     }
+
+    /// The elapsed time as minutes and seconds.
+    fn time(&self, now: Instant) -> String {
+        let elapsed = match self {
+            PomodoroState::Stopped => Duration::from_secs(0),
+            _ => self.elapsed(now),
+        };
+        format!("{}:{:02}", elapsed.as_secs() / 60, elapsed.as_secs() % 60)
+    }
 }
 
-impl fmt::Display for PomodoroState {
-    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
-        match self {
-            PomodoroState::Stopped => write!(f, "0:00"),
-            PomodoroState::Started(_) => write!(
-                f,
-                "{}:{:02}",
-                self.elapsed().as_secs() / 60,
-                self.elapsed().as_secs() % 60
-            ),
-            PomodoroState::OnBreak(_) => write!(
-                f,
-                "{}:{:02}",
-                self.elapsed().as_secs() / 60,
-                self.elapsed().as_secs() % 60
-            ),
-            PomodoroState::Paused(duration) => write!(
-                f,
-                "{}:{:02}",
-                duration.as_secs() / 60,
-                duration.as_secs() % 60
-            ),
+/// Source of the current time, so that tests can control it.
+trait Clock {
+    fn now(&self) -> Instant;
+}
+
+struct SystemClock;
+
+impl Clock for SystemClock {
+    fn now(&self) -> Instant {
+        Instant::now()
+    }
+}
+
+/// A completed work session, as reported to the time tracker.
+struct Session {
+    start: DateTime<Local>,
+    duration: Duration,
+}
+
+/// Delay before the first retry of a failed upload. It doubles with every
+/// further failure, up to `TRACKER_MAX_RETRY`.
+const TRACKER_MIN_RETRY: Duration = Duration::from_secs(30);
+const TRACKER_MAX_RETRY: Duration = Duration::from_secs(30 * 60);
+
+struct Tracker {
+    url: String,
+    token_cmd: Option<String>,
+    project: Option<String>,
+}
+
+impl Tracker {
+    /// Starts a worker thread that posts the sessions sent to the returned
+    /// channel. Sessions that fail to upload are kept in order and retried.
+    fn spawn(self) -> Sender<Session> {
+        let (tx, rx) = crossbeam_channel::unbounded::<Session>();
+        thread::spawn(move || {
+            let mut queue = VecDeque::new();
+            let mut retry = TRACKER_MIN_RETRY;
+            loop {
+                if queue.is_empty() {
+                    match rx.recv() {
+                        Ok(session) => queue.push_back(session),
+                        Err(_) => return,
+                    }
+                }
+                while let Some(session) = queue.front() {
+                    match self.post(session) {
+                        Ok(()) => {
+                            queue.pop_front();
+                            retry = TRACKER_MIN_RETRY;
+                        }
+                        Err(e) => {
+                            eprintln!("pomodoro: failed to post session: {}", e);
+                            break;
+                        }
+                    }
+                }
+                if !queue.is_empty() {
+                    match rx.recv_timeout(retry) {
+                        Ok(session) => queue.push_back(session),
+                        Err(RecvTimeoutError::Timeout) => {
+                            retry = (retry * 2).min(TRACKER_MAX_RETRY);
+                        }
+                        Err(RecvTimeoutError::Disconnected) => return,
+                    }
+                }
+            }
+        });
+        tx
+    }
+
+    fn token(&self) -> Result<Option<String>> {
+        let cmd = match self.token_cmd {
+            Some(ref cmd) => cmd,
+            None => return Ok(None),
+        };
+        let output = Command::new("sh")
+            .arg("-c")
+            .arg(cmd)
+            .output()
+            .block_error("pomodoro", "failed to run tracker_token_cmd")?;
+        if !output.status.success() {
+            return Err(BlockError(
+                "pomodoro".to_string(),
+                "tracker_token_cmd failed".to_string(),
+            ));
+        }
+        Ok(Some(
+            String::from_utf8_lossy(&output.stdout).trim().to_string(),
+        ))
+    }
+
+    fn post(&self, session: &Session) -> Result<()> {
+        let body = json!({
+            "description": "Pomodoro",
+            "project": self.project,
+            "start": session.start.to_rfc3339(),
+            "duration": session.duration.as_secs(),
+        });
+
+        let mut command = Command::new("curl");
+        command
+            .arg("--fail")
+            .arg("--silent")
+            .arg("--max-time")
+            .arg("10")
+            .arg("--header")
+            .arg("Content-Type: application/json");
+        if let Some(token) = self.token()? {
+            command
+                .arg("--header")
+                .arg(format!("Authorization: Bearer {}", token));
+        }
+        let mut child = command
+            .arg("--data")
+            .arg("@-")
+            .arg(&self.url)
+            .stdin(Stdio::piped())
+            .stdout(Stdio::null())
+            .spawn()
+            .block_error("pomodoro", "failed to execute curl")?;
+        child
+            .stdin
+            .take()
+            .internal_error("pomodoro", "curl has no stdin")?
+            .write_all(body.to_string().as_bytes())
+            .block_error("pomodoro", "failed to write to curl")?;
+        let status = child
+            .wait()
+            .block_error("pomodoro", "failed to wait for curl")?;
+        if !status.success() {
+            return Err(BlockError(
+                "pomodoro".to_string(),
+                format!("curl exited with {}", status),
+            ));
+        }
+        Ok(())
+    }
+}
+
+/// Counts the sessions of a log written by `History::log` per day, for the
+/// `days` days up to and including `today`, oldest first. Malformed lines are
+/// skipped.
+fn sessions_per_day(log: &str, today: NaiveDate, days: usize) -> Vec<usize> {
+    let mut counts = vec![0; days];
+    for line in log.lines() {
+        let start = match line
+            .split_whitespace()
+            .next()
+            .and_then(|start| DateTime::parse_from_rfc3339(start).ok())
+        {
+            Some(start) => start.with_timezone(&Local).naive_local().date(),
+            None => continue,
+        };
+        let age = today.signed_duration_since(start).num_days();
+        if age >= 0 && (age as usize) < days {
+            counts[days - 1 - age as usize] += 1;
+        }
+    }
+    counts
+}
+
+/// Completed pomodoros per day, kept in a log file that is read when the block
+/// starts and once a day after, for the `{history}` sparkline.
+struct History {
+    path: PathBuf,
+    days: usize,
+    /// Day the counts end with
+    today: NaiveDate,
+    counts: Vec<usize>,
+    sparkline: String,
+}
+
+impl History {
+    fn new(path: PathBuf, days: usize) -> Self {
+        let mut history = History {
+            path,
+            days,
+            today: Local::now().naive_local().date(),
+            counts: Vec::new(),
+            sparkline: String::new(),
+        };
+        history.reload();
+        history
+    }
+
+    fn reload(&mut self) {
+        self.today = Local::now().naive_local().date();
+        // A missing log is an empty history
+        let log = fs::read_to_string(&self.path).unwrap_or_default();
+        self.counts = sessions_per_day(&log, self.today, self.days);
+        self.render();
+    }
+
+    fn render(&mut self) {
+        // Scaled from zero, so that a day without pomodoros shows as empty
+        let max = self.counts.iter().copied().max().unwrap_or(0).max(1);
+        self.sparkline = format_vec_to_bar_graph(&self.counts, Some(0), Some(max));
+    }
+
+    /// Reloads the log on the first call after midnight.
+    fn refresh(&mut self) {
+        if Local::now().naive_local().date() != self.today {
+            self.reload();
         }
     }
+
+    /// Appends a completed session to the log and counts it.
+    fn log(&mut self, session: &Session) {
+        if let Err(e) = append_session(&self.path, session) {
+            eprintln!(
+                "pomodoro: failed to log the session to {}: {}",
+                self.path.display(),
+                e
+            );
+        }
+        self.refresh();
+        if let Some(today) = self.counts.last_mut() {
+            *today += 1;
+        }
+        self.render();
+    }
+}
+
+fn append_session(path: &Path, session: &Session) -> std::io::Result<()> {
+    if let Some(parent) = path.parent() {
+        fs::create_dir_all(parent)?;
+    }
+    let mut file = OpenOptions::new().create(true).append(true).open(path)?;
+    writeln!(
+        file,
+        "{} {}",
+        session.start.to_rfc3339(),
+        session.duration.as_secs()
+    )
+}
+
+/// A command received over D-Bus, from a media key through the MPRIS interface
+/// or from `i3status-rs-pomodoro` through the control interface.
+#[derive(Debug, Copy, Clone)]
+enum RemoteCommand {
+    /// Starts or resumes the pomodoro, or ends the break early
+    Start,
+    Pause,
+    /// Does what a left click does
+    Toggle,
+    /// Ends the break early
+    Skip,
+    /// Stops the timer, keeping the count
+    Stop,
+    /// Stops the timer and resets the count, like a right click
+    Reset,
+}
+
+impl FromStr for RemoteCommand {
+    type Err = ();
+
+    fn from_str(s: &str) -> std::result::Result<Self, Self::Err> {
+        match s {
+            "start" => Ok(RemoteCommand::Start),
+            "pause" => Ok(RemoteCommand::Pause),
+            "toggle" => Ok(RemoteCommand::Toggle),
+            "skip" => Ok(RemoteCommand::Skip),
+            "stop" => Ok(RemoteCommand::Stop),
+            "reset" => Ok(RemoteCommand::Reset),
+            _ => Err(()),
+        }
+    }
+}
+
+const MPRIS_PATH: &str = "/org/mpris/MediaPlayer2";
+const MPRIS_INTERFACE: &str = "org.mpris.MediaPlayer2";
+const MPRIS_PLAYER_INTERFACE: &str = "org.mpris.MediaPlayer2.Player";
+
+/// Bus name of the control interface is this prefix followed by `ipc_name`.
+/// Keep in sync with src/bin/i3status-rs-pomodoro.rs.
+const CONTROL_NAME_PREFIX: &str = "i3.status.rs.pomodoro";
+const CONTROL_PATH: &str = "/pomodoro";
+const CONTROL_INTERFACE: &str = "i3.status.rs.Pomodoro";
+
+/// Whether `name` can be used as an element of a D-Bus bus name.
+fn is_ipc_name(name: &str) -> bool {
+    !name.is_empty()
+        && !name.starts_with(|c: char| c.is_ascii_digit())
+        && name
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || c == '_' || c == '-')
+}
+
+/// The D-Bus interfaces of the block: a minimal MPRIS player, so that media
+/// keys bound to e.g. `playerctl play-pause` control the pomodoro, and the
+/// control interface used by `i3status-rs-pomodoro`. The received commands
+/// are applied by the block in `update`.
+struct Remote {
+    commands: Receiver<RemoteCommand>,
+    /// Value of the PlaybackStatus property
+    status: Arc<Mutex<&'static str>>,
+    stop: Arc<AtomicBool>,
+    thread: Option<JoinHandle<()>>,
+}
+
+impl Remote {
+    fn spawn(
+        id: String,
+        tx_update_request: Sender<Task>,
+        mpris: bool,
+        ipc_name: Option<String>,
+    ) -> Result<Self> {
+        let (tx_commands, commands) = crossbeam_channel::unbounded();
+        let status = Arc::new(Mutex::new("Stopped"));
+        let stop = Arc::new(AtomicBool::new(false));
+
+        let _test_conn = LocalConnection::new_session()
+            .block_error("pomodoro", "failed to connect to the session bus")?;
+
+        let thread_status = status.clone();
+        let thread_stop = stop.clone();
+        let thread = thread::Builder::new()
+            .name("pomodoro_dbus".into())
+            .spawn(move || {
+                let remote = RemoteThread {
+                    id,
+                    tx_update_request,
+                    tx_commands,
+                    status: thread_status,
+                    stop: thread_stop,
+                };
+                if let Err(e) = remote.serve(mpris, ipc_name) {
+                    eprintln!("pomodoro: D-Bus interface stopped: {}", e);
+                }
+            })
+            .block_error("pomodoro", "failed to start the D-Bus thread")?;
+
+        Ok(Remote {
+            commands,
+            status,
+            stop,
+            thread: Some(thread),
+        })
+    }
+}
+
+impl Drop for Remote {
+    fn drop(&mut self) {
+        self.stop.store(true, Ordering::Relaxed);
+        if let Some(thread) = self.thread.take() {
+            thread.join().ok();
+        }
+    }
+}
+
+struct RemoteThread {
+    id: String,
+    tx_update_request: Sender<Task>,
+    tx_commands: Sender<RemoteCommand>,
+    status: Arc<Mutex<&'static str>>,
+    stop: Arc<AtomicBool>,
+}
+
+impl RemoteThread {
+    /// Serves the objects until `stop` is set, then gives up the bus names.
+    fn serve(&self, mpris: bool, ipc_name: Option<String>) -> std::result::Result<(), dbus::Error> {
+        let c = LocalConnection::new_session()?;
+        let f = Factory::new_fn::<()>();
+        let mut tree = f.tree(()).add(f.object_path("/", ()).introspectable());
+        let mut names = Vec::new();
+
+        if mpris {
+            // Several bars may run a pomodoro, the instance suffix is allowed by MPRIS
+            names.push(format!(
+                "{}.i3status_rs_pomodoro.instance{}",
+                MPRIS_INTERFACE,
+                process::id()
+            ));
+            tree = tree.add(self.mpris_object(&f));
+        }
+
+        if let Some(ipc_name) = ipc_name {
+            names.push(format!("{}.{}", CONTROL_NAME_PREFIX, ipc_name));
+            let commands = self.clone_senders();
+            tree = tree.add(
+                f.object_path(CONTROL_PATH, ()).introspectable().add(
+                    f.interface(CONTROL_INTERFACE, ()).add_m(
+                        f.method("Command", (), move |m| {
+                            let command: &str = m.msg.read1()?;
+                            let parsed = command
+                                .parse()
+                                .map_err(|_| MethodErr::invalid_arg(&command))?;
+                            commands.send(parsed);
+                            Ok(vec![m.msg.method_return()])
+                        })
+                        .inarg::<&str, _>("command"),
+                    ),
+                ),
+            );
+        }
+
+        for name in &names {
+            let reply = c.request_name(name.as_str(), false, true, true)?;
+            if reply != RequestNameReply::PrimaryOwner {
+                return Err(dbus::Error::new_custom(
+                    "org.freedesktop.DBus.Error.AddressInUse",
+                    &format!("{} is taken by another bar", name),
+                ));
+            }
+        }
+        tree.start_receive(&c);
+
+        // Wake up regularly to notice when the block is dropped
+        while !self.stop.load(Ordering::Relaxed) {
+            c.process(Duration::from_millis(250))?;
+        }
+        for name in &names {
+            c.release_name(name.as_str())?;
+        }
+        Ok(())
+    }
+
+    fn clone_senders(&self) -> CommandSender {
+        CommandSender {
+            id: self.id.clone(),
+            tx_update_request: self.tx_update_request.clone(),
+            tx_commands: self.tx_commands.clone(),
+        }
+    }
+
+    fn mpris_object(&self, f: &Factory<MTFn<()>, ()>) -> ObjectPath<MTFn<()>, ()> {
+        let command_method = |name: &'static str, command: RemoteCommand| {
+            let commands = self.clone_senders();
+            f.method(name, (), move |m| {
+                commands.send(command);
+                Ok(vec![m.msg.method_return()])
+            })
+        };
+        let flag = |name: &'static str, value: bool| {
+            f.property::<bool, _>(name, ()).on_get(move |i, _| {
+                i.append(value);
+                Ok(())
+            })
+        };
+        let no_op = |name: &'static str| f.method(name, (), |m| Ok(vec![m.msg.method_return()]));
+
+        let playback_status = self.status.clone();
+        f.object_path(MPRIS_PATH, ())
+            .introspectable()
+            .add(
+                f.interface(MPRIS_INTERFACE, ())
+                    .add_m(no_op("Raise"))
+                    .add_m(no_op("Quit"))
+                    .add_p(flag("CanQuit", false))
+                    .add_p(flag("CanRaise", false))
+                    .add_p(flag("HasTrackList", false))
+                    .add_p(f.property::<&str, _>("Identity", ()).on_get(|i, _| {
+                        i.append("i3status-rust pomodoro");
+                        Ok(())
+                    })),
+            )
+            .add(
+                f.interface(MPRIS_PLAYER_INTERFACE, ())
+                    .add_m(command_method("Play", RemoteCommand::Start))
+                    .add_m(command_method("Pause", RemoteCommand::Pause))
+                    .add_m(command_method("PlayPause", RemoteCommand::Toggle))
+                    .add_m(command_method("Next", RemoteCommand::Skip))
+                    .add_m(command_method("Stop", RemoteCommand::Stop))
+                    .add_p(
+                        f.property::<&str, _>("PlaybackStatus", ())
+                            .on_get(move |i, _| {
+                                i.append(*playback_status.lock().unwrap());
+                                Ok(())
+                            }),
+                    )
+                    .add_p(flag("CanControl", true))
+                    .add_p(flag("CanPlay", true))
+                    .add_p(flag("CanPause", true))
+                    .add_p(flag("CanGoNext", true))
+                    .add_p(flag("CanGoPrevious", false))
+                    .add_p(flag("CanSeek", false)),
+            )
+    }
+}
+
+/// Passes a command to the block and requests an update to apply it.
+struct CommandSender {
+    id: String,
+    tx_update_request: Sender<Task>,
+    tx_commands: Sender<RemoteCommand>,
+}
+
+impl CommandSender {
+    fn send(&self, command: RemoteCommand) {
+        // The bar may be shutting down
+        self.tx_commands.send(command).ok();
+        self.tx_update_request
+            .send(Task {
+                id: self.id.clone(),
+                update_time: Instant::now(),
+            })
+            .ok();
+    }
 }
 
 pub struct Pomodoro {
     id: String,
     time: ButtonWidget,
+    /// Separate widget for the count and its format, see `count_format`
+    count_widget: Option<(ButtonWidget, FormatTemplate)>,
+    count_id: String,
     state: PomodoroState,
     length: Duration,
     break_length: Duration,
+    long_break_length: Duration,
+    long_break_after: Option<usize>,
+    /// Whether the current break is a long one
+    long_break: bool,
     update_interval: Duration,
     message: String,
     break_message: String,
+    long_break_message: String,
+    nag_level: NagLevel,
+    break_nag_level: NagLevel,
+    long_break_nag_level: NagLevel,
     count: usize,
     use_nag: bool,
     nag_path: std::path::PathBuf,
+    format: FormatTemplate,
+    /// Default format used while the count is hidden, `None` with a custom format
+    format_without_count: Option<FormatTemplate>,
+    show_count: ShowCount,
+    ends_at_format: String,
+    ascii: bool,
+    tracker: Option<Sender<Session>>,
+    history: Option<History>,
+    tick_sound_path: Option<PathBuf>,
+    tick_player: String,
+    last_tick: Option<Instant>,
+    confirm_reset: bool,
+    /// Time of the first right click, while waiting for the second one
+    reset_requested: Option<Instant>,
+    remote: Option<Remote>,
+    work_state: State,
+    short_break_state: State,
+    long_break_state: State,
+    paused_state: State,
+    clock: Box<dyn Clock>,
+    /// Shows an i3-nagbar, replaced in tests
+    nagger: fn(&Path, &str, NagLevel),
 }
 
+/// Change of the length of a pomodoro or break per scroll step.
+const LENGTH_STEP: Duration = Duration::from_secs(60);
+
+/// Time to confirm a reset with a second right click.
+const RESET_CONFIRM_TIMEOUT: Duration = Duration::from_secs(3);
+
+/// Minimum time between two ticks. Slightly below a second, so that the
+/// updates every second still tick when they run a little early.
+const TICK_INTERVAL: Duration = Duration::from_millis(950);
+
+/// Clock face showing one o'clock, followed by the faces up to twelve o'clock.
+const CLOCK_ONE: u32 = 0x1f550;
+
+/// Clock face showing half past one, followed by the faces up to half past twelve.
+const CLOCK_ONE_THIRTY: u32 = 0x1f55c;
+
 impl Pomodoro {
+    fn shows_count(&self) -> bool {
+        match self.show_count {
+            ShowCount::Always => true,
+            ShowCount::WhenNonzero => self.count > 0,
+            ShowCount::Never => false,
+        }
+    }
+
     fn set_text(&mut self) {
-        self.time
-            .set_text(format!("{} | {}", self.count, self.state));
-        self.time
-            .set_state(self.compute_state());
+        let clock = if self.ascii {
+            self.marker().to_string()
+        } else {
+            self.clock().to_string()
+        };
+        let shows_count = self.shows_count();
+        let values = map!(
+            "{count}" => if shows_count {
+                self.count.to_string()
+            } else {
+                String::new()
+            },
+            "{time}" => self.state.time(self.clock.now()),
+            "{clock}" => clock,
+            "{marker}" => self.marker().to_string(),
+            "{ends_at}" => self.ends_at(),
+            "{history}" => match self.history {
+                Some(ref mut history) => {
+                    history.refresh();
+                    history.sparkline.clone()
+                }
+                None => String::new(),
+            }
+        );
+        let state = self.compute_state();
+        if let Some((ref mut widget, ref format)) = self.count_widget {
+            // The format is validated in `new`
+            if let Ok(text) = format.render_static_str(&map!("{count}" => self.count)) {
+                widget.set_text(text);
+            }
+            widget.set_state(state);
+        }
+        if self.reset_pending() {
+            self.time.set_text("reset?".to_string());
+            self.time.set_state(State::Warning);
+            return;
+        }
+        let format = match self.format_without_count {
+            Some(ref format) if !shows_count => format,
+            _ => &self.format,
+        };
+        // The format is validated in `new`
+        if let Ok(text) = format.render_static_str(&values) {
+            self.time.set_text(text);
+        }
+        self.time.set_state(self.compute_state());
+        if let Some(ref remote) = self.remote {
+            *remote.status.lock().unwrap() = match self.state {
+                PomodoroState::Started(_) => "Playing",
+                PomodoroState::Paused(_) => "Paused",
+                _ => "Stopped",
+            };
+        }
+    }
+
+    /// Starts, pauses or resumes the pomodoro, or ends the break early.
+    fn toggle(&mut self) {
+        let now = self.clock.now();
+        match &self.state {
+            PomodoroState::Stopped => {
+                self.state = PomodoroState::Started(now);
+            }
+            PomodoroState::Started(_) => {
+                self.state = PomodoroState::Paused(self.state.elapsed(now));
+            }
+            PomodoroState::Paused(duration) => {
+                self.state = PomodoroState::Started(now.checked_sub(duration.to_owned()).unwrap());
+            }
+            PomodoroState::OnBreak(_) => {
+                self.state = PomodoroState::Started(now);
+                self.count += 1;
+            }
+        }
+    }
+
+    fn remote_command(&mut self, command: RemoteCommand) {
+        self.reset_requested = None;
+        let started = matches!(self.state, PomodoroState::Started(_));
+        let on_break = matches!(self.state, PomodoroState::OnBreak(_));
+        match command {
+            RemoteCommand::Start if started => {}
+            RemoteCommand::Pause if !started => {}
+            RemoteCommand::Skip if !on_break => {}
+            RemoteCommand::Stop => self.state = PomodoroState::Stopped,
+            RemoteCommand::Reset => {
+                self.state = PomodoroState::Stopped;
+                self.count = 0;
+            }
+            _ => self.toggle(),
+        }
+    }
+
+    fn reset_pending(&self) -> bool {
+        match self.reset_requested {
+            Some(requested) => self.clock.now().duration_since(requested) < RESET_CONFIRM_TIMEOUT,
+            None => false,
+        }
+    }
+
+    /// A plain text marker of the state, for bars without emoji fonts.
+    fn marker(&self) -> &'static str {
+        match self.state {
+            PomodoroState::Started(_) => "[W]",
+            PomodoroState::OnBreak(_) => "[B]",
+            PomodoroState::Paused(_) => "[P]",
+            PomodoroState::Stopped => "[-]",
+        }
+    }
+
+    /// Picks the clock face showing the elapsed fraction of the current
+    /// session, from one o'clock to a full twelve o'clock. Breaks use the
+    /// half past faces.
+    fn clock(&self) -> char {
+        let (first, length) = match self.state {
+            PomodoroState::Stopped => (CLOCK_ONE, Duration::from_secs(0)),
+            PomodoroState::OnBreak(_) => (CLOCK_ONE_THIRTY, self.current_break_length()),
+            _ => (CLOCK_ONE, self.length),
+        };
+        let fraction = if length.as_secs() == 0 {
+            1.
+        } else {
+            self.state.elapsed(self.clock.now()).as_secs_f64() / length.as_secs_f64()
+        };
+        // The hand moves to the next hour as soon as the previous one is over,
+        // so it points at twelve only in the last twelfth
+        let hour = ((fraction * 12.).ceil() as u32).clamp(1, 12);
+        std::char::from_u32(first + hour - 1).unwrap_or(' ')
+    }
+
+    /// Wall clock time at which the pomodoro or the break ends. While paused,
+    /// the time it would end if resumed now.
+    fn ends_at(&self) -> String {
+        let length = match self.state {
+            PomodoroState::Stopped => return String::new(),
+            PomodoroState::OnBreak(_) => self.current_break_length(),
+            _ => self.length,
+        };
+        let remaining = length
+            .checked_sub(self.state.elapsed(self.clock.now()))
+            .unwrap_or_default();
+        let ends_at = Local::now()
+            + chrono::Duration::from_std(remaining).unwrap_or_else(|_| chrono::Duration::zero());
+        ends_at.format(&self.ends_at_format).to_string()
+    }
+
+    /// Lengthens or shortens the current pomodoro or break by `LENGTH_STEP`,
+    /// keeping it at least that long.
+    fn step_length(&mut self, longer: bool) {
+        let length = match self.state {
+            PomodoroState::OnBreak(_) if self.long_break => &mut self.long_break_length,
+            PomodoroState::OnBreak(_) => &mut self.break_length,
+            _ => &mut self.length,
+        };
+        *length = if longer {
+            *length + LENGTH_STEP
+        } else {
+            length
+                .checked_sub(LENGTH_STEP)
+                .unwrap_or_default()
+                .max(LENGTH_STEP)
+        };
+    }
+
+    fn current_break_length(&self) -> Duration {
+        if self.long_break {
+            self.long_break_length
+        } else {
+            self.break_length
+        }
+    }
+
+    /// Whether the pomodoro that is about to end earns a long break.
+    fn earns_long_break(&self) -> bool {
+        match self.long_break_after {
+            Some(after) if after > 0 => self.count % after == after - 1,
+            _ => false,
+        }
     }
 
     fn compute_state(&self) -> State {
         match self.state {
-            PomodoroState::Started(_) => State::Info,
+            PomodoroState::Started(_) => self.work_state,
             PomodoroState::Stopped => State::Idle,
-            PomodoroState::Paused(_) => State::Warning,
-            PomodoroState::OnBreak(_) => State::Critical,
+            PomodoroState::Paused(_) => self.paused_state,
+            PomodoroState::OnBreak(_) if self.long_break => self.long_break_state,
+            PomodoroState::OnBreak(_) => self.short_break_state,
         }
     }
 
-    fn nag(&self, message: &str, level: &str) {
-        spawn_child_async(
-            self.nag_path.to_str().unwrap(),
-            &["-t", level, "-m", message],
-        )
-        .expect("Failed to start i3-nagbar");
+    /// Plays the tick sound, at most once per second. A player that fails to
+    /// start turns the ticks off instead of failing every second.
+    fn tick(&mut self) {
+        let path = match self.tick_sound_path {
+            Some(ref path) => path.to_string_lossy().into_owned(),
+            None => return,
+        };
+        let now = self.clock.now();
+        if let Some(last) = self.last_tick {
+            if now.duration_since(last) < TICK_INTERVAL {
+                return;
+            }
+        }
+        self.last_tick = Some(now);
+        if let Err(e) = spawn_child_async(&self.tick_player, &[&path]) {
+            eprintln!(
+                "pomodoro: failed to play the tick sound, disabling it: {}",
+                e
+            );
+            self.tick_sound_path = None;
+        }
+    }
+
+    fn nag(&self, message: &str, level: NagLevel) {
+        (self.nagger)(&self.nag_path, message, level);
     }
 }
 
+fn spawn_nag(nag_path: &Path, message: &str, level: NagLevel) {
+    spawn_child_async(
+        nag_path.to_str().unwrap(),
+        &["-t", level.as_str(), "-m", message],
+    )
+    .expect("Failed to start i3-nagbar");
+}
+
 #[derive(Deserialize, Debug, Default, Clone)]
 #[serde(deny_unknown_fields)]
 pub struct PomodoroConfig {
@@ -110,10 +894,68 @@ pub struct PomodoroConfig {
     pub message: String,
     #[serde(default = "PomodoroConfig::default_break_message")]
     pub break_message: String,
+    #[serde(default = "PomodoroConfig::default_long_break_length")]
+    pub long_break_length: u64,
+    #[serde(default)]
+    pub long_break_after: Option<usize>,
+    /// Defaults to `break_message`
+    #[serde(default)]
+    pub long_break_message: Option<String>,
+    #[serde(default = "PomodoroConfig::default_nag_level")]
+    pub nag_level: NagLevel,
+    #[serde(default = "PomodoroConfig::default_break_nag_level")]
+    pub break_nag_level: NagLevel,
+    #[serde(default = "PomodoroConfig::default_break_nag_level")]
+    pub long_break_nag_level: NagLevel,
     #[serde(default = "PomodoroConfig::default_use_nag")]
     pub use_nag: bool,
     #[serde(default = "PomodoroConfig::default_nag_path")]
     pub nag_path: std::path::PathBuf,
+    #[serde(default)]
+    pub format: Option<String>,
+    /// Shows the count in a separate widget with this format
+    #[serde(default)]
+    pub count_format: Option<String>,
+    #[serde(default)]
+    pub show_count: ShowCount,
+    /// Format of `{ends_at}`, see `chrono::format::strftime`
+    #[serde(default = "PomodoroConfig::default_ends_at_format")]
+    pub ends_at_format: String,
+    #[serde(default = "PomodoroConfig::default_ascii")]
+    pub ascii: bool,
+    #[serde(default = "PomodoroConfig::default_start_count")]
+    pub start_count: usize,
+    #[serde(default = "PomodoroConfig::default_confirm_reset")]
+    pub confirm_reset: bool,
+    #[serde(default)]
+    pub tick_sound_path: Option<PathBuf>,
+    #[serde(default = "PomodoroConfig::default_tick_player")]
+    pub tick_player: String,
+    #[serde(default)]
+    pub tracker_url: Option<String>,
+    #[serde(default)]
+    pub tracker_token_cmd: Option<String>,
+    #[serde(default)]
+    pub project: Option<String>,
+    /// File completed pomodoros are appended to, read for `{history}`
+    #[serde(default)]
+    pub log_file: Option<PathBuf>,
+    /// Number of days shown by `{history}`, including today
+    #[serde(default = "PomodoroConfig::default_history_days")]
+    pub history_days: usize,
+    #[serde(default = "PomodoroConfig::default_mpris")]
+    pub mpris: bool,
+    /// Name under which `i3status-rs-pomodoro` finds the block
+    #[serde(default)]
+    pub ipc_name: Option<String>,
+    #[serde(default = "PomodoroConfig::default_work_state")]
+    pub work_state: State,
+    #[serde(default = "PomodoroConfig::default_break_state")]
+    pub short_break_state: State,
+    #[serde(default = "PomodoroConfig::default_break_state")]
+    pub long_break_state: State,
+    #[serde(default = "PomodoroConfig::default_paused_state")]
+    pub paused_state: State,
 }
 
 impl PomodoroConfig {
@@ -125,6 +967,10 @@ impl PomodoroConfig {
         5
     }
 
+    fn default_long_break_length() -> u64 {
+        15
+    }
+
     fn default_message() -> String {
         "Pomodoro over! Take a break!".to_owned()
     }
@@ -133,6 +979,14 @@ impl PomodoroConfig {
         "Break over! Time to work!".to_owned()
     }
 
+    fn default_nag_level() -> NagLevel {
+        NagLevel::Error
+    }
+
+    fn default_break_nag_level() -> NagLevel {
+        NagLevel::Warning
+    }
+
     fn default_use_nag() -> bool {
         false
     }
@@ -140,26 +994,196 @@ impl PomodoroConfig {
     fn default_nag_path() -> std::path::PathBuf {
         std::path::PathBuf::from("i3-nagbar")
     }
+
+    fn default_format(ascii: bool, separate_count: bool) -> String {
+        match (ascii, separate_count) {
+            (true, false) => "{marker} {count} {time}".to_owned(),
+            (true, true) => "{marker} {time}".to_owned(),
+            (false, false) => "{count} | {time}".to_owned(),
+            (false, true) => "{time}".to_owned(),
+        }
+    }
+
+    fn default_ends_at_format() -> String {
+        "%H:%M".to_owned()
+    }
+
+    fn default_ascii() -> bool {
+        false
+    }
+
+    fn default_start_count() -> usize {
+        0
+    }
+
+    fn default_confirm_reset() -> bool {
+        false
+    }
+
+    fn default_tick_player() -> String {
+        "paplay".to_owned()
+    }
+
+    fn default_mpris() -> bool {
+        false
+    }
+
+    fn default_history_days() -> usize {
+        7
+    }
+
+    fn default_work_state() -> State {
+        State::Info
+    }
+
+    fn default_break_state() -> State {
+        State::Critical
+    }
+
+    fn default_paused_state() -> State {
+        State::Warning
+    }
 }
 
 impl ConfigBlock for Pomodoro {
     type Config = PomodoroConfig;
 
-    fn new(block_config: Self::Config, config: Config, _send: Sender<Task>) -> Result<Self> {
+    fn new(block_config: Self::Config, config: Config, send: Sender<Task>) -> Result<Self> {
         let id: String = Uuid::new_v4().to_simple().to_string();
+        let ascii = block_config.ascii;
+        let separate_count = block_config.count_format.is_some();
+        // Without a custom format, a hidden count drops its separator as well
+        let format_without_count = match block_config.format {
+            None if !separate_count => Some(
+                FormatTemplate::from_string(&PomodoroConfig::default_format(ascii, true))
+                    .block_error("pomodoro", "Invalid format specified")?,
+            ),
+            _ => None,
+        };
+        let format = block_config
+            .format
+            .unwrap_or_else(|| PomodoroConfig::default_format(ascii, separate_count));
+        let format = FormatTemplate::from_string(&format)
+            .block_error("pomodoro", "Invalid format specified")?;
+        format
+            .render_static_str(&map!(
+                "{count}" => "",
+                "{time}" => "",
+                "{clock}" => "",
+                "{marker}" => "",
+                "{ends_at}" => "",
+                "{history}" => ""
+            ))
+            .block_error("pomodoro", "Invalid placeholder in format")?;
+
+        let count_id = format!("{}_count", id);
+        let count_widget = match block_config.count_format {
+            Some(count_format) => {
+                let count_format = FormatTemplate::from_string(&count_format)
+                    .block_error("pomodoro", "Invalid count_format specified")?;
+                count_format
+                    .render_static_str(&map!("{count}" => ""))
+                    .block_error("pomodoro", "Invalid placeholder in count_format")?;
+                Some((ButtonWidget::new(config.clone(), &count_id), count_format))
+            }
+            None => None,
+        };
+
+        let tracker = match block_config.tracker_url {
+            Some(url) => Some(
+                Tracker {
+                    url,
+                    token_cmd: block_config.tracker_token_cmd,
+                    project: block_config.project,
+                }
+                .spawn(),
+            ),
+            None => None,
+        };
+
+        if block_config.history_days == 0 {
+            return Err(ConfigurationError(
+                "pomodoro".to_string(),
+                (
+                    "history_days must be at least 1".to_string(),
+                    "invalid history_days".to_string(),
+                ),
+            ));
+        }
+        let history = match block_config.log_file {
+            Some(path) => Some(History::new(path, block_config.history_days)),
+            None => None,
+        };
+
+        if let Some(ref ipc_name) = block_config.ipc_name {
+            if !is_ipc_name(ipc_name) {
+                return Err(ConfigurationError(
+                    "pomodoro".to_string(),
+                    (
+                        format!(
+                            "ipc_name '{}' may only contain letters, digits, '_' and '-', \
+                             and may not start with a digit",
+                            ipc_name
+                        ),
+                        "invalid ipc_name".to_string(),
+                    ),
+                ));
+            }
+        }
+        let remote = if block_config.mpris || block_config.ipc_name.is_some() {
+            Some(Remote::spawn(
+                id.clone(),
+                send,
+                block_config.mpris,
+                block_config.ipc_name,
+            )?)
+        } else {
+            None
+        };
 
         Ok(Pomodoro {
             id: id.clone(),
             time: ButtonWidget::new(config, &id),
+            count_widget,
+            count_id,
             state: PomodoroState::Stopped,
             length: Duration::from_secs(block_config.length * 60), // convert to minutes
             break_length: Duration::from_secs(block_config.break_length * 60), // convert to minutes
+            long_break_length: Duration::from_secs(block_config.long_break_length * 60),
+            long_break_after: block_config.long_break_after,
+            long_break: false,
             update_interval: Duration::from_millis(1000),
             message: block_config.message,
+            long_break_message: match block_config.long_break_message {
+                Some(message) => message,
+                None => block_config.break_message.clone(),
+            },
             break_message: block_config.break_message,
+            nag_level: block_config.nag_level,
+            break_nag_level: block_config.break_nag_level,
+            long_break_nag_level: block_config.long_break_nag_level,
             use_nag: block_config.use_nag,
-            count: 0,
+            count: block_config.start_count,
             nag_path: block_config.nag_path,
+            format,
+            format_without_count,
+            show_count: block_config.show_count,
+            ends_at_format: block_config.ends_at_format,
+            ascii,
+            tracker,
+            history,
+            tick_sound_path: block_config.tick_sound_path,
+            tick_player: block_config.tick_player,
+            last_tick: None,
+            confirm_reset: block_config.confirm_reset,
+            reset_requested: None,
+            remote,
+            work_state: block_config.work_state,
+            short_break_state: block_config.short_break_state,
+            long_break_state: block_config.long_break_state,
+            paused_state: block_config.paused_state,
+            clock: Box::new(SystemClock),
+            nagger: spawn_nag,
         })
     }
 }
@@ -170,21 +1194,49 @@ impl Block for Pomodoro {
     }
 
     fn update(&mut self) -> Result<Option<Update>> {
+        let commands: Vec<RemoteCommand> = match self.remote {
+            Some(ref remote) => remote.commands.try_iter().collect(),
+            None => Vec::new(),
+        };
+        for command in commands {
+            self.remote_command(command);
+        }
         self.set_text();
+        let now = self.clock.now();
         match &self.state {
             PomodoroState::Started(_) => {
-                if self.state.elapsed() >= self.length {
+                if self.state.elapsed(now) >= self.length {
                     if self.use_nag {
-                        self.nag(&self.message, "error");
+                        self.nag(&self.message, self.nag_level);
+                    }
+                    let elapsed = self.state.elapsed(now);
+                    let session = Session {
+                        start: Local::now()
+                            - chrono::Duration::from_std(elapsed)
+                                .unwrap_or_else(|_| chrono::Duration::zero()),
+                        duration: elapsed,
+                    };
+                    if let Some(ref mut history) = self.history {
+                        history.log(&session);
+                    }
+                    if let Some(ref tracker) = self.tracker {
+                        tracker.send(session)?;
                     }
 
-                    self.state = PomodoroState::OnBreak(Instant::now());
+                    self.long_break = self.earns_long_break();
+                    self.state = PomodoroState::OnBreak(now);
+                } else {
+                    self.tick();
                 }
             }
             PomodoroState::OnBreak(_) => {
-                if self.state.elapsed() >= self.break_length {
+                if self.state.elapsed(now) >= self.current_break_length() {
                     if self.use_nag {
-                        self.nag(&self.break_message, "warning");
+                        if self.long_break {
+                            self.nag(&self.long_break_message, self.long_break_nag_level);
+                        } else {
+                            self.nag(&self.break_message, self.break_nag_level);
+                        }
                     }
                     self.state = PomodoroState::Stopped;
                     self.count += 1;
@@ -198,29 +1250,29 @@ impl Block for Pomodoro {
 
     fn click(&mut self, event: &I3BarEvent) -> Result<()> {
         if let Some(ref name) = event.name {
-            if name.as_str() == self.id {
+            let on_count = self.count_widget.is_some() && *name == self.count_id;
+            if on_count || name.as_str() == self.id {
+                // Any other click cancels a pending reset
+                let reset_confirmed = self.reset_pending();
+                self.reset_requested = None;
                 match event.button {
+                    MouseButton::Right if self.confirm_reset && !reset_confirmed => {
+                        self.reset_requested = Some(self.clock.now());
+                    }
                     MouseButton::Right => {
                         self.state = PomodoroState::Stopped;
                         self.count = 0;
                     }
-                    _ => match &self.state {
-                        PomodoroState::Stopped => {
-                            self.state = PomodoroState::Started(Instant::now());
-                        }
-                        PomodoroState::Started(_) => {
-                            self.state = PomodoroState::Paused(self.state.elapsed());
-                        }
-                        PomodoroState::Paused(duration) => {
-                            self.state = PomodoroState::Started(
-                                Instant::now().checked_sub(duration.to_owned()).unwrap(),
-                            );
-                        }
-                        PomodoroState::OnBreak(_) => {
-                            self.state = PomodoroState::Started(Instant::now());
-                            self.count += 1;
-                        }
-                    },
+                    // Lets the count be corrected by hand
+                    MouseButton::WheelUp if on_count || self.count_widget.is_none() => {
+                        self.count += 1
+                    }
+                    MouseButton::WheelDown if on_count || self.count_widget.is_none() => {
+                        self.count = self.count.saturating_sub(1)
+                    }
+                    MouseButton::WheelUp => self.step_length(true),
+                    MouseButton::WheelDown => self.step_length(false),
+                    _ => self.toggle(),
                 }
             }
         }
@@ -230,6 +1282,172 @@ impl Block for Pomodoro {
     }
 
     fn view(&self) -> Vec<&dyn I3BarWidget> {
-        vec![&self.time]
+        match self.count_widget {
+            Some((ref count_widget, _)) if self.shows_count() => vec![count_widget, &self.time],
+            _ => vec![&self.time],
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::cell::{Cell, RefCell};
+    use std::path::Path;
+    use std::rc::Rc;
+    use std::time::{Duration, Instant};
+
+    use chrono::{Local, NaiveDate, TimeZone};
+
+    use crate::blocks::pomodoro::{
+        sessions_per_day, Clock, NagLevel, Pomodoro, PomodoroConfig, PomodoroState,
+    };
+    use crate::blocks::{Block, ConfigBlock};
+    use crate::config::Config;
+
+    /// A clock that only moves when told to.
+    #[derive(Clone)]
+    struct MockClock(Rc<Cell<Instant>>);
+
+    impl MockClock {
+        fn advance(&self, minutes: u64) {
+            self.0.set(self.0.get() + Duration::from_secs(minutes * 60));
+        }
+    }
+
+    impl Clock for MockClock {
+        fn now(&self) -> Instant {
+            self.0.get()
+        }
+    }
+
+    thread_local! {
+        static NAGS: RefCell<Vec<String>> = const { RefCell::new(Vec::new()) };
+    }
+
+    fn record_nag(_nag_path: &Path, message: &str, _level: NagLevel) {
+        NAGS.with(|nags| nags.borrow_mut().push(message.to_string()));
+    }
+
+    fn nags() -> Vec<String> {
+        NAGS.with(|nags| nags.borrow().clone())
+    }
+
+    fn pomodoro(config: &str) -> (Pomodoro, MockClock) {
+        let block_config: PomodoroConfig = toml::from_str(config).unwrap();
+        let (tx, _) = crossbeam_channel::unbounded();
+        let mut pomodoro = Pomodoro::new(block_config, Config::default(), tx).unwrap();
+        let clock = MockClock(Rc::new(Cell::new(Instant::now())));
+        pomodoro.clock = Box::new(clock.clone());
+        pomodoro.nagger = record_nag;
+        (pomodoro, clock)
+    }
+
+    #[test]
+    fn test_session() {
+        let (mut pomodoro, clock) = pomodoro("use_nag = true");
+        pomodoro.toggle();
+        assert!(matches!(pomodoro.state, PomodoroState::Started(_)));
+
+        clock.advance(24);
+        pomodoro.update().unwrap();
+        assert_eq!(pomodoro.state.time(clock.now()), "24:00");
+        assert!(matches!(pomodoro.state, PomodoroState::Started(_)));
+        assert!(nags().is_empty());
+
+        clock.advance(1);
+        pomodoro.update().unwrap();
+        assert!(matches!(pomodoro.state, PomodoroState::OnBreak(_)));
+        assert_eq!(pomodoro.count, 0);
+        assert_eq!(nags(), vec!["Pomodoro over! Take a break!"]);
+
+        clock.advance(5);
+        pomodoro.update().unwrap();
+        assert!(matches!(pomodoro.state, PomodoroState::Stopped));
+        assert_eq!(pomodoro.count, 1);
+        assert_eq!(
+            nags(),
+            vec!["Pomodoro over! Take a break!", "Break over! Time to work!"]
+        );
+    }
+
+    #[test]
+    fn test_pause() {
+        let (mut pomodoro, clock) = pomodoro("length = 20");
+        pomodoro.toggle();
+        clock.advance(10);
+        pomodoro.toggle();
+        assert!(matches!(pomodoro.state, PomodoroState::Paused(_)));
+
+        // Time does not pass while paused
+        clock.advance(60);
+        pomodoro.update().unwrap();
+        assert_eq!(pomodoro.state.time(clock.now()), "10:00");
+
+        pomodoro.toggle();
+        clock.advance(9);
+        pomodoro.update().unwrap();
+        assert!(matches!(pomodoro.state, PomodoroState::Started(_)));
+        clock.advance(1);
+        pomodoro.update().unwrap();
+        assert!(matches!(pomodoro.state, PomodoroState::OnBreak(_)));
+        assert!(nags().is_empty());
+    }
+
+    #[test]
+    fn test_clock() {
+        // One hour on the clock face per minute
+        let (mut pomodoro, clock) = pomodoro("length = 12");
+        let seconds = |secs| clock.0.set(clock.0.get() + Duration::from_secs(secs));
+        pomodoro.toggle();
+        assert_eq!(pomodoro.clock(), '\u{1f550}');
+
+        seconds(60);
+        assert_eq!(pomodoro.clock(), '\u{1f550}');
+        seconds(1);
+        assert_eq!(pomodoro.clock(), '\u{1f551}');
+
+        seconds(10 * 60);
+        assert_eq!(pomodoro.clock(), '\u{1f55b}');
+        seconds(59);
+        assert_eq!(pomodoro.clock(), '\u{1f55b}');
+    }
+
+    #[test]
+    fn test_long_break() {
+        let (mut pomodoro, clock) = pomodoro(
+            "use_nag = true\nstart_count = 3\nlong_break_after = 4\nlong_break_message = \"long\"",
+        );
+        pomodoro.toggle();
+        clock.advance(25);
+        pomodoro.update().unwrap();
+        assert!(pomodoro.long_break);
+
+        clock.advance(5);
+        pomodoro.update().unwrap();
+        assert!(matches!(pomodoro.state, PomodoroState::OnBreak(_)));
+
+        clock.advance(10);
+        pomodoro.update().unwrap();
+        assert!(matches!(pomodoro.state, PomodoroState::Stopped));
+        assert_eq!(pomodoro.count, 4);
+        assert_eq!(nags().last().map(String::as_str), Some("long"));
+    }
+
+    #[test]
+    fn test_sessions_per_day() {
+        let at = |day: u32, hour: u32| Local.ymd(2021, 2, day).and_hms(hour, 0, 0).to_rfc3339();
+        let log = format!(
+            "{} 1500\n{} 1500\n{} 1500\nnot a session\n{} 1500\n{} 1500\n",
+            at(1, 9),
+            at(3, 9),
+            at(3, 23),
+            // Too old and in the future
+            at(20, 9),
+            at(10, 9)
+        );
+        let today = NaiveDate::from_ymd(2021, 2, 3);
+        assert_eq!(sessions_per_day(&log, today, 3), vec![1, 0, 2]);
+        assert_eq!(sessions_per_day(&log, today, 1), vec![2]);
+        assert_eq!(sessions_per_day("", today, 2), vec![0, 0]);
     }
 }
@@ -0,0 +1,234 @@
+use std::fs;
+use std::path::PathBuf;
+use std::time::{Duration, Instant};
+
+use crossbeam_channel::Sender;
+use serde_derive::Deserialize;
+use uuid::Uuid;
+
+use crate::blocks::{Block, ConfigBlock, Update};
+use crate::config::Config;
+use crate::de::deserialize_duration;
+use crate::errors::*;
+use crate::scheduler::Task;
+use crate::util::{read_file, read_sysfs, FormatTemplate};
+use crate::widget::{I3BarWidget, State};
+use crate::widgets::text::TextWidget;
+
+const POWERCAP_PATH: &str = "/sys/class/powercap";
+
+/// An energy counter of a RAPL power zone, e.g. /sys/class/powercap/intel-rapl:0.
+struct Zone {
+    path: PathBuf,
+    /// Value in microjoules after which the counter wraps around to zero
+    max_energy: u64,
+    last_energy: Option<(u64, Instant)>,
+}
+
+impl Zone {
+    fn new(path: PathBuf) -> Result<Self> {
+        Ok(Zone {
+            max_energy: read_sysfs("rapl", &path.join("max_energy_range_uj"))?,
+            path,
+            last_energy: None,
+        })
+    }
+
+    /// Average power draw in watts since the previous call, `None` on the first
+    /// call.
+    fn watts(&mut self) -> Result<Option<f64>> {
+        let energy: u64 = read_sysfs("rapl", &self.path.join("energy_uj"))?;
+        let now = Instant::now();
+        let watts = match self.last_energy {
+            Some((last, last_time)) => {
+                let elapsed = now.duration_since(last_time).as_secs_f64();
+                let used = if energy >= last {
+                    energy - last
+                } else {
+                    // The counter wrapped around since the last reading
+                    self.max_energy - last + energy
+                };
+                if elapsed > 0. {
+                    Some(used as f64 / 1_000_000. / elapsed)
+                } else {
+                    None
+                }
+            }
+            None => None,
+        };
+        self.last_energy = Some((energy, now));
+        Ok(watts)
+    }
+}
+
+/// Sums the power draw of several zones, `None` if there are none or one of
+/// them has no reading yet.
+fn total_watts(zones: &mut [Zone]) -> Result<Option<f64>> {
+    if zones.is_empty() {
+        return Ok(None);
+    }
+    let mut total = Some(0.);
+    for zone in zones {
+        let watts = zone.watts()?;
+        total = total.and_then(|total| Some(total + watts?));
+    }
+    Ok(total)
+}
+
+/// Finds the package zones, intel-rapl:<package>, and the core zones below
+/// them, intel-rapl:<package>:<zone>, by their names.
+fn find_zones() -> Result<(Vec<Zone>, Vec<Zone>)> {
+    let mut paths: Vec<PathBuf> = fs::read_dir(POWERCAP_PATH)
+        .block_error("rapl", &format!("failed to read {}", POWERCAP_PATH))?
+        .filter_map(|entry| entry.ok())
+        .filter(|entry| {
+            entry
+                .file_name()
+                .to_string_lossy()
+                .starts_with("intel-rapl:")
+        })
+        .map(|entry| entry.path())
+        .collect();
+    paths.sort();
+
+    let mut packages = Vec::new();
+    let mut cores = Vec::new();
+    for path in paths {
+        let name = read_file("rapl", &path.join("name")).unwrap_or_default();
+        if name.starts_with("package") {
+            packages.push(Zone::new(path)?);
+        } else if name == "core" {
+            cores.push(Zone::new(path)?);
+        }
+    }
+    if packages.is_empty() {
+        return Err(BlockError(
+            "rapl".to_string(),
+            "no RAPL package zone found".to_string(),
+        ));
+    }
+    Ok((packages, cores))
+}
+
+pub struct Rapl {
+    id: String,
+    text: TextWidget,
+    packages: Vec<Zone>,
+    cores: Vec<Zone>,
+    format: FormatTemplate,
+    update_interval: Duration,
+    warning: f64,
+    critical: f64,
+}
+
+#[derive(Deserialize, Debug, Default, Clone)]
+#[serde(deny_unknown_fields)]
+pub struct RaplConfig {
+    /// Update interval in seconds
+    #[serde(
+        default = "RaplConfig::default_interval",
+        deserialize_with = "deserialize_duration"
+    )]
+    pub interval: Duration,
+
+    /// Format override
+    #[serde(default = "RaplConfig::default_format")]
+    pub format: String,
+
+    /// Minimum package power in watts, where state is set to warning
+    #[serde(default = "RaplConfig::default_warning")]
+    pub warning: f64,
+
+    /// Minimum package power in watts, where state is set to critical
+    #[serde(default = "RaplConfig::default_critical")]
+    pub critical: f64,
+}
+
+impl RaplConfig {
+    fn default_interval() -> Duration {
+        Duration::from_secs(5)
+    }
+
+    fn default_format() -> String {
+        "{package_watts}".to_owned()
+    }
+
+    fn default_warning() -> f64 {
+        60.
+    }
+
+    fn default_critical() -> f64 {
+        90.
+    }
+}
+
+impl ConfigBlock for Rapl {
+    type Config = RaplConfig;
+
+    fn new(
+        block_config: Self::Config,
+        config: Config,
+        _tx_update_request: Sender<Task>,
+    ) -> Result<Self> {
+        let (mut packages, mut cores) = find_zones()?;
+        // Take the first readings, so that the first update can show the power
+        total_watts(&mut packages).block_error(
+            "rapl",
+            "failed to read the energy counters, they may only be readable by root",
+        )?;
+        total_watts(&mut cores)?;
+
+        Ok(Rapl {
+            id: Uuid::new_v4().to_simple().to_string(),
+            text: TextWidget::new(config).with_icon("cpu"),
+            packages,
+            cores,
+            format: FormatTemplate::from_string(&block_config.format)
+                .block_error("rapl", "Invalid format specified")?,
+            update_interval: block_config.interval,
+            warning: block_config.warning,
+            critical: block_config.critical,
+        })
+    }
+}
+
+fn format_watts(watts: Option<f64>) -> String {
+    watts
+        .map(|watts| format!("{:.1}W", watts))
+        .unwrap_or_else(|| "N/A".to_string())
+}
+
+impl Block for Rapl {
+    fn update(&mut self) -> Result<Option<Update>> {
+        let package_watts = total_watts(&mut self.packages)?;
+        let core_watts = total_watts(&mut self.cores)?;
+
+        let values = map!(
+            "{package_watts}" => format_watts(package_watts),
+            "{core_watts}" => format_watts(core_watts)
+        );
+
+        self.text.set_state(match package_watts {
+            Some(watts) if watts >= self.critical => State::Critical,
+            Some(watts) if watts >= self.warning => State::Warning,
+            _ => State::Idle,
+        });
+        self.text.set_text(self.format.render_static_str(&values)?);
+
+        Ok(Some(self.update_interval.into()))
+    }
+
+    fn view(&self) -> Vec<&dyn I3BarWidget> {
+        vec![&self.text]
+    }
+
+    fn set_format(&mut self, format: &str) -> Result<()> {
+        self.format =
+            FormatTemplate::from_string(format).block_error("rapl", "Invalid format specified")?;
+        Ok(())
+    }
+
+    fn id(&self) -> &str {
+        &self.id
+    }
+}
@@ -2,8 +2,8 @@
 use {
     crate::pulse::callbacks::ListResult,
     crate::pulse::context::{
-        flags, introspect::ServerInfo, introspect::SinkInfo, introspect::SourceInfo,
-        subscribe::subscription_masks, subscribe::Facility,
+        flags, introspect::ServerInfo, introspect::SinkInfo, introspect::SinkInputInfo,
+        introspect::SourceInfo, subscribe::subscription_masks, subscribe::Facility,
         subscribe::Operation as SubscribeOperation, Context, State as PulseState,
     },
     crate::pulse::mainloop::standard::IterateResult,
@@ -17,6 +17,7 @@ use {
     std::convert::{TryFrom, TryInto},
     std::ops::Deref,
     std::rc::Rc,
+    std::sync::atomic::{AtomicBool, Ordering},
     std::sync::Mutex,
 };
 
@@ -46,6 +47,11 @@ trait SoundDevice {
     fn volume(&self) -> u32;
     fn muted(&self) -> bool;
     fn output_name(&self) -> String;
+    /// Whether there is anything to control, e.g. whether the application of
+    /// an `app` block is playing.
+    fn available(&self) -> bool {
+        true
+    }
 
     fn get_info(&mut self) -> Result<()>;
     fn set_volume(&mut self, step: i32, max_vol: Option<u32>) -> Result<()>;
@@ -261,6 +267,43 @@ impl TryFrom<&SinkInfo<'_>> for PulseAudioVolInfo {
     }
 }
 
+/// A stream played by an application.
+#[cfg(feature = "pulseaudio")]
+#[derive(Debug)]
+struct PulseAudioSinkInputInfo {
+    volume: ChannelVolumes,
+    mute: bool,
+    /// `application.name` of the stream
+    app_name: Option<String>,
+    /// `application.process.binary` of the stream
+    binary: Option<String>,
+}
+
+#[cfg(feature = "pulseaudio")]
+impl From<&SinkInputInfo<'_>> for PulseAudioSinkInputInfo {
+    fn from(sink_input_info: &SinkInputInfo) -> Self {
+        PulseAudioSinkInputInfo {
+            volume: sink_input_info.volume,
+            mute: sink_input_info.mute,
+            app_name: sink_input_info
+                .proplist
+                .get_str(properties::APPLICATION_NAME),
+            binary: sink_input_info
+                .proplist
+                .get_str(properties::APPLICATION_PROCESS_BINARY),
+        }
+    }
+}
+
+#[cfg(feature = "pulseaudio")]
+impl PulseAudioSinkInputInfo {
+    fn is_app(&self, app: &str) -> bool {
+        [&self.app_name, &self.binary]
+            .iter()
+            .any(|name| matches!(name, Some(name) if name.eq_ignore_ascii_case(app)))
+    }
+}
+
 #[cfg(feature = "pulseaudio")]
 #[derive(Debug)]
 enum PulseAudioClientRequest {
@@ -269,8 +312,17 @@ enum PulseAudioClientRequest {
     GetInfoByName(DeviceKind, String),
     SetVolumeByName(DeviceKind, String, ChannelVolumes),
     SetMuteByName(DeviceKind, String, bool),
+    GetSinkInputs,
+    GetSinkInputByIndex(u32),
+    SetSinkInputVolume(u32, ChannelVolumes),
+    SetSinkInputMute(u32, bool),
 }
 
+/// Whether a block controls the volume of an application, the streams are
+/// only tracked if so.
+#[cfg(feature = "pulseaudio")]
+static PULSEAUDIO_WATCH_SINK_INPUTS: AtomicBool = AtomicBool::new(false);
+
 #[cfg(feature = "pulseaudio")]
 lazy_static! {
     static ref PULSEAUDIO_CLIENT: Result<PulseAudioClient> = PulseAudioClient::new();
@@ -284,6 +336,10 @@ lazy_static! {
     // State for each device
     static ref PULSEAUDIO_DEVICES: Mutex<HashMap<(DeviceKind, String), PulseAudioVolInfo>> =
         Mutex::new(HashMap::new());
+
+    // Streams of the applications by their index
+    static ref PULSEAUDIO_SINK_INPUTS: Mutex<HashMap<u32, PulseAudioSinkInputInfo>> =
+        Mutex::new(HashMap::new());
 }
 
 #[cfg(feature = "pulseaudio")]
@@ -432,6 +488,23 @@ impl PulseAudioClient {
                                 SetMuteByName(DeviceKind::Source, name, mute) => {
                                     introspector.set_source_mute_by_name(&name, mute, None);
                                 }
+                                GetSinkInputs => {
+                                    introspector.get_sink_input_info_list(
+                                        PulseAudioClient::sink_input_info_callback,
+                                    );
+                                }
+                                GetSinkInputByIndex(index) => {
+                                    introspector.get_sink_input_info(
+                                        index,
+                                        PulseAudioClient::sink_input_info_callback,
+                                    );
+                                }
+                                SetSinkInputVolume(index, volumes) => {
+                                    introspector.set_sink_input_volume(index, &volumes, None);
+                                }
+                                SetSinkInputMute(index, mute) => {
+                                    introspector.set_sink_input_mute(index, mute, None);
+                                }
                             };
 
                             // send request and receive response
@@ -458,7 +531,8 @@ impl PulseAudioClient {
                 connection.context.borrow_mut().subscribe(
                     subscription_masks::SERVER
                         | subscription_masks::SINK
-                        | subscription_masks::SOURCE,
+                        | subscription_masks::SOURCE
+                        | subscription_masks::SINK_INPUT,
                     |_| {},
                 );
 
@@ -526,9 +600,20 @@ impl PulseAudioClient {
         }
     }
 
+    fn sink_input_info_callback(result: ListResult<&SinkInputInfo>) {
+        if let ListResult::Item(info) = result {
+            PULSEAUDIO_SINK_INPUTS
+                .lock()
+                .unwrap()
+                .insert(info.index, info.into());
+
+            PulseAudioClient::send_update_event();
+        }
+    }
+
     fn subscribe_callback(
         facility: Option<Facility>,
-        _operation: Option<SubscribeOperation>,
+        operation: Option<SubscribeOperation>,
         index: u32,
     ) {
         match facility {
@@ -551,6 +636,15 @@ impl PulseAudioClient {
                     ))
                     .ok();
                 }
+                Facility::SinkInput if PULSEAUDIO_WATCH_SINK_INPUTS.load(Ordering::Relaxed) => {
+                    if let Some(SubscribeOperation::Removed) = operation {
+                        PULSEAUDIO_SINK_INPUTS.lock().unwrap().remove(&index);
+                        PulseAudioClient::send_update_event();
+                    } else {
+                        PulseAudioClient::send(PulseAudioClientRequest::GetSinkInputByIndex(index))
+                            .ok();
+                    }
+                }
                 _ => {}
             },
         }
@@ -602,7 +696,30 @@ impl PulseAudioSoundDevice {
 
     fn volume(&mut self, volume: ChannelVolumes) {
         self.volume = Some(volume);
-        self.volume_avg = (volume.avg().0 as f32 / VOLUME_NORM.0 as f32 * 100.0).round() as u32;
+        self.volume_avg = volume_percent(&volume);
+    }
+}
+
+#[cfg(feature = "pulseaudio")]
+fn volume_percent(volume: &ChannelVolumes) -> u32 {
+    (volume.avg().0 as f32 / VOLUME_NORM.0 as f32 * 100.0).round() as u32
+}
+
+/// Changes all channels by `step` percent, up to `max_vol` percent.
+#[cfg(feature = "pulseaudio")]
+fn step_volume(volume: &mut ChannelVolumes, step: i32, max_vol: Option<u32>) {
+    let step = (step as f32 * VOLUME_NORM.0 as f32 / 100.0).round() as i32;
+    for vol in volume.get_mut().iter_mut() {
+        let uncapped_vol = max(0, vol.0 as i32 + step) as u32;
+        let capped_vol = if let Some(vol_cap) = max_vol {
+            min(
+                uncapped_vol,
+                (vol_cap as f32 * VOLUME_NORM.0 as f32 / 100.0).round() as u32,
+            )
+        } else {
+            uncapped_vol
+        };
+        vol.0 = min(capped_vol, VOLUME_MAX.0);
     }
 }
 
@@ -637,20 +754,7 @@ impl SoundDevice for PulseAudioSoundDevice {
             None => return Err(BlockError("sound".into(), "volume unknown".into())),
         };
 
-        // apply step to volumes
-        let step = (step as f32 * VOLUME_NORM.0 as f32 / 100.0).round() as i32;
-        for vol in volume.get_mut().iter_mut() {
-            let uncapped_vol = max(0, vol.0 as i32 + step) as u32;
-            let capped_vol = if let Some(vol_cap) = max_vol {
-                min(
-                    uncapped_vol,
-                    (vol_cap as f32 * VOLUME_NORM.0 as f32 / 100.0).round() as u32,
-                )
-            } else {
-                uncapped_vol
-            };
-            vol.0 = min(capped_vol, VOLUME_MAX.0);
-        }
+        step_volume(&mut volume, step, max_vol);
 
         // update volumes
         self.volume(volume);
@@ -684,6 +788,278 @@ impl SoundDevice for PulseAudioSoundDevice {
     }
 }
 
+/// The streams of an application, controlled together.
+#[cfg(feature = "pulseaudio")]
+struct PulseAudioAppDevice {
+    app: String,
+    /// Index and volume of the streams, empty while the application is not playing
+    streams: Vec<(u32, ChannelVolumes)>,
+    volume_avg: u32,
+    muted: bool,
+}
+
+#[cfg(feature = "pulseaudio")]
+impl PulseAudioAppDevice {
+    fn new(app: String) -> Result<Self> {
+        PULSEAUDIO_WATCH_SINK_INPUTS.store(true, Ordering::Relaxed);
+        PulseAudioClient::send(PulseAudioClientRequest::GetSinkInputs)?;
+
+        Ok(PulseAudioAppDevice {
+            app,
+            streams: Vec::new(),
+            volume_avg: 0,
+            muted: false,
+        })
+    }
+}
+
+#[cfg(feature = "pulseaudio")]
+impl SoundDevice for PulseAudioAppDevice {
+    fn volume(&self) -> u32 {
+        self.volume_avg
+    }
+
+    fn muted(&self) -> bool {
+        self.muted
+    }
+
+    fn output_name(&self) -> String {
+        self.app.clone()
+    }
+
+    fn available(&self) -> bool {
+        !self.streams.is_empty()
+    }
+
+    fn get_info(&mut self) -> Result<()> {
+        let sink_inputs = PULSEAUDIO_SINK_INPUTS.lock().unwrap();
+
+        let mut streams: Vec<(u32, &PulseAudioSinkInputInfo)> = sink_inputs
+            .iter()
+            .filter(|(_, info)| info.is_app(&self.app))
+            .map(|(index, info)| (*index, info))
+            .collect();
+        // Show the oldest stream
+        streams.sort_by_key(|(index, _)| *index);
+        if let Some((_, info)) = streams.first() {
+            self.volume_avg = volume_percent(&info.volume);
+            self.muted = info.mute;
+        }
+        self.streams = streams
+            .into_iter()
+            .map(|(index, info)| (index, info.volume))
+            .collect();
+
+        Ok(())
+    }
+
+    fn set_volume(&mut self, step: i32, max_vol: Option<u32>) -> Result<()> {
+        for (index, volume) in &mut self.streams {
+            step_volume(volume, step, max_vol);
+            PulseAudioClient::send(PulseAudioClientRequest::SetSinkInputVolume(*index, *volume))?;
+        }
+        if let Some((_, volume)) = self.streams.first() {
+            self.volume_avg = volume_percent(volume);
+        }
+
+        Ok(())
+    }
+
+    fn toggle(&mut self) -> Result<()> {
+        if self.streams.is_empty() {
+            return Ok(());
+        }
+        self.muted = !self.muted;
+
+        for (index, _) in &self.streams {
+            PulseAudioClient::send(PulseAudioClientRequest::SetSinkInputMute(
+                *index, self.muted,
+            ))?;
+        }
+
+        Ok(())
+    }
+
+    fn monitor(&mut self, id: String, tx_update_request: Sender<Task>) -> Result<()> {
+        PULSEAUDIO_EVENT_LISTENER
+            .lock()
+            .unwrap()
+            .insert(id, tx_update_request);
+        Ok(())
+    }
+}
+
+/// Creates the device controlling the streams of `app`, which requires
+/// PulseAudio (or PipeWire with `pipewire-pulse`).
+#[cfg(feature = "pulseaudio")]
+fn app_device(app: &str, block_config: &SoundConfig) -> Result<Box<dyn SoundDevice>> {
+    let conflict = if let SoundDriver::Alsa = block_config.driver {
+        Some("the alsa driver")
+    } else if block_config.device_kind == DeviceKind::Source {
+        Some("device_kind = \"source\"")
+    } else if block_config.name.is_some() {
+        Some("name")
+    } else {
+        None
+    };
+    if let Some(conflict) = conflict {
+        return Err(ConfigurationError(
+            "sound".to_string(),
+            (
+                format!("app cannot be combined with {}", conflict),
+                "invalid app".to_string(),
+            ),
+        ));
+    }
+    Ok(Box::new(PulseAudioAppDevice::new(app.to_string())?))
+}
+
+#[cfg(not(feature = "pulseaudio"))]
+fn app_device(_app: &str, _block_config: &SoundConfig) -> Result<Box<dyn SoundDevice>> {
+    Err(ConfigurationError(
+        "sound".to_string(),
+        (
+            "app requires the pulseaudio feature".to_string(),
+            "invalid app".to_string(),
+        ),
+    ))
+}
+
+/// A device as reported by the tool used to switch the default device.
+#[derive(Debug, PartialEq)]
+struct DeviceEntry {
+    id: String,
+    description: String,
+    is_default: bool,
+}
+
+#[derive(Deserialize, Copy, Clone, Debug, Default)]
+#[serde(rename_all = "lowercase")]
+pub enum DeviceSwitcher {
+    #[default]
+    Pactl,
+    Wpctl,
+}
+
+impl DeviceSwitcher {
+    fn devices(self, device_kind: DeviceKind) -> Result<Vec<DeviceEntry>> {
+        match self {
+            DeviceSwitcher::Pactl => {
+                let list_kind = match device_kind {
+                    DeviceKind::Sink => "sinks",
+                    DeviceKind::Source => "sources",
+                };
+                let default = pactl_default_device(device_kind)?;
+                let list = run_switcher_command("pactl", &["list", list_kind])?;
+                Ok(parse_pactl_devices(&list, default.as_deref()))
+            }
+            DeviceSwitcher::Wpctl => {
+                let status = run_switcher_command("wpctl", &["status"])?;
+                Ok(parse_wpctl_devices(&status, device_kind))
+            }
+        }
+    }
+
+    fn set_default(self, device_kind: DeviceKind, id: &str) -> Result<()> {
+        match self {
+            DeviceSwitcher::Pactl => {
+                let command = match device_kind {
+                    DeviceKind::Sink => "set-default-sink",
+                    DeviceKind::Source => "set-default-source",
+                };
+                run_switcher_command("pactl", &[command, id])?;
+            }
+            DeviceSwitcher::Wpctl => {
+                run_switcher_command("wpctl", &["set-default", id])?;
+            }
+        }
+
+        Ok(())
+    }
+}
+
+/// Queries the name of the default sink or source with `pactl`, which works
+/// with PipeWire too through `pipewire-pulse`.
+pub fn pactl_default_device(device_kind: DeviceKind) -> Result<Option<String>> {
+    let default_key = match device_kind {
+        DeviceKind::Sink => "Default Sink:",
+        DeviceKind::Source => "Default Source:",
+    };
+    let info = run_switcher_command("pactl", &["info"])?;
+    Ok(info
+        .lines()
+        .find_map(|line| line.trim().strip_prefix(default_key))
+        .map(|name| name.trim().to_string()))
+}
+
+fn run_switcher_command(command: &str, args: &[&str]) -> Result<String> {
+    let output = Command::new(command)
+        .args(args)
+        .env("LC_ALL", "C")
+        .output()
+        .block_error("sound", &format!("failed to run {}", command))?;
+    if !output.status.success() {
+        return Err(BlockError(
+            "sound".into(),
+            format!("{} exited with {}", command, output.status),
+        ));
+    }
+    Ok(String::from_utf8_lossy(&output.stdout).into_owned())
+}
+
+/// Parses the output of `pactl list sinks` (or `sources`), skipping monitor sources.
+fn parse_pactl_devices(output: &str, default: Option<&str>) -> Vec<DeviceEntry> {
+    let mut devices: Vec<DeviceEntry> = Vec::new();
+    for line in output.lines().map(str::trim) {
+        if let Some(name) = line.strip_prefix("Name:") {
+            let name = name.trim();
+            devices.push(DeviceEntry {
+                id: name.to_string(),
+                description: name.to_string(),
+                is_default: Some(name) == default,
+            });
+        } else if let Some(description) = line.strip_prefix("Description:") {
+            if let Some(device) = devices.last_mut() {
+                device.description = description.trim().to_string();
+            }
+        }
+    }
+    devices.retain(|device| !device.id.ends_with(".monitor"));
+    devices
+}
+
+/// Parses the `Sinks:` (or `Sources:`) section of the audio tree printed by `wpctl status`.
+fn parse_wpctl_devices(output: &str, device_kind: DeviceKind) -> Vec<DeviceEntry> {
+    let header = match device_kind {
+        DeviceKind::Sink => "Sinks:",
+        DeviceKind::Source => "Sources:",
+    };
+    let tree_chars: &[char] = &[' ', '│', '├', '└', '─'];
+
+    output
+        .lines()
+        .map(|line| line.trim_matches(tree_chars))
+        .skip_while(|line| *line != header)
+        .skip(1)
+        .take_while(|line| !line.is_empty())
+        .filter_map(|line| {
+            let is_default = line.starts_with('*');
+            let line = line.trim_start_matches('*').trim_start();
+            let dot = line.find(". ")?;
+            let id = &line[..dot];
+            let description = match line.rfind(" [") {
+                Some(end) if end > dot => &line[dot + 2..end],
+                _ => &line[dot + 2..],
+            };
+            Some(DeviceEntry {
+                id: id.to_string(),
+                description: description.trim().to_string(),
+                is_default,
+            })
+        })
+        .collect()
+}
+
 // TODO: Use the alsa control bindings to implement push updates
 pub struct Sound {
     text: ButtonWidget,
@@ -698,6 +1074,13 @@ pub struct Sound {
     bar: bool,
     mappings: Option<BTreeMap<String, String>>,
     max_vol: Option<u32>,
+    show_device: bool,
+    /// Description shown by `{device}` and the default device it was read for
+    device_description: Option<(String, String)>,
+    device_switcher: DeviceSwitcher,
+    switch_device_button: Option<MouseButton>,
+    max_device_width: usize,
+    hide_missing_app: bool,
 }
 
 #[derive(Clone, Copy, Debug, Deserialize, Eq, Hash, PartialEq)]
@@ -771,6 +1154,26 @@ pub struct SoundConfig {
 
     #[serde(default = "SoundConfig::default_max_vol")]
     pub max_vol: Option<u32>,
+
+    /// Tool used to query and change the default device: "pactl" or "wpctl"
+    #[serde(default)]
+    pub device_switcher: DeviceSwitcher,
+
+    /// Mouse button that cycles the default device, e.g. "middle"
+    #[serde(default = "SoundConfig::default_switch_device_button")]
+    pub switch_device_button: Option<MouseButton>,
+
+    /// Max number of characters of the device description shown by `{device}`
+    #[serde(default = "SoundConfig::default_max_device_width")]
+    pub max_device_width: usize,
+
+    /// Control the streams of this application instead of a device
+    #[serde(default)]
+    pub app: Option<String>,
+
+    /// Hide the block while the application of `app` is not playing
+    #[serde(default)]
+    pub hide_missing_app: bool,
 }
 
 #[derive(Deserialize, Copy, Clone, Debug)]
@@ -828,6 +1231,14 @@ impl SoundConfig {
     fn default_max_vol() -> Option<u32> {
         None
     }
+
+    fn default_switch_device_button() -> Option<MouseButton> {
+        None
+    }
+
+    fn default_max_device_width() -> usize {
+        20
+    }
 }
 
 impl Sound {
@@ -847,9 +1258,70 @@ impl Sound {
         format!("{}_{}", prefix, suffix)
     }
 
+    fn default_device_description(&self) -> Result<String> {
+        let description = self
+            .device_switcher
+            .devices(self.device_kind)?
+            .into_iter()
+            .find(|device| device.is_default)
+            .map(|device| device.description)
+            .unwrap_or_default();
+
+        Ok(description.chars().take(self.max_device_width).collect())
+    }
+
+    /// Identifies the default device, to notice when it changes. Without
+    /// PulseAudio events, it only changes through `cycle_device`.
+    fn default_device_key(&self) -> String {
+        #[cfg(feature = "pulseaudio")]
+        let key = self.device_kind.default_name();
+        #[cfg(not(feature = "pulseaudio"))]
+        let key = self.device.output_name();
+        key
+    }
+
+    /// The description of the default device, read again only when the
+    /// default device changed.
+    fn cached_device_description(&mut self) -> Result<String> {
+        let key = self.default_device_key();
+        match self.device_description {
+            Some((ref cached_key, ref description)) if *cached_key == key => {
+                Ok(description.clone())
+            }
+            _ => {
+                let description = self.default_device_description()?;
+                self.device_description = Some((key, description.clone()));
+                Ok(description)
+            }
+        }
+    }
+
+    fn cycle_device(&mut self) -> Result<()> {
+        let devices = self.device_switcher.devices(self.device_kind)?;
+        if devices.is_empty() {
+            return Ok(());
+        }
+
+        let next = match devices.iter().position(|device| device.is_default) {
+            Some(current) => (current + 1) % devices.len(),
+            None => 0,
+        };
+
+        self.device_description = None;
+        self.device_switcher
+            .set_default(self.device_kind, &devices[next].id)
+    }
+
     fn display(&mut self) -> Result<()> {
         self.device.get_info()?;
 
+        if !self.device.available() {
+            self.text.set_icon(&self.icon(0));
+            self.text.set_text("");
+            self.text.set_state(State::Idle);
+            return Ok(());
+        }
+
         let volume = self.device.volume();
         let output_name = self.device.output_name();
         let mapped_output_name = if let Some(m) = &self.mappings {
@@ -860,8 +1332,14 @@ impl Sound {
         } else {
             output_name
         };
+        let device = if self.show_device {
+            self.cached_device_description()?
+        } else {
+            String::new()
+        };
         let values = map!("{volume}" => format!("{:02}", volume),
-                          "{output_name}" => mapped_output_name
+                          "{output_name}" => mapped_output_name,
+                          "{device}" => device
         );
         let text = self.format.render_static_str(&values)?;
 
@@ -908,31 +1386,41 @@ impl ConfigBlock for Sound {
         #[cfg(not(feature = "pulseaudio"))]
         type PulseAudioSoundDevice = AlsaSoundDevice;
 
-        // try to create a pulseaudio device if feature is enabled and `driver != "alsa"`
-        let pulseaudio_device: Result<PulseAudioSoundDevice> = match block_config.driver {
-            #[cfg(feature = "pulseaudio")]
-            SoundDriver::Auto | SoundDriver::PulseAudio => {
-                let sound_device = PulseAudioSoundDevice::new(block_config.device_kind);
-
-                match block_config.name.as_ref() {
-                    None => sound_device,
-                    Some(name) => sound_device.map(|device| device.with_name(name.to_string())),
+        let device: Box<dyn SoundDevice> = match block_config.app {
+            Some(ref app) => app_device(app, &block_config)?,
+            None => {
+                // try to create a pulseaudio device if feature is enabled and `driver != "alsa"`
+                let pulseaudio_device: Result<PulseAudioSoundDevice> = match block_config.driver {
+                    #[cfg(feature = "pulseaudio")]
+                    SoundDriver::Auto | SoundDriver::PulseAudio => {
+                        let sound_device = PulseAudioSoundDevice::new(block_config.device_kind);
+
+                        match block_config.name.as_ref() {
+                            None => sound_device,
+                            Some(name) => {
+                                sound_device.map(|device| device.with_name(name.to_string()))
+                            }
+                        }
+                    }
+                    _ => Err(BlockError(
+                        "sound".into(),
+                        "PulseAudio feature or driver disabled".into(),
+                    )),
+                };
+
+                // prefer PulseAudio if available and selected, fallback to ALSA
+                match pulseaudio_device {
+                    Ok(dev) => Box::new(dev),
+                    Err(_) => Box::new(AlsaSoundDevice::new(
+                        block_config.name.clone().unwrap_or_else(|| "Master".into()),
+                        block_config
+                            .device
+                            .clone()
+                            .unwrap_or_else(|| "default".into()),
+                        block_config.natural_mapping,
+                    )?),
                 }
             }
-            _ => Err(BlockError(
-                "sound".into(),
-                "PulseAudio feature or driver disabled".into(),
-            )),
-        };
-
-        // prefer PulseAudio if available and selected, fallback to ALSA
-        let device: Box<dyn SoundDevice> = match pulseaudio_device {
-            Ok(dev) => Box::new(dev),
-            Err(_) => Box::new(AlsaSoundDevice::new(
-                block_config.name.unwrap_or_else(|| "Master".into()),
-                block_config.device.unwrap_or_else(|| "default".into()),
-                block_config.natural_mapping,
-            )?),
         };
 
         let mut sound = Self {
@@ -948,6 +1436,12 @@ impl ConfigBlock for Sound {
             bar: block_config.bar,
             mappings: block_config.mappings,
             max_vol: block_config.max_vol,
+            show_device: block_config.format.contains("{device}"),
+            device_description: None,
+            device_switcher: block_config.device_switcher,
+            switch_device_button: block_config.switch_device_button,
+            max_device_width: block_config.max_device_width,
+            hide_missing_app: block_config.hide_missing_app,
         };
 
         sound.device.monitor(id, tx_update_request)?;
@@ -966,13 +1460,18 @@ impl Block for Sound {
     }
 
     fn view(&self) -> Vec<&dyn I3BarWidget> {
-        vec![&self.text]
+        if self.hide_missing_app && !self.device.available() {
+            vec![]
+        } else {
+            vec![&self.text]
+        }
     }
 
     fn click(&mut self, e: &I3BarEvent) -> Result<()> {
         if let Some(ref name) = e.name {
             if name.as_str() == self.id {
                 match e.button {
+                    button if Some(button) == self.switch_device_button => self.cycle_device()?,
                     MouseButton::Right => self.device.toggle()?,
                     MouseButton::Left => {
                         if let Some(ref cmd) = self.on_click {
@@ -1004,3 +1503,65 @@ impl Block for Sound {
         &self.id
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use crate::blocks::sound::{parse_pactl_devices, parse_wpctl_devices, DeviceKind};
+
+    #[test]
+    fn test_parse_pactl_devices() {
+        let output = concat!(
+            "Sink #0\n",
+            "\tState: SUSPENDED\n",
+            "\tName: alsa_output.pci-0000_00_1f.3.analog-stereo\n",
+            "\tDescription: Built-in Audio Analog Stereo\n",
+            "\n",
+            "Sink #1\n",
+            "\tState: RUNNING\n",
+            "\tName: bluez_sink.00_11_22_33_44_55.a2dp_sink\n",
+            "\tDescription: Headphones\n",
+        );
+        let devices = parse_pactl_devices(output, Some("bluez_sink.00_11_22_33_44_55.a2dp_sink"));
+        assert_eq!(devices.len(), 2);
+        assert_eq!(devices[0].description, "Built-in Audio Analog Stereo");
+        assert!(!devices[0].is_default);
+        assert_eq!(devices[1].id, "bluez_sink.00_11_22_33_44_55.a2dp_sink");
+        assert!(devices[1].is_default);
+
+        let monitors = concat!(
+            "Source #0\n",
+            "\tName: alsa_output.pci-0000_00_1f.3.analog-stereo.monitor\n",
+            "\tDescription: Monitor of Built-in Audio Analog Stereo\n",
+        );
+        assert!(parse_pactl_devices(monitors, None).is_empty());
+    }
+
+    #[test]
+    fn test_parse_wpctl_devices() {
+        let output = concat!(
+            "Audio\n",
+            " ├─ Devices:\n",
+            " │      42. Built-in Audio                      [alsa]\n",
+            " │  \n",
+            " ├─ Sinks:\n",
+            " │      46. Built-in Audio Analog Stereo        [vol: 0.40]\n",
+            " │  *   53. HDMI / DisplayPort                  [vol: 1.00]\n",
+            " │  \n",
+            " ├─ Sources:\n",
+            " │  *   47. Built-in Audio Analog Stereo        [vol: 1.00]\n",
+            " │  \n",
+        );
+        let sinks = parse_wpctl_devices(output, DeviceKind::Sink);
+        assert_eq!(sinks.len(), 2);
+        assert_eq!(sinks[0].id, "46");
+        assert_eq!(sinks[0].description, "Built-in Audio Analog Stereo");
+        assert!(!sinks[0].is_default);
+        assert_eq!(sinks[1].id, "53");
+        assert_eq!(sinks[1].description, "HDMI / DisplayPort");
+        assert!(sinks[1].is_default);
+
+        let sources = parse_wpctl_devices(output, DeviceKind::Source);
+        assert_eq!(sources.len(), 1);
+        assert!(sources[0].is_default);
+    }
+}
@@ -0,0 +1,200 @@
+use std::process::Command;
+use std::time::{Duration, Instant};
+
+use crossbeam_channel::Sender;
+use serde_derive::Deserialize;
+use uuid::Uuid;
+
+use crate::blocks::{Block, ConfigBlock, Update};
+use crate::config::Config;
+use crate::de::deserialize_duration;
+use crate::errors::*;
+use crate::input::{I3BarEvent, MouseButton};
+use crate::scheduler::Task;
+use crate::signals::convert_to_valid_signal;
+use crate::subprocess::spawn_child_async;
+use crate::util::FormatTemplate;
+use crate::widget::{I3BarWidget, State};
+use crate::widgets::button::ButtonWidget;
+
+#[derive(Deserialize, Debug, Copy, Clone, PartialEq, Eq, Default)]
+#[serde(rename_all = "lowercase")]
+pub enum SystemdScope {
+    #[default]
+    System,
+    User,
+    Both,
+}
+
+impl SystemdScope {
+    fn managers(self) -> &'static [bool] {
+        // Whether to pass `--user` to systemctl, per manager to query
+        match self {
+            SystemdScope::System => &[false],
+            SystemdScope::User => &[true],
+            SystemdScope::Both => &[false, true],
+        }
+    }
+}
+
+pub struct Systemd {
+    id: String,
+    output: ButtonWidget,
+    format: FormatTemplate,
+    scope: SystemdScope,
+    update_interval: Duration,
+    on_click: Option<String>,
+    signal: Option<i32>,
+    tx_update_request: Sender<Task>,
+}
+
+#[derive(Deserialize, Debug, Default, Clone)]
+#[serde(deny_unknown_fields)]
+pub struct SystemdConfig {
+    /// Update interval in seconds
+    #[serde(
+        default = "SystemdConfig::default_interval",
+        deserialize_with = "deserialize_duration"
+    )]
+    pub interval: Duration,
+
+    /// Which service managers to query
+    #[serde(default)]
+    pub scope: SystemdScope,
+
+    /// Format override
+    #[serde(default = "SystemdConfig::default_format")]
+    pub format: String,
+
+    /// Command to run on left click
+    #[serde(default)]
+    pub on_click: Option<String>,
+
+    /// Signal to update upon reception
+    #[serde(default)]
+    pub signal: Option<i32>,
+}
+
+impl SystemdConfig {
+    fn default_interval() -> Duration {
+        Duration::from_secs(30)
+    }
+
+    fn default_format() -> String {
+        "{count}".to_owned()
+    }
+}
+
+impl ConfigBlock for Systemd {
+    type Config = SystemdConfig;
+
+    fn new(
+        block_config: Self::Config,
+        config: Config,
+        tx_update_request: Sender<Task>,
+    ) -> Result<Self> {
+        let id = Uuid::new_v4().to_simple().to_string();
+        let signal = match block_config.signal {
+            Some(signal) => Some(convert_to_valid_signal(signal)?),
+            None => None,
+        };
+
+        Ok(Systemd {
+            output: ButtonWidget::new(config, &id).with_text("N/A"),
+            id,
+            format: FormatTemplate::from_string(&block_config.format)
+                .block_error("systemd", "Invalid format specified")?,
+            scope: block_config.scope,
+            update_interval: block_config.interval,
+            on_click: block_config.on_click,
+            signal,
+            tx_update_request,
+        })
+    }
+}
+
+/// Lists the names of the failed units of the system or the user manager.
+fn failed_units(user: bool) -> Result<Vec<String>> {
+    let mut command = Command::new("systemctl");
+    if user {
+        command.arg("--user");
+    }
+    let output = command
+        .arg("list-units")
+        .arg("--failed")
+        .arg("--plain")
+        .arg("--no-legend")
+        .output()
+        .block_error("systemd", "failed to execute systemctl")?;
+    if !output.status.success() {
+        return Err(BlockError(
+            "systemd".to_string(),
+            String::from_utf8_lossy(&output.stderr).trim().to_string(),
+        ));
+    }
+
+    Ok(String::from_utf8_lossy(&output.stdout)
+        .lines()
+        .filter_map(|line| line.split_whitespace().next())
+        .map(|unit| unit.to_string())
+        .collect())
+}
+
+impl Block for Systemd {
+    fn update(&mut self) -> Result<Option<Update>> {
+        let mut units = Vec::new();
+        for &user in self.scope.managers() {
+            units.extend(failed_units(user)?);
+        }
+
+        let values = map!(
+            "{count}" => units.len().to_string(),
+            "{first}" => units.first().cloned().unwrap_or_default()
+        );
+        self.output
+            .set_text(self.format.render_static_str(&values)?);
+        self.output.set_state(if units.is_empty() {
+            State::Good
+        } else {
+            State::Critical
+        });
+
+        Ok(Some(self.update_interval.into()))
+    }
+
+    fn view(&self) -> Vec<&dyn I3BarWidget> {
+        vec![&self.output]
+    }
+
+    fn signal(&mut self, signal: i32) -> Result<()> {
+        if self.signal == Some(signal) {
+            self.tx_update_request.send(Task {
+                id: self.id.clone(),
+                update_time: Instant::now(),
+            })?;
+        }
+        Ok(())
+    }
+
+    fn click(&mut self, e: &I3BarEvent) -> Result<()> {
+        if e.matches_name(self.id()) {
+            if let MouseButton::Left = e.button {
+                if let Some(ref cmd) = self.on_click {
+                    spawn_child_async("sh", &["-c", cmd])
+                        .block_error("systemd", "could not spawn child")?;
+                }
+            }
+        }
+        Ok(())
+    }
+
+    fn set_format(&mut self, format: &str) -> Result<()> {
+        self.format = FormatTemplate::from_string(format)
+            .block_error("systemd", "Invalid format specified")?;
+        Ok(())
+    }
+
+    fn id(&self) -> &str {
+        &self.id
+    }
+}
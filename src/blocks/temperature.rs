@@ -1,8 +1,11 @@
 use std::collections::HashMap;
+use std::fs;
+use std::path::PathBuf;
 use std::process::Command;
 use std::time::Duration;
 
 use crossbeam_channel::Sender;
+use regex::Regex;
 use serde_derive::Deserialize;
 use uuid::Uuid;
 
@@ -13,7 +16,7 @@ use crate::de::deserialize_duration;
 use crate::errors::*;
 use crate::input::{I3BarEvent, MouseButton};
 use crate::scheduler::Task;
-use crate::util::FormatTemplate;
+use crate::util::{has_command, read_file, read_sysfs, FormatTemplate};
 use crate::widget::{I3BarWidget, Spacing, State};
 use crate::widgets::button::ButtonWidget;
 
@@ -30,6 +33,16 @@ impl Default for TemperatureScale {
     }
 }
 
+#[derive(Clone, Copy, Debug, Deserialize, Eq, PartialEq, Default)]
+#[serde(rename_all = "lowercase")]
+pub enum TemperatureSource {
+    /// `sensors` if it is installed, `sysfs` otherwise
+    #[default]
+    Auto,
+    Sensors,
+    Sysfs,
+}
+
 pub struct Temperature {
     text: ButtonWidget,
     output: String,
@@ -44,6 +57,9 @@ pub struct Temperature {
     format: FormatTemplate,
     chip: Option<String>,
     inputs: Option<Vec<String>>,
+    source: TemperatureSource,
+    /// Files of the selected temperature inputs in `/sys/class/hwmon`
+    hwmon_inputs: Option<Vec<PathBuf>>,
 }
 
 #[derive(Deserialize, Debug, Default, Clone)]
@@ -88,9 +104,13 @@ pub struct TemperatureConfig {
     #[serde(default = "TemperatureConfig::default_chip")]
     pub chip: Option<String>,
 
-    /// Inputs whitelist
+    /// Inputs whitelist, entries may be qualified by chip as `chip:input`
     #[serde(default = "TemperatureConfig::default_inputs")]
     pub inputs: Option<Vec<String>>,
+
+    /// Where to read temperatures from
+    #[serde(default)]
+    pub source: TemperatureSource,
 }
 
 impl TemperatureConfig {
@@ -124,6 +144,13 @@ impl ConfigBlock for Temperature {
         _tx_update_request: Sender<Task>,
     ) -> Result<Self> {
         let id = Uuid::new_v4().to_simple().to_string();
+        let source = match block_config.source {
+            TemperatureSource::Auto if has_command("temperature", "sensors")? => {
+                TemperatureSource::Sensors
+            }
+            TemperatureSource::Auto => TemperatureSource::Sysfs,
+            source => source,
+        };
         Ok(Temperature {
             update_interval: block_config.interval,
             text: ButtonWidget::new(config, &id)
@@ -165,6 +192,8 @@ impl ConfigBlock for Temperature {
                 .block_error("temperature", "Invalid format specified for temperature")?,
             chip: block_config.chip,
             inputs: block_config.inputs,
+            source,
+            hwmon_inputs: None,
         })
     }
 }
@@ -172,8 +201,23 @@ impl ConfigBlock for Temperature {
 type SensorsOutput = HashMap<String, HashMap<String, serde_json::Value>>;
 type InputReadings = HashMap<String, f64>;
 
-impl Block for Temperature {
-    fn update(&mut self) -> Result<Option<Update>> {
+impl Temperature {
+    fn is_input_selected(&self, chip: &str, input: &str) -> bool {
+        match self.inputs {
+            None => true,
+            Some(ref whitelist) => whitelist.iter().any(|entry| {
+                let mut parts = entry.splitn(2, ':');
+                match (parts.next(), parts.next()) {
+                    (Some(entry_chip), Some(entry_input)) => {
+                        entry_chip == chip && entry_input == input
+                    }
+                    _ => entry == input,
+                }
+            }),
+        }
+    }
+
+    fn read_sensors(&self) -> Result<Vec<i64>> {
         let mut args = vec!["-j"];
         if let TemperatureScale::Fahrenheit = self.scale {
             args.push("-f");
@@ -191,12 +235,10 @@ impl Block for Temperature {
             .block_error("temperature", "sensors output is invalid")?;
 
         let mut temperatures: Vec<i64> = Vec::new();
-        for (_chip, inputs) in parsed {
+        for (chip, inputs) in parsed {
             for (input_name, input_values) in inputs {
-                if let Some(ref whitelist) = self.inputs {
-                    if !whitelist.contains(&input_name) {
-                        continue;
-                    }
+                if !self.is_input_selected(&chip, &input_name) {
+                    continue;
                 }
 
                 let values_parsed: InputReadings = match serde_json::from_value(input_values) {
@@ -209,16 +251,105 @@ impl Block for Temperature {
                         continue;
                     }
 
-                    if value > -101f64 && value < 151f64 {
-                        temperatures.push(value as i64);
-                    } else {
-                        // This error is recoverable and therefore should not stop the program
-                        eprintln!("Temperature ({}) outside of range ([-100, 150])", value);
-                    }
+                    push_temperature(&mut temperatures, value);
+                }
+            }
+        }
+
+        Ok(temperatures)
+    }
+
+    /// Walks `/sys/class/hwmon` for the temperature inputs matching `chip`
+    /// and `inputs`. The result is cached, since hwmon devices rarely change.
+    fn find_hwmon_inputs(&self) -> Result<Vec<PathBuf>> {
+        let chip_regex = match self.chip {
+            Some(ref chip) => Some(
+                Regex::new(&format!("^{}$", regex::escape(chip).replace(r"\*", ".*")))
+                    .block_error("temperature", "invalid chip name")?,
+            ),
+            None => None,
+        };
+
+        let mut inputs = Vec::new();
+        let hwmon_dirs = fs::read_dir("/sys/class/hwmon")
+            .block_error("temperature", "failed to read /sys/class/hwmon")?;
+        for hwmon_dir in hwmon_dirs.filter_map(|entry| entry.ok()).map(|e| e.path()) {
+            let chip = match read_file("temperature", &hwmon_dir.join("name")) {
+                Ok(chip) => chip,
+                Err(_) => continue,
+            };
+            if let Some(ref chip_regex) = chip_regex {
+                if !chip_regex.is_match(&chip) {
+                    continue;
+                }
+            }
+
+            let files = match fs::read_dir(&hwmon_dir) {
+                Ok(files) => files,
+                Err(_) => continue,
+            };
+            for file_name in files.filter_map(|entry| entry.ok()).map(|e| e.file_name()) {
+                let file_name = file_name.to_string_lossy();
+                if !file_name.starts_with("temp") || !file_name.ends_with("_input") {
+                    continue;
+                }
+
+                let sensor = file_name.trim_end_matches("_input");
+                let feature =
+                    read_file("temperature", &hwmon_dir.join(format!("{}_label", sensor)))
+                        .unwrap_or_else(|_| sensor.to_string());
+                if self.is_input_selected(&chip, &feature) {
+                    inputs.push(hwmon_dir.join(file_name.as_ref()));
                 }
             }
         }
 
+        Ok(inputs)
+    }
+
+    fn read_hwmon(&mut self) -> Result<Vec<i64>> {
+        if self.hwmon_inputs.is_none() {
+            self.hwmon_inputs = Some(self.find_hwmon_inputs()?);
+        }
+
+        let mut temperatures: Vec<i64> = Vec::new();
+        for input in self.hwmon_inputs.iter().flatten() {
+            // Some sensors fail to read (EIO, ENODATA) while the chip is
+            // asleep or not wired up, the others still give a temperature
+            let millidegrees = match read_sysfs::<f64>("temperature", input) {
+                Ok(millidegrees) => millidegrees,
+                Err(_) => continue,
+            };
+            let celsius = millidegrees / 1000.0;
+            push_temperature(
+                &mut temperatures,
+                match self.scale {
+                    TemperatureScale::Celsius => celsius,
+                    TemperatureScale::Fahrenheit => celsius * 9.0 / 5.0 + 32.0,
+                },
+            );
+        }
+
+        Ok(temperatures)
+    }
+}
+
+fn push_temperature(temperatures: &mut Vec<i64>, value: f64) {
+    if value > -101f64 && value < 151f64 {
+        temperatures.push(value as i64);
+    } else {
+        // This error is recoverable and therefore should not stop the program
+        eprintln!("Temperature ({}) outside of range ([-100, 150])", value);
+    }
+}
+
+impl Block for Temperature {
+    fn update(&mut self) -> Result<Option<Update>> {
+        let temperatures = match self.source {
+            TemperatureSource::Sysfs => self.read_hwmon()?,
+            _ => self.read_sensors()?,
+        };
+
         if !temperatures.is_empty() {
             let max: i64 = *temperatures
                 .iter()
@@ -15,7 +15,7 @@ use crate::config::Config;
 use crate::de::deserialize_duration;
 use crate::errors::*;
 use crate::input::{I3BarEvent, MouseButton};
-use crate::scheduler::Task;
+use crate::scheduler::{next_boundary, Task};
 use crate::subprocess::spawn_child_async;
 use crate::widget::I3BarWidget;
 use crate::widgets::button::ButtonWidget;
@@ -120,7 +120,8 @@ impl Block for Time {
             },
         };
         self.time.set_text(format!("{}", time));
-        Ok(Some(self.update_interval.into()))
+        // Aligned to the clock, so that e.g. the minutes flip right on time
+        Ok(Some(Update::At(next_boundary(self.update_interval))))
     }
 
     fn click(&mut self, e: &I3BarEvent) -> Result<()> {
@@ -2,6 +2,8 @@ use crate::scheduler::Task;
 use crossbeam_channel::Sender;
 use serde_derive::Deserialize;
 use std::env;
+use std::fs;
+use std::path::PathBuf;
 use std::process::Command;
 use std::time::Duration;
 
@@ -16,11 +18,30 @@ use crate::widgets::button::ButtonWidget;
 
 use uuid::Uuid;
 
+/// Where the state of the toggle is read from.
+enum StateSource {
+    /// Shell command, the toggle is on if it prints anything
+    Command(String),
+    /// File, the toggle is on if it contains `on_value`
+    File(PathBuf),
+}
+
+/// How the toggle is switched.
+enum Control {
+    Commands {
+        on: String,
+        off: String,
+    },
+    /// File that `on_value` or `off_value` is written to
+    File(PathBuf),
+}
+
 pub struct Toggle {
     text: ButtonWidget,
-    command_on: String,
-    command_off: String,
-    command_state: String,
+    state_source: StateSource,
+    control: Control,
+    on_value: String,
+    off_value: String,
     icon_on: String,
     icon_off: String,
     update_interval: Option<Duration>,
@@ -36,13 +57,33 @@ pub struct ToggleConfig {
     pub interval: Option<Duration>,
 
     /// Shell Command to enable the toggle
-    pub command_on: String,
+    #[serde(default)]
+    pub command_on: Option<String>,
 
     /// Shell Command to disable the toggle
-    pub command_off: String,
+    #[serde(default)]
+    pub command_off: Option<String>,
 
     /// Shell Command to determine toggle state. <br/>Empty output => off. Any output => on.
-    pub command_state: String,
+    #[serde(default)]
+    pub command_state: Option<String>,
+
+    /// File to read the toggle state from instead of running `command_state`
+    #[serde(default)]
+    pub state_file: Option<PathBuf>,
+
+    /// File to write `on_value` or `off_value` to instead of running the
+    /// commands, `state_file` by default
+    #[serde(default)]
+    pub control_file: Option<PathBuf>,
+
+    /// Content of `state_file` while on, and value written to turn on
+    #[serde(default = "ToggleConfig::default_on_value")]
+    pub on_value: String,
+
+    /// Value written to turn off
+    #[serde(default = "ToggleConfig::default_off_value")]
+    pub off_value: String,
 
     /// Icon ID when toggled on (default is "toggle_on")
     #[serde(default = "ToggleConfig::default_icon_on")]
@@ -64,6 +105,21 @@ impl ToggleConfig {
     fn default_icon_off() -> String {
         "toggle_off".to_owned()
     }
+
+    fn default_on_value() -> String {
+        "1".to_owned()
+    }
+
+    fn default_off_value() -> String {
+        "0".to_owned()
+    }
+}
+
+fn configuration_error(message: &str) -> Error {
+    ConfigurationError(
+        "toggle".to_string(),
+        (message.to_string(), "invalid configuration".to_string()),
+    )
 }
 
 impl ConfigBlock for Toggle {
@@ -75,11 +131,47 @@ impl ConfigBlock for Toggle {
         _tx_update_request: Sender<Task>,
     ) -> Result<Self> {
         let id = Uuid::new_v4().to_simple().to_string();
+
+        let state_source = match (block_config.command_state, block_config.state_file.clone()) {
+            (Some(command), None) => StateSource::Command(command),
+            (None, Some(path)) => StateSource::File(path),
+            (Some(_), Some(_)) => {
+                return Err(configuration_error(
+                    "only one of command_state and state_file can be set",
+                ))
+            }
+            (None, None) => {
+                return Err(configuration_error(
+                    "either command_state or state_file must be set",
+                ))
+            }
+        };
+        let control = match (
+            block_config.command_on,
+            block_config.command_off,
+            block_config.control_file.or(block_config.state_file),
+        ) {
+            (Some(on), Some(off), None) => Control::Commands { on, off },
+            (None, None, Some(path)) => Control::File(path),
+            (None, None, None) => {
+                return Err(configuration_error(
+                    "either command_on and command_off, or control_file must be set",
+                ))
+            }
+            _ => {
+                return Err(configuration_error(
+                    "command_on and command_off must be set together, \
+                     and not along with a control file",
+                ))
+            }
+        };
+
         Ok(Toggle {
             text: ButtonWidget::new(config, &id).with_content(block_config.text),
-            command_on: block_config.command_on,
-            command_off: block_config.command_off,
-            command_state: block_config.command_state,
+            state_source,
+            control,
+            on_value: block_config.on_value,
+            off_value: block_config.off_value,
             icon_on: block_config.icon_on,
             icon_off: block_config.icon_off,
             id,
@@ -89,26 +181,71 @@ impl ConfigBlock for Toggle {
     }
 }
 
-impl Block for Toggle {
-    fn update(&mut self) -> Result<Option<Update>> {
-        let output = Command::new(env::var("SHELL").unwrap_or_else(|_| "sh".to_owned()))
-            .args(&["-c", &self.command_state])
-            .output()
-            .map(|o| String::from_utf8_lossy(&o.stdout).trim().to_owned())
-            .unwrap_or_else(|e| e.to_string());
-
-        self.text.set_icon(match output.trim_start() {
-            "" => {
-                self.toggled = false;
-                self.icon_off.as_str()
+impl Toggle {
+    /// Reads whether the toggle is on, `None` if the state file cannot be read.
+    fn read_state(&self) -> Option<bool> {
+        match self.state_source {
+            StateSource::Command(ref command) => {
+                let output = Command::new(env::var("SHELL").unwrap_or_else(|_| "sh".to_owned()))
+                    .args(&["-c", command])
+                    .output()
+                    .map(|o| String::from_utf8_lossy(&o.stdout).trim().to_owned())
+                    .unwrap_or_else(|e| e.to_string());
+                Some(!output.is_empty())
             }
-            _ => {
-                self.toggled = true;
-                self.icon_on.as_str()
+            StateSource::File(ref path) => match fs::read_to_string(path) {
+                Ok(content) => Some(content.trim() == self.on_value),
+                Err(e) => {
+                    eprintln!("toggle: failed to read {}: {}", path.display(), e);
+                    None
+                }
+            },
+        }
+    }
+
+    /// Switches the toggle, returns whether it succeeded.
+    fn switch(&self, on: bool) -> Result<bool> {
+        match self.control {
+            Control::Commands {
+                on: ref command_on,
+                off: ref command_off,
+            } => {
+                let cmd = if on { command_on } else { command_off };
+                let output = Command::new(env::var("SHELL").unwrap_or_else(|_| "sh".to_owned()))
+                    .args(&["-c", cmd])
+                    .output()
+                    .block_error("toggle", "failed to run toggle command")?;
+                Ok(output.status.success())
+            }
+            Control::File(ref path) => {
+                let value = if on { &self.on_value } else { &self.off_value };
+                match fs::write(path, value) {
+                    Ok(()) => Ok(true),
+                    Err(e) => {
+                        eprintln!("toggle: failed to write {}: {}", path.display(), e);
+                        Ok(false)
+                    }
+                }
             }
-        });
+        }
+    }
+}
 
-        self.text.set_state(State::Idle);
+impl Block for Toggle {
+    fn update(&mut self) -> Result<Option<Update>> {
+        match self.read_state() {
+            Some(toggled) => {
+                self.toggled = toggled;
+                self.text.set_icon(if toggled {
+                    self.icon_on.as_str()
+                } else {
+                    self.icon_off.as_str()
+                });
+                self.text.set_state(State::Idle);
+            }
+            // Keeps the last known state, marked as unknown
+            None => self.text.set_state(State::Critical),
+        }
 
         Ok(self.update_interval.map(|d| d.into()))
     }
@@ -120,18 +257,7 @@ impl Block for Toggle {
     fn click(&mut self, e: &I3BarEvent) -> Result<()> {
         if let Some(ref name) = e.name {
             if name.as_str() == self.id {
-                let cmd = if self.toggled {
-                    &self.command_off
-                } else {
-                    &self.command_on
-                };
-
-                let output = Command::new(env::var("SHELL").unwrap_or_else(|_| "sh".to_owned()))
-                    .args(&["-c", cmd])
-                    .output()
-                    .block_error("toggle", "failed to run toggle command")?;
-
-                if output.status.success() {
+                if self.switch(!self.toggled)? {
                     self.text.set_state(State::Idle);
                     self.toggled = !self.toggled;
                     self.text.set_icon(if self.toggled {
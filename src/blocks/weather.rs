@@ -1,5 +1,6 @@
 use crossbeam_channel::Sender;
 use serde_derive::Deserialize;
+use std::cmp::Ordering;
 use std::collections::HashMap;
 use std::env;
 use std::process::Command;
@@ -14,7 +15,7 @@ use crate::errors::*;
 use crate::input::{I3BarEvent, MouseButton};
 use crate::scheduler::Task;
 use crate::util::FormatTemplate;
-use crate::widget::I3BarWidget;
+use crate::widget::{I3BarWidget, State};
 use crate::widgets::button::ButtonWidget;
 
 const OPENWEATHERMAP_API_KEY_ENV: &str = "OPENWEATHERMAP_API_KEY";
@@ -62,6 +63,104 @@ pub struct Weather {
     service: WeatherService,
     update_interval: Duration,
     autolocate: bool,
+    oppressive_dew_point: Option<f64>,
+    oppressive: bool,
+    /// Show the temperature it feels like as `{temp}`
+    apparent: bool,
+    /// Whether the format shows the air quality, which takes another request
+    show_air_quality: bool,
+    /// Air quality index from 1 (good) to 5 (very poor)
+    air_quality: Option<u64>,
+}
+
+/// Pollutants of the OpenWeatherMap air pollution API, with the upper bound of
+/// their concentration in µg/m³ for the best index.
+const POLLUTANTS: &[(&str, &str, f64)] = &[
+    ("pm2_5", "PM2.5", 10.),
+    ("pm10", "PM10", 20.),
+    ("o3", "O3", 60.),
+    ("no2", "NO2", 40.),
+    ("so2", "SO2", 20.),
+    ("co", "CO", 4400.),
+];
+
+/// Fetches the air quality index and the dominant pollutant, i.e. the one
+/// furthest above its good level. Returns `None` when the service has no data
+/// for the location or cannot be reached, since the weather is still useful
+/// without it.
+fn fetch_air_quality(lat: f64, lon: f64, api_key: &str) -> Option<(u64, String)> {
+    let output = Command::new("curl")
+        .arg("-m")
+        .arg("3")
+        .arg("--silent")
+        .arg("--fail")
+        .arg(format!(
+            "https://api.openweathermap.org/data/2.5/air_pollution?lat={}&lon={}&appid={}",
+            lat, lon, api_key
+        ))
+        .output()
+        .ok()?;
+    let json: serde_json::value::Value = serde_json::from_slice(&output.stdout).ok()?;
+    let aqi = json.pointer("/list/0/main/aqi")?.as_u64()?;
+    let components = json.pointer("/list/0/components")?;
+    let pollutant = POLLUTANTS
+        .iter()
+        .filter_map(|(key, name, good)| Some((name, components.get(key)?.as_f64()? / good)))
+        .max_by(|a, b| a.1.partial_cmp(&b.1).unwrap_or(Ordering::Equal))
+        .map(|(name, _)| name.to_string())
+        .unwrap_or_default();
+    Some((aqi, pollutant))
+}
+
+/// Computes the dew point in degrees Celsius with the Magnus formula.
+fn dew_point(temp_celsius: f64, relative_humidity: f64) -> f64 {
+    const A: f64 = 17.62;
+    const B: f64 = 243.12;
+    // The logarithm is undefined for completely dry air
+    let relative_humidity = relative_humidity.max(1.0);
+    let gamma = (relative_humidity / 100.0).ln() + A * temp_celsius / (B + temp_celsius);
+    B * gamma / (A - gamma)
+}
+
+/// Computes how warm it feels in degrees Fahrenheit with the formulas of the US
+/// National Weather Service: the wind chill in the cold, the heat index in the
+/// heat and the plain temperature in between.
+fn feels_like(temp_fahrenheit: f64, relative_humidity: f64, wind_mph: f64) -> f64 {
+    let (t, rh) = (temp_fahrenheit, relative_humidity);
+    if t <= 50.0 && wind_mph >= 3.0 {
+        let v = wind_mph.powf(0.16);
+        return 35.74 + 0.6215 * t - 35.75 * v + 0.4275 * t * v;
+    }
+
+    // The simple formula is accurate enough below about 80°F
+    let simple = 0.5 * (t + 61.0 + (t - 68.0) * 1.2 + rh * 0.094);
+    if (simple + t) / 2.0 < 80.0 {
+        return if t <= 50.0 { t } else { simple };
+    }
+    let mut heat_index = -42.379 + 2.049_015_23 * t + 10.143_331_27 * rh
+        - 0.224_755_41 * t * rh
+        - 0.006_837_83 * t * t
+        - 0.054_817_17 * rh * rh
+        + 0.001_228_74 * t * t * rh
+        + 0.000_852_82 * t * rh * rh
+        - 0.000_001_99 * t * t * rh * rh;
+    if rh < 13.0 && (80.0..=112.0).contains(&t) {
+        heat_index -= (13.0 - rh) / 4.0 * ((17.0 - (t - 95.0).abs()) / 17.0).sqrt();
+    } else if rh > 85.0 && (80.0..=87.0).contains(&t) {
+        heat_index += (rh - 85.0) / 10.0 * (87.0 - t) / 5.0;
+    }
+    heat_index
+}
+
+/// Classifies how the air feels from its dew point in degrees Celsius.
+fn comfort(dew_point_celsius: f64) -> &'static str {
+    if dew_point_celsius < 10.0 {
+        "dry"
+    } else if dew_point_celsius < 16.0 {
+        "comfortable"
+    } else {
+        "humid"
+    }
 }
 
 fn malformed_json_error() -> Error {
@@ -204,6 +303,26 @@ impl Weather {
                     .map_or(Some(None), |v| v.as_f64().map(Some)) // provide default value None
                     .ok_or_else(malformed_json_error)?; // error when conversion to f64 fails
 
+                self.air_quality = None;
+                let air_quality = if self.show_air_quality {
+                    match (
+                        json.pointer("/coord/lat").and_then(|v| v.as_f64()),
+                        json.pointer("/coord/lon").and_then(|v| v.as_f64()),
+                    ) {
+                        (Some(lat), Some(lon)) => fetch_air_quality(lat, lon, api_key),
+                        _ => None,
+                    }
+                } else {
+                    None
+                };
+                let (aqi, pollutant) = match air_quality {
+                    Some((aqi, pollutant)) => {
+                        self.air_quality = Some(aqi);
+                        (aqi.to_string(), pollutant)
+                    }
+                    None => (String::new(), String::new()),
+                };
+
                 let raw_location = json
                     .pointer("/name")
                     .and_then(|v| v.as_str())
@@ -243,6 +362,26 @@ impl Weather {
                     1.8 * metric_apparent_temp + 32.0
                 };
 
+                let feels_like = if metric {
+                    let temp_fahrenheit = raw_temp * 1.8 + 32.0;
+                    let wind_mph = raw_wind_speed * 2.237;
+                    (feels_like(temp_fahrenheit, raw_humidity, wind_mph) - 32.0) / 1.8
+                } else {
+                    feels_like(raw_temp, raw_humidity, raw_wind_speed)
+                };
+                let temp = if self.apparent { feels_like } else { raw_temp };
+
+                let dew_point_celsius = dew_point(temp_celsius, raw_humidity);
+                let dew_point = if metric {
+                    dew_point_celsius
+                } else {
+                    1.8 * dew_point_celsius + 32.0
+                };
+                self.oppressive = match self.oppressive_dew_point {
+                    Some(oppressive) => dew_point >= oppressive,
+                    None => false,
+                };
+
                 // Convert wind direction in azimuth degrees to abbreviation names
                 fn convert_wind_direction(direction_opt: Option<f64>) -> String {
                     match direction_opt {
@@ -270,11 +409,16 @@ impl Weather {
                 });
 
                 self.weather_keys = map_to_owned!("{weather}" => raw_weather,
-                                  "{temp}" => format!("{:.0}", raw_temp),
+                                  "{temp}" => format!("{:.0}", temp),
+                                  "{feels_like}" => format!("{:.0}", feels_like),
                                   "{humidity}" => format!("{:.0}", raw_humidity),
                                   "{apparent}" => format!("{:.0}",apparent_temp),
+                                  "{dew_point}" => format!("{:.0}", dew_point),
+                                  "{comfort}" => comfort(dew_point_celsius).to_string(),
                                   "{wind}" => format!("{:.1}", raw_wind_speed),
                                   "{direction}" => convert_wind_direction(raw_wind_direction),
+                                  "{aqi}" => aqi,
+                                  "{pollutant}" => pollutant,
                                   "{location}" => raw_location);
                 Ok(())
             }
@@ -308,6 +452,12 @@ pub struct WeatherConfig {
     pub service: WeatherService,
     #[serde(default = "WeatherConfig::default_autolocate")]
     pub autolocate: bool,
+    /// Dew point, in the configured units, from which the block is set to warning
+    #[serde(default = "WeatherConfig::default_oppressive_dew_point")]
+    pub oppressive_dew_point: Option<f64>,
+    /// Show the wind chill or heat index as `{temp}` instead of the measured temperature
+    #[serde(default = "WeatherConfig::default_apparent")]
+    pub apparent: bool,
 }
 
 impl WeatherConfig {
@@ -322,6 +472,14 @@ impl WeatherConfig {
     fn default_autolocate() -> bool {
         false
     }
+
+    fn default_oppressive_dew_point() -> Option<f64> {
+        None
+    }
+
+    fn default_apparent() -> bool {
+        false
+    }
 }
 
 impl ConfigBlock for Weather {
@@ -336,11 +494,17 @@ impl ConfigBlock for Weather {
         Ok(Weather {
             id: id.clone(),
             weather: ButtonWidget::new(config, &id),
-            format: block_config.format,
             weather_keys: HashMap::new(),
             service: block_config.service,
             update_interval: block_config.interval,
             autolocate: block_config.autolocate,
+            show_air_quality: block_config.format.contains("{aqi}")
+                || block_config.format.contains("{pollutant}"),
+            format: block_config.format,
+            oppressive_dew_point: block_config.oppressive_dew_point,
+            oppressive: false,
+            apparent: block_config.apparent,
+            air_quality: None,
         })
     }
 }
@@ -355,6 +519,13 @@ impl Block for Weather {
         } else {
             let fmt = FormatTemplate::from_string(&self.format)?;
             self.weather.set_text(fmt.render(&self.weather_keys));
+            self.weather.set_state(match self.air_quality {
+                Some(4..=5) => State::Critical,
+                Some(3) => State::Warning,
+                _ if self.oppressive => State::Warning,
+                Some(1..=2) => State::Good,
+                _ => State::Idle,
+            });
         }
         Ok(Some(self.update_interval.into()))
     }
@@ -376,3 +547,19 @@ impl Block for Weather {
         &self.id
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use crate::blocks::weather::feels_like;
+
+    #[test]
+    fn test_feels_like() {
+        // Values of the wind chill and heat index charts of the NWS
+        assert_eq!(feels_like(0.0, 50.0, 15.0).round(), -19.0);
+        assert_eq!(feels_like(30.0, 50.0, 10.0).round(), 21.0);
+        assert_eq!(feels_like(90.0, 60.0, 5.0).round(), 100.0);
+        assert_eq!(feels_like(80.0, 40.0, 5.0).round(), 80.0);
+        // No wind chill without wind
+        assert_eq!(feels_like(30.0, 50.0, 0.0), 30.0);
+    }
+}
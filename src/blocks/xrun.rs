@@ -0,0 +1,291 @@
+use std::process::Command;
+use std::time::Duration;
+
+use crossbeam_channel::Sender;
+use serde_derive::Deserialize;
+use uuid::Uuid;
+
+use crate::blocks::sound::{pactl_default_device, DeviceKind};
+use crate::blocks::{Block, ConfigBlock, Update};
+use crate::config::Config;
+use crate::de::deserialize_duration;
+use crate::errors::*;
+use crate::scheduler::Task;
+use crate::util::FormatTemplate;
+use crate::widget::{I3BarWidget, State};
+use crate::widgets::text::TextWidget;
+
+#[derive(Deserialize, Debug, Copy, Clone, PartialEq, Eq, Default)]
+#[serde(rename_all = "lowercase")]
+pub enum XrunBackend {
+    /// Reads the latency and the xruns of the node with `pw-top`.
+    #[default]
+    Pipewire,
+    /// Reads the latency with `pactl`. PulseAudio does not count underruns.
+    Pulseaudio,
+}
+
+/// A sample of the statistics of an audio device.
+#[derive(Debug, Default, PartialEq)]
+struct Stats {
+    /// Latency in milliseconds
+    latency: Option<f64>,
+    /// Number of xruns since the device was started
+    xruns: Option<u64>,
+}
+
+/// Reads the statistics of the node `name` from the output of
+/// `pw-top --batch-mode`, taking the last of the printed iterations.
+fn parse_pw_top(output: &str, name: &str) -> Option<Stats> {
+    // Columns: S ID QUANT RATE WAIT BUSY W/Q B/Q ERR FORMAT NAME, where the
+    // format may contain spaces but the node name does not
+    output.lines().rev().find_map(|line| {
+        let fields: Vec<&str> = line.split_whitespace().collect();
+        if fields.len() < 10 || fields.last() != Some(&name) {
+            return None;
+        }
+        let quantum: f64 = fields[2].parse().ok()?;
+        let rate: f64 = fields[3].parse().ok()?;
+        Some(Stats {
+            latency: if quantum > 0. && rate > 0. {
+                Some(quantum / rate * 1000.)
+            } else {
+                None
+            },
+            xruns: fields[8].parse().ok(),
+        })
+    })
+}
+
+/// Reads the latency of the sink or source `name` from the output of
+/// `pactl list sinks` (or `sources`), e.g. "Latency: 21208 usec, configured 25000 usec".
+fn parse_pactl_latency(output: &str, name: &str) -> Option<Stats> {
+    output
+        .lines()
+        .map(str::trim)
+        .skip_while(|line| line.strip_prefix("Name:").map(str::trim) != Some(name))
+        .skip(1)
+        .take_while(|line| !line.starts_with("Name:"))
+        .find_map(|line| line.strip_prefix("Latency:"))
+        .map(|latency| Stats {
+            latency: latency
+                .split_whitespace()
+                .next()
+                .and_then(|usec| usec.parse::<f64>().ok())
+                .map(|usec| usec / 1000.),
+            xruns: None,
+        })
+}
+
+fn run_command(command: &str, args: &[&str]) -> Result<String> {
+    let output = Command::new(command)
+        .args(args)
+        .env("LC_ALL", "C")
+        .output()
+        .block_error("xrun", &format!("failed to run {}", command))?;
+    if !output.status.success() {
+        return Err(BlockError(
+            "xrun".to_string(),
+            format!("{} exited with {}", command, output.status),
+        ));
+    }
+    Ok(String::from_utf8_lossy(&output.stdout).into_owned())
+}
+
+pub struct Xrun {
+    id: String,
+    text: TextWidget,
+    backend: XrunBackend,
+    device_kind: DeviceKind,
+    name: Option<String>,
+    format: FormatTemplate,
+    update_interval: Duration,
+    /// Xruns since the block started
+    xruns: u64,
+    /// Last reading of the counter of the device
+    last_xruns: Option<u64>,
+}
+
+#[derive(Deserialize, Debug, Default, Clone)]
+#[serde(deny_unknown_fields)]
+pub struct XrunConfig {
+    /// Update interval in seconds
+    #[serde(
+        default = "XrunConfig::default_interval",
+        deserialize_with = "deserialize_duration"
+    )]
+    pub interval: Duration,
+
+    /// Sound server to query
+    #[serde(default)]
+    pub backend: XrunBackend,
+
+    /// Whether to watch the default sink or the default source
+    #[serde(default)]
+    pub device_kind: DeviceKind,
+
+    /// Node name of the device, the default sink or source by default
+    #[serde(default)]
+    pub name: Option<String>,
+
+    /// Format override
+    #[serde(default = "XrunConfig::default_format")]
+    pub format: String,
+}
+
+impl XrunConfig {
+    fn default_interval() -> Duration {
+        Duration::from_secs(5)
+    }
+
+    fn default_format() -> String {
+        "{latency} {xruns}".to_owned()
+    }
+}
+
+impl ConfigBlock for Xrun {
+    type Config = XrunConfig;
+
+    fn new(
+        block_config: Self::Config,
+        config: Config,
+        _tx_update_request: Sender<Task>,
+    ) -> Result<Self> {
+        Ok(Xrun {
+            id: Uuid::new_v4().to_simple().to_string(),
+            text: TextWidget::new(config).with_icon("volume_full"),
+            backend: block_config.backend,
+            device_kind: block_config.device_kind,
+            name: block_config.name,
+            format: FormatTemplate::from_string(&block_config.format)
+                .block_error("xrun", "Invalid format specified")?,
+            update_interval: block_config.interval,
+            xruns: 0,
+            last_xruns: None,
+        })
+    }
+}
+
+impl Xrun {
+    fn stats(&self) -> Result<Option<Stats>> {
+        // The default device is looked up on every update since it can change
+        let name = match self.name {
+            Some(ref name) => name.clone(),
+            None => match pactl_default_device(self.device_kind)? {
+                Some(name) => name,
+                None => return Ok(None),
+            },
+        };
+        Ok(match self.backend {
+            XrunBackend::Pipewire => {
+                // The first iteration has no timings yet
+                let output = run_command("pw-top", &["--batch-mode", "--iterations", "2"])?;
+                parse_pw_top(&output, &name)
+            }
+            XrunBackend::Pulseaudio => {
+                let list_kind = match self.device_kind {
+                    DeviceKind::Sink => "sinks",
+                    DeviceKind::Source => "sources",
+                };
+                let output = run_command("pactl", &["list", list_kind])?;
+                parse_pactl_latency(&output, &name)
+            }
+        })
+    }
+}
+
+impl Block for Xrun {
+    fn update(&mut self) -> Result<Option<Update>> {
+        let stats = self.stats()?.unwrap_or_default();
+
+        let new_xruns = match (self.last_xruns, stats.xruns) {
+            (Some(last), Some(xruns)) if xruns >= last => xruns - last,
+            // The counter starts over when the device is restarted
+            (Some(_), Some(xruns)) => xruns,
+            _ => 0,
+        };
+        self.xruns += new_xruns;
+        self.last_xruns = stats.xruns;
+
+        let values = map!(
+            "{latency}" => match stats.latency {
+                Some(latency) => format!("{:.1}ms", latency),
+                None => "N/A".to_string(),
+            },
+            "{xruns}" => match stats.xruns {
+                Some(_) => self.xruns.to_string(),
+                None => "N/A".to_string(),
+            }
+        );
+        self.text.set_state(if new_xruns > 0 {
+            State::Warning
+        } else {
+            State::Idle
+        });
+        self.text.set_text(self.format.render_static_str(&values)?);
+
+        Ok(Some(self.update_interval.into()))
+    }
+
+    fn view(&self) -> Vec<&dyn I3BarWidget> {
+        vec![&self.text]
+    }
+
+    fn set_format(&mut self, format: &str) -> Result<()> {
+        self.format =
+            FormatTemplate::from_string(format).block_error("xrun", "Invalid format specified")?;
+        Ok(())
+    }
+
+    fn id(&self) -> &str {
+        &self.id
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::blocks::xrun::{parse_pactl_latency, parse_pw_top, Stats};
+
+    #[test]
+    fn test_parse_pw_top() {
+        let output = "\
+S   ID  QUANT   RATE    WAIT    BUSY   W/Q   B/Q  ERR FORMAT           NAME
+R   30      0      0    ---     ---   ---   ---     0                  alsa_output.pci-0000_00_1f.3.analog-stereo
+S   ID  QUANT   RATE    WAIT    BUSY   W/Q   B/Q  ERR FORMAT           NAME
+R   30   1024  48000  69.8us   5.2us  0.00  0.00    3    S32LE 2 48000 alsa_output.pci-0000_00_1f.3.analog-stereo
+R   65   1024  48000  12.1us   8.3us  0.00  0.00    0    F32LE 2 48000  + Firefox
+";
+        assert_eq!(
+            parse_pw_top(output, "alsa_output.pci-0000_00_1f.3.analog-stereo"),
+            Some(Stats {
+                latency: Some(1024. / 48000. * 1000.),
+                xruns: Some(3),
+            })
+        );
+        assert_eq!(
+            parse_pw_top(output, "Firefox").and_then(|stats| stats.xruns),
+            Some(0)
+        );
+        assert_eq!(parse_pw_top(output, "missing"), None);
+    }
+
+    #[test]
+    fn test_parse_pactl_latency() {
+        let output = "\
+Sink #0
+\tName: alsa_output.pci-0000_00_1f.3.analog-stereo
+\tLatency: 21208 usec, configured 25000 usec
+Sink #1
+\tName: bluez_sink.00_11_22_33_44_55.a2dp_sink
+\tLatency: 180000 usec, configured 180000 usec
+";
+        assert_eq!(
+            parse_pactl_latency(output, "bluez_sink.00_11_22_33_44_55.a2dp_sink"),
+            Some(Stats {
+                latency: Some(180.),
+                xruns: None,
+            })
+        );
+        assert_eq!(parse_pactl_latency(output, "missing"), None);
+    }
+}
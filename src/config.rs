@@ -1,17 +1,24 @@
 use std::collections::HashMap as Map;
 use std::marker::PhantomData;
 use std::ops::Deref;
-use std::path::Path;
+use std::path::{Path, PathBuf};
+use std::process::Command;
 use std::str::FromStr;
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
 
+use regex::Regex;
 use serde::de::{Deserialize, Deserializer, Error};
 use serde_derive::Deserialize;
 use toml::value;
 
 use crate::de::*;
+use crate::errors::ResultExtInternal;
 use crate::input::MouseButton;
+use crate::subprocess::{output_with_input, spawn_child_async};
 use crate::themes::{Theme, ThemeConfig};
-use crate::util::deserialize_file;
+use crate::util::{deserialize_file, FormatTemplate, MarkupEscape};
+use crate::widget::State;
 use crate::{errors, icons};
 
 #[derive(Deserialize, Debug, Clone)]
@@ -27,8 +34,17 @@ pub struct Config {
     /// motion which is undesired for sliders. Use "natural" to invert this.
     #[serde(default = "Scrolling::default", rename = "scrolling")]
     pub scrolling: Scrolling,
+    /// Lay out the blocks for right-to-left scripts, can be overridden per block.
+    #[serde(default)]
+    pub rtl: bool,
+    /// How the text of the blocks is escaped, can be overridden per block.
+    #[serde(default)]
+    pub markup_escape: MarkupEscape,
     #[serde(rename = "block", deserialize_with = "deserialize_blocks")]
     pub blocks: Vec<(String, value::Value)>,
+    /// Options shared by all blocks, as set for the block this config is passed to.
+    #[serde(skip)]
+    pub block: CommonBlockConfig,
 }
 
 impl Default for Config {
@@ -37,7 +53,10 @@ impl Default for Config {
             icons: icons::default(),
             theme: Theme::default(),
             scrolling: Scrolling::default(),
+            rtl: false,
+            markup_escape: MarkupEscape::default(),
             blocks: Vec::new(),
+            block: CommonBlockConfig::default(),
         }
     }
 }
@@ -51,11 +70,358 @@ impl From<LegacyConfig> for Config {
                 .and_then(|s| Theme::from_name(s.as_str()))
                 .unwrap_or_default(),
             scrolling: legacy_config.scrolling,
+            rtl: false,
+            markup_escape: MarkupEscape::default(),
             blocks: legacy_config.blocks,
+            block: CommonBlockConfig::default(),
+        }
+    }
+}
+
+/// Options that every block accepts next to its own.
+#[derive(Deserialize, Debug, Default, Clone)]
+#[serde(deny_unknown_fields)]
+pub struct CommonBlockConfig {
+    /// Format of the block's text in `Warning` state, `{text}` is the original text.
+    #[serde(default, deserialize_with = "deserialize_opt_format")]
+    pub warning_format: Option<FormatTemplate>,
+
+    /// Format of the block's text in `Critical` state, `{text}` is the original text.
+    #[serde(default, deserialize_with = "deserialize_opt_format")]
+    pub critical_format: Option<FormatTemplate>,
+
+    /// Transformation applied to the block's text before it is displayed.
+    #[serde(default, deserialize_with = "deserialize_transform")]
+    pub transform: Option<Transform>,
+
+    /// Shell command that receives the block's text on stdin and prints the
+    /// text to display instead. It runs on the main thread for every text not
+    /// seen yet, which stalls the whole bar until it exits.
+    #[serde(default)]
+    pub transform_cmd: Option<String>,
+
+    /// Outputs of `transform_cmd` by input, shared by all widgets of the block.
+    #[serde(skip)]
+    transform_cache: Arc<Mutex<Map<String, String>>>,
+
+    /// Sends a desktop notification when the displayed text matches this regex.
+    #[serde(default, deserialize_with = "deserialize_opt_regex")]
+    pub notify_match: Option<Regex>,
+
+    /// Shell command run in addition to the notification when the text
+    /// matches, with the text as its first argument.
+    #[serde(default)]
+    pub notify_command: Option<String>,
+
+    /// Minimum time between two notifications for matching text.
+    #[serde(
+        default = "CommonBlockConfig::default_notify_debounce",
+        deserialize_with = "deserialize_duration"
+    )]
+    pub notify_debounce: Duration,
+
+    /// Time of the last notification, shared by all widgets of the block.
+    #[serde(skip)]
+    last_notify: Arc<Mutex<Option<Instant>>>,
+
+    /// Formats the block switches between on left click, see `Block::set_format`.
+    #[serde(default)]
+    pub formats: Option<Vec<String>>,
+
+    /// File to remember the selected entry of `formats` in.
+    #[serde(default)]
+    pub formats_file: Option<PathBuf>,
+
+    /// Keep updating the block while updates are paused with `--pause-when-hidden`.
+    /// Read by the scheduler rather than by the block itself.
+    #[serde(default)]
+    pub always_update: bool,
+
+    /// Consecutive blocks with the same group are drawn as one pill.
+    /// Read by `util::print_blocks` rather than by the block itself.
+    #[serde(default)]
+    pub group: Option<String>,
+
+    /// Color of the separator in front of the block, in place of the theme's.
+    #[serde(default)]
+    pub separator_color: Option<String>,
+
+    /// Overrides the global `rtl` for this block.
+    #[serde(default)]
+    pub rtl: Option<bool>,
+
+    /// Overrides the global `markup_escape` for this block.
+    #[serde(default)]
+    pub markup_escape: Option<MarkupEscape>,
+}
+
+/// Limit of cached `transform_cmd` outputs per block, the cache is cleared
+/// when it is reached.
+const TRANSFORM_CACHE_SIZE: usize = 64;
+
+/// Time `transform_cmd` may take before it is killed and the text is shown
+/// untransformed, since it runs while the bar renders.
+const TRANSFORM_CMD_TIMEOUT: Duration = Duration::from_secs(1);
+
+impl CommonBlockConfig {
+    const KEYS: &'static [&'static str] = &[
+        "warning_format",
+        "critical_format",
+        "transform",
+        "transform_cmd",
+        "notify_match",
+        "notify_command",
+        "notify_debounce",
+        "always_update",
+        "formats",
+        "formats_file",
+        "group",
+        "separator_color",
+        "rtl",
+        "markup_escape",
+    ];
+
+    fn default_notify_debounce() -> Duration {
+        Duration::from_secs(60)
+    }
+
+    /// Takes the common options out of a block's configuration, leaving only
+    /// the options specific to the block.
+    pub fn extract(block_config: &mut value::Value) -> errors::Result<Self> {
+        let mut common = value::Table::new();
+        if let value::Value::Table(ref mut table) = block_config {
+            for key in Self::KEYS {
+                if let Some(value) = table.remove(*key) {
+                    common.insert((*key).to_string(), value);
+                }
+            }
+        }
+
+        let common = CommonBlockConfig::deserialize(value::Value::Table(common))
+            .configuration_error("Failed to deserialize common block config.")?;
+        for format in common.warning_format.iter().chain(&common.critical_format) {
+            format.render_static_str(&map!("{text}" => ""))?;
+        }
+        if let Some(ref formats) = common.formats {
+            if formats.is_empty() {
+                return Err(errors::Error::ConfigurationError(
+                    "`formats` must not be empty".to_string(),
+                    (String::new(), String::new()),
+                ));
+            }
+        }
+
+        Ok(common)
+    }
+
+    /// Applies the transformations and the format configured for `state` to
+    /// the text a block produced.
+    pub fn format_text(&self, text: &str, state: State) -> String {
+        let mut text = match self.transform {
+            Some(ref transform) => transform.apply(text),
+            None => text.to_string(),
+        };
+        if let Some(ref cmd) = self.transform_cmd {
+            text = self.run_transform_cmd(cmd, text);
+        }
+        let text = text.as_str();
+
+        let format = match state {
+            State::Warning => self.warning_format.as_ref(),
+            State::Critical => self.critical_format.as_ref(),
+            _ => None,
+        };
+
+        match format {
+            // The values in `text` were escaped when the block rendered it
+            Some(format) => MarkupEscape::None
+                .scope(|| format.render_static_str(&map!("{text}" => text)))
+                .unwrap_or_else(|_| text.to_string()),
+            None => text.to_string(),
+        }
+    }
+
+    /// Fires the notification and `notify_command` when `text` matches
+    /// `notify_match`, unless they fired less than `notify_debounce` ago.
+    /// Returns whether they fired.
+    pub fn check_notify(&self, text: &str) -> bool {
+        match self.notify_match {
+            Some(ref regex) if regex.is_match(text) => {}
+            _ => return false,
+        }
+        if !self.notify(text) {
+            return false;
+        }
+        if let Some(ref cmd) = self.notify_command {
+            // The text is passed as the first positional parameter, `$1`
+            if let Err(e) = spawn_child_async("sh", &["-c", cmd, "i3status-rust", text]) {
+                eprintln!("failed to run notify_command '{}': {}", cmd, e);
+            }
+        }
+        true
+    }
+
+    /// Sends a desktop notification with `text`, unless the block sent one
+    /// less than `notify_debounce` ago. Returns whether it was sent.
+    pub fn notify(&self, text: &str) -> bool {
+        let mut last_notify = self.last_notify.lock().unwrap();
+        if let Some(last) = *last_notify {
+            if last.elapsed() < self.notify_debounce {
+                return false;
+            }
+        }
+        *last_notify = Some(Instant::now());
+
+        if let Err(e) = spawn_child_async("notify-send", &["i3status-rust", text]) {
+            eprintln!("failed to send notification: {}", e);
+        }
+        true
+    }
+
+    fn run_transform_cmd(&self, cmd: &str, text: String) -> String {
+        let mut cache = self.transform_cache.lock().unwrap();
+        if let Some(output) = cache.get(&text) {
+            return output.clone();
+        }
+
+        let output = output_with_input(
+            Command::new("sh").arg("-c").arg(cmd),
+            text.clone().into_bytes(),
+            TRANSFORM_CMD_TIMEOUT,
+        );
+        let output = match output {
+            Ok(Some(output)) => String::from_utf8_lossy(&output.stdout)
+                .trim_end_matches('\n')
+                .to_string(),
+            // Cached too, so that a hung command only stalls the bar once per text
+            Ok(None) => {
+                eprintln!(
+                    "transform_cmd '{}' timed out after {:?}",
+                    cmd, TRANSFORM_CMD_TIMEOUT
+                );
+                text.clone()
+            }
+            Err(e) => {
+                eprintln!("failed to run transform_cmd '{}': {}", cmd, e);
+                return text;
+            }
+        };
+
+        if cache.len() >= TRANSFORM_CACHE_SIZE {
+            cache.clear();
+        }
+        cache.insert(text, output.clone());
+        output
+    }
+}
+
+/// A transformation of a block's text, see `CommonBlockConfig::transform`.
+#[derive(Debug, Clone)]
+pub enum Transform {
+    Uppercase,
+    Lowercase,
+    /// A sed-like substitution, `s/regex/replacement/` or `s/regex/replacement/g`.
+    Replace {
+        regex: Regex,
+        replacement: String,
+        global: bool,
+    },
+}
+
+impl Transform {
+    pub fn apply(&self, text: &str) -> String {
+        match self {
+            Transform::Uppercase => text.to_uppercase(),
+            Transform::Lowercase => text.to_lowercase(),
+            Transform::Replace {
+                regex,
+                replacement,
+                global: true,
+            } => regex.replace_all(text, replacement.as_str()).into_owned(),
+            Transform::Replace {
+                regex,
+                replacement,
+                global: false,
+            } => regex.replace(text, replacement.as_str()).into_owned(),
+        }
+    }
+}
+
+impl FromStr for Transform {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "uppercase" => return Ok(Transform::Uppercase),
+            "lowercase" => return Ok(Transform::Lowercase),
+            _ => {}
+        }
+
+        let mut chars = s.chars();
+        if chars.next() != Some('s') {
+            return Err(format!("unknown transform '{}'", s));
+        }
+        let delimiter = chars
+            .next()
+            .ok_or_else(|| format!("unknown transform '{}'", s))?;
+
+        // Split into pattern, replacement and flags, honoring escaped delimiters
+        let mut parts = vec![String::new()];
+        let mut escaped = false;
+        for c in chars {
+            let part = parts.last_mut().unwrap();
+            if escaped {
+                if c != delimiter {
+                    part.push('\\');
+                }
+                part.push(c);
+                escaped = false;
+            } else if c == '\\' {
+                escaped = true;
+            } else if c == delimiter {
+                parts.push(String::new());
+            } else {
+                part.push(c);
+            }
+        }
+        if parts.len() != 3 {
+            return Err(format!(
+                "substitution '{}' must look like s/regex/replacement/",
+                s
+            ));
         }
+
+        let global = match parts[2].as_str() {
+            "" => false,
+            "g" => true,
+            flags => return Err(format!("unknown substitution flags '{}'", flags)),
+        };
+        let regex = Regex::new(&parts[0]).map_err(|e| e.to_string())?;
+
+        Ok(Transform::Replace {
+            regex,
+            replacement: parts.remove(1),
+            global,
+        })
     }
 }
 
+fn deserialize_opt_regex<'de, D>(deserializer: D) -> Result<Option<Regex>, D::Error>
+where
+    D: Deserializer<'de>,
+{
+    let regex = String::deserialize(deserializer)?;
+    Regex::new(&regex).map(Some).map_err(D::Error::custom)
+}
+
+fn deserialize_transform<'de, D>(deserializer: D) -> Result<Option<Transform>, D::Error>
+where
+    D: Deserializer<'de>,
+{
+    let transform = String::deserialize(deserializer)?;
+    transform.parse().map(Some).map_err(D::Error::custom)
+}
+
 #[derive(Deserialize, Debug, Clone)]
 pub struct LegacyConfig {
     #[serde(default = "icons::default", deserialize_with = "deserialize_icons")]
@@ -163,7 +529,8 @@ pub fn load_config(config_path: &Path) -> errors::Result<Config> {
 }
 #[cfg(test)]
 mod tests {
-    use crate::config::load_config;
+    use crate::config::{load_config, CommonBlockConfig, Transform};
+    use crate::widget::State;
     use assert_fs::prelude::{FileWriteStr, PathChild};
     use assert_fs::TempDir;
 
@@ -209,4 +576,75 @@ mod tests {
         let config = load_config(config_file_path.path());
         config.unwrap();
     }
+
+    #[test]
+    fn test_common_block_config() {
+        let mut block_config: toml::value::Value = toml::from_str(concat!(
+            "interval = 1\n",
+            "warning_format = \"LOW! {text}\"\n",
+        ))
+        .unwrap();
+        let common = CommonBlockConfig::extract(&mut block_config).unwrap();
+        assert!(block_config.get("warning_format").is_none());
+        assert!(block_config.get("interval").is_some());
+        assert_eq!(common.format_text("5%", State::Warning), "LOW! 5%");
+        assert_eq!(common.format_text("5%", State::Critical), "5%");
+        assert_eq!(common.format_text("5%", State::Idle), "5%");
+
+        let mut block_config: toml::value::Value =
+            toml::from_str("critical_format = \"{percentage}\"").unwrap();
+        assert!(CommonBlockConfig::extract(&mut block_config).is_err());
+
+        let mut block_config: toml::value::Value =
+            toml::from_str("notify_match = \"ERROR(\"").unwrap();
+        assert!(CommonBlockConfig::extract(&mut block_config).is_err());
+    }
+
+    #[test]
+    fn test_transform() {
+        let upper: Transform = "uppercase".parse().unwrap();
+        assert_eq!(upper.apply("wlan0"), "WLAN0");
+        let first: Transform = "s/o/0/".parse().unwrap();
+        assert_eq!(first.apply("foo"), "f0o");
+        let global: Transform = "s/o/0/g".parse().unwrap();
+        assert_eq!(global.apply("foo"), "f00");
+        let escaped: Transform = r"s/a\/b/$0 c/".parse().unwrap();
+        assert_eq!(escaped.apply("a/b"), "a/b c");
+        let groups: Transform = "s|(\\d+)%|$1 percent|".parse().unwrap();
+        assert_eq!(groups.apply("42%"), "42 percent");
+
+        assert!("s/foo/bar".parse::<Transform>().is_err());
+        assert!("s/foo/bar/x".parse::<Transform>().is_err());
+        assert!("s/(/bar/".parse::<Transform>().is_err());
+        assert!("reverse".parse::<Transform>().is_err());
+    }
+
+    #[test]
+    fn test_transform_cmd() {
+        let mut block_config: toml::value::Value =
+            toml::from_str("transform_cmd = \"tr a-z A-Z\"").unwrap();
+        let common = CommonBlockConfig::extract(&mut block_config).unwrap();
+        assert_eq!(common.format_text("load", State::Idle), "LOAD");
+        assert_eq!(common.transform_cache.lock().unwrap().len(), 1);
+        assert_eq!(common.format_text("load", State::Idle), "LOAD");
+
+        let mut block_config: toml::value::Value =
+            toml::from_str("transform_cmd = \"sleep 5\"").unwrap();
+        let common = CommonBlockConfig::extract(&mut block_config).unwrap();
+        assert_eq!(common.format_text("load", State::Idle), "load");
+    }
+
+    #[test]
+    fn test_check_notify() {
+        let mut block_config: toml::value::Value =
+            toml::from_str("notify_match = \"ERROR\"\nnotify_debounce = 60").unwrap();
+        let common = CommonBlockConfig::extract(&mut block_config).unwrap();
+        assert_eq!(common.format_text("ERROR", State::Idle), "ERROR");
+        assert!(common.last_notify.lock().unwrap().is_none());
+
+        assert!(!common.check_notify("ok"));
+        assert!(common.check_notify("ERROR"));
+        assert!(!common.check_notify("ERROR"));
+        assert!(!common.check_notify("another ERROR"));
+    }
 }
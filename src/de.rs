@@ -6,6 +6,7 @@ use std::str::FromStr;
 use std::time::Duration;
 
 use crate::blocks::Update;
+use crate::util::{FormatTemplate, Threshold};
 use chrono::{DateTime, Local};
 use serde::de::{self, Deserialize, DeserializeSeed, Deserializer};
 use toml::{self, value};
@@ -97,6 +98,54 @@ where
     deserialize_duration(deserializer).map(Some)
 }
 
+pub fn deserialize_opt_format<'de, D>(deserializer: D) -> Result<Option<FormatTemplate>, D::Error>
+where
+    D: Deserializer<'de>,
+{
+    let format = String::deserialize(deserializer)?;
+    FormatTemplate::from_string(&format)
+        .map(Some)
+        .map_err(|e| de::Error::custom(e.to_string()))
+}
+
+pub fn deserialize_threshold<'de, D>(deserializer: D) -> Result<Threshold, D::Error>
+where
+    D: Deserializer<'de>,
+{
+    struct ThresholdWrapper;
+
+    impl<'de> de::Visitor<'de> for ThresholdWrapper {
+        type Value = Threshold;
+
+        fn expecting(&self, formatter: &mut fmt::Formatter) -> fmt::Result {
+            formatter.write_str(r#"i64, f64 or a string like "10 GiB" or "5%""#)
+        }
+
+        fn visit_i64<E>(self, value: i64) -> Result<Self::Value, E>
+        where
+            E: de::Error,
+        {
+            Ok(Threshold::Unitless(value as f64))
+        }
+
+        fn visit_f64<E>(self, value: f64) -> Result<Self::Value, E>
+        where
+            E: de::Error,
+        {
+            Ok(Threshold::Unitless(value))
+        }
+
+        fn visit_str<E>(self, value: &str) -> Result<Self::Value, E>
+        where
+            E: de::Error,
+        {
+            value.parse().map_err(de::Error::custom)
+        }
+    }
+
+    deserializer.deserialize_any(ThresholdWrapper)
+}
+
 pub struct MapType<T, V>(pub PhantomData<T>, pub PhantomData<V>);
 
 macro_rules! map_type {
@@ -5,6 +5,8 @@ use lazy_static::lazy_static;
 lazy_static! {
     pub static ref NONE: Map<String, String> = map_to_owned! {
         "" => "",
+        "ac_offline" => " BAT ",
+        "ac_online" => " AC ",
         "backlight_empty" => " BRIGHT ",
         "backlight_full" => " BRIGHT ",
         "backlight_partial1" => " BRIGHT ",
@@ -12,6 +14,7 @@ lazy_static! {
         "backlight_partial3" => " BRIGHT ",
         "bat" => " BAT ",
         "bat_charging" => " CHG ",
+        "bat_conservation" => " CONS ",
         "bat_discharging" => " DCG ",
         "bat_empty" => " EMP ",
         "bat_full" => " FULL ",
@@ -77,17 +80,34 @@ lazy_static! {
     // FontAwesome 4
     pub static ref AWESOME: Map<String, String> = map_to_owned! {
         "" => "",
+        "ac_offline" => " \u{f242} ",
+        "ac_online" => " \u{f1e6} ",
         "backlight_empty" => " \u{1f315} ",
         "backlight_full" => " \u{1f311} ",
         "backlight_partial1" => " \u{1f314} ",
         "backlight_partial2" => " \u{1f313} ",
         "backlight_partial3" => " \u{1f312} ",
         "bat_charging" => " \u{f1e6} ",
+        "bat_charging_empty" => " \u{f244}\u{f0e7} ",
+        "bat_charging_full" => " \u{f240}\u{f0e7} ",
+        "bat_charging_half" => " \u{f242}\u{f0e7} ",
+        "bat_charging_quarter" => " \u{f243}\u{f0e7} ",
+        "bat_charging_three_quarters" => " \u{f241}\u{f0e7} ",
+        "bat_conservation" => " \u{f06c} ",
         "bat_discharging" => " \u{f242} ",
         "bat_empty" => " \u{f244} ",
         "bat_full" => " \u{f240} ",
+        "bat_full_empty" => " \u{f244}\u{f00c} ",
+        "bat_full_half" => " \u{f242}\u{f00c} ",
+        "bat_full_quarter" => " \u{f243}\u{f00c} ",
+        "bat_full_three_quarters" => " \u{f241}\u{f00c} ",
         "bat_half" => " \u{f242} ",
         "bat_not_available" => " \u{f244} ",
+        "bat_plugged_empty" => " \u{f244}\u{f1e6} ",
+        "bat_plugged_full" => " \u{f240}\u{f1e6} ",
+        "bat_plugged_half" => " \u{f242}\u{f1e6} ",
+        "bat_plugged_quarter" => " \u{f243}\u{f1e6} ",
+        "bat_plugged_three_quarters" => " \u{f241}\u{f1e6} ",
         "bat_quarter" => " \u{f243} ",
         "bat_three_quarters" => " \u{f241} ",
         "bell" => " \u{f0f3} ",
@@ -151,16 +171,33 @@ lazy_static! {
     // FontAwesome 5
     pub static ref AWESOME5: Map<String, String> = map_to_owned! {
         "" => "",
+        "ac_offline" => " \u{f242} ",
+        "ac_online" => " \u{f1e6} ",
         "backlight_empty" => " \u{1f315} ",
         "backlight_full" => " \u{1f311} ",
         "backlight_partial1" => " \u{1f314} ",
         "backlight_partial2" => " \u{1f313} ",
         "backlight_partial3" => " \u{1f312} ",
         "bat_charging" => " \u{f1e6} ",
+        "bat_charging_empty" => " \u{f244}\u{f0e7} ",
+        "bat_charging_full" => " \u{f240}\u{f0e7} ",
+        "bat_charging_half" => " \u{f242}\u{f0e7} ",
+        "bat_charging_quarter" => " \u{f243}\u{f0e7} ",
+        "bat_charging_three_quarters" => " \u{f241}\u{f0e7} ",
+        "bat_conservation" => " \u{f06c} ",
         "bat_discharging" => " \u{f242} ",
         "bat_empty" => " \u{f244} ",
         "bat_full" => " \u{f240} ",
+        "bat_full_empty" => " \u{f244}\u{f00c} ",
+        "bat_full_half" => " \u{f242}\u{f00c} ",
+        "bat_full_quarter" => " \u{f243}\u{f00c} ",
+        "bat_full_three_quarters" => " \u{f241}\u{f00c} ",
         "bat_half" => " \u{f242} ",
+        "bat_plugged_empty" => " \u{f244}\u{f1e6} ",
+        "bat_plugged_full" => " \u{f240}\u{f1e6} ",
+        "bat_plugged_half" => " \u{f242}\u{f1e6} ",
+        "bat_plugged_quarter" => " \u{f243}\u{f1e6} ",
+        "bat_plugged_three_quarters" => " \u{f241}\u{f1e6} ",
         "bat_quarter" => " \u{f243} ",
         "bat_three_quarters" => " \u{f241} ",
         "bell" => " \u{f0f3} ",
@@ -223,11 +260,28 @@ lazy_static! {
 
     pub static ref MATERIAL: Map<String, String> = map_to_owned! {
         "" => "",
+        "ac_offline" => " \u{e1a5} ",
+        "ac_online" => " \u{e63c} ",
         "bat_charging" => " \u{e1a3} ",
+        "bat_charging_empty" => " \u{f0a2} ",
+        "bat_charging_full" => " \u{e1a3} ",
+        "bat_charging_half" => " \u{f0a4} ",
+        "bat_charging_quarter" => " \u{f0a3} ",
+        "bat_charging_three_quarters" => " \u{f0a6} ",
+        "bat_conservation" => " \u{ea35} ",
         "bat_discharging" => " \u{e19c} ",
         "bat_empty" => " \u{e19c} ",
         "bat_full" => " \u{e1a4} ",
+        "bat_full_empty" => " \u{e19c}\u{e5ca} ",
+        "bat_full_half" => " \u{e1a5}\u{e5ca} ",
+        "bat_full_quarter" => " \u{e1a5}\u{e5ca} ",
+        "bat_full_three_quarters" => " \u{e1a5}\u{e5ca} ",
         "bat_half" => " \u{e1a5} ",
+        "bat_plugged_empty" => " \u{e19c}\u{e63c} ",
+        "bat_plugged_full" => " \u{e1a4}\u{e63c} ",
+        "bat_plugged_half" => " \u{e1a5}\u{e63c} ",
+        "bat_plugged_quarter" => " \u{e1a5}\u{e63c} ",
+        "bat_plugged_three_quarters" => " \u{e1a5}\u{e63c} ",
         "bat_quarter" => " \u{e1a5} ",
         "bat_three_quarters" => " \u{e1a5} ",
         "bell" => " \u{e7f4} ",
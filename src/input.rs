@@ -6,9 +6,10 @@ use std::thread;
 
 use crossbeam_channel::Sender;
 use serde::{de, Deserializer};
-use serde_derive::Deserialize;
+use serde_derive::{Deserialize, Serialize};
 
-#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+#[derive(Deserialize, Serialize, Debug, Copy, Clone, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
 pub enum MouseButton {
     Left,
     Middle,
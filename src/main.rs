@@ -16,13 +16,14 @@ mod scheduler;
 mod signals;
 mod subprocess;
 mod themes;
+mod watch;
 mod widget;
 mod widgets;
 
 #[cfg(feature = "profiling")]
 use cpuprofiler::PROFILER;
 
-use std::collections::HashMap;
+use std::collections::{HashMap, HashSet};
 use std::ops::DerefMut;
 use std::time::Duration;
 
@@ -31,7 +32,7 @@ use crossbeam_channel::{select, Receiver, Sender};
 
 use crate::blocks::create_block;
 use crate::blocks::Block;
-use crate::config::{load_config, Config};
+use crate::config::{load_config, CommonBlockConfig, Config};
 use crate::errors::*;
 use crate::input::{process_events, I3BarEvent};
 use crate::scheduler::{Task, UpdateScheduler};
@@ -63,6 +64,16 @@ fn main() {
                 .long("never-pause")
                 .takes_value(false),
         )
+        .arg(
+            Arg::with_name("pause-when-hidden")
+                .help(
+                    "Pause updating blocks while the bar is hidden/fullscreen, except those with \
+                     `always_update`. Works with swaybar, i3bar does not signal its hidden mode",
+                )
+                .long("pause-when-hidden")
+                .takes_value(false)
+                .conflicts_with("never-pause"),
+        )
         .arg(
             Arg::with_name("one-shot")
                 .help("Print blocks once and exit")
@@ -122,6 +133,12 @@ fn run(matches: &ArgMatches) -> Result<()> {
             "\"version\": 1, \"click_events\": true, \"stop_signal\": {}",
             nix::sys::signal::Signal::SIGCONT as i8
         )
+    } else if matches.is_present("pause-when-hidden") {
+        format!(
+            "\"version\": 1, \"click_events\": true, \"stop_signal\": {}, \"cont_signal\": {}",
+            signals::SIGTSTP,
+            nix::sys::signal::Signal::SIGCONT as i8
+        )
     } else {
         "\"version\": 1, \"click_events\": true".to_string()
     };
@@ -187,11 +204,17 @@ fn run(matches: &ArgMatches) -> Result<()> {
     }
 
     let mut blocks: Vec<Box<dyn Block>> = Vec::new();
+    let mut always_update = HashSet::new();
+    // We save the order of the blocks here,
+    // because they will be passed to an unordered HashMap
+    let mut layout = Vec::new();
 
     let mut alternator = false;
     // Initialize the blocks
     for &(ref block_name, ref block_config) in &config.blocks {
-        blocks.push(create_block(
+        // The options read outside of the block itself
+        let common = CommonBlockConfig::extract(&mut block_config.clone())?;
+        let block = create_block(
             block_name,
             block_config.clone(),
             if alternator {
@@ -200,18 +223,20 @@ fn run(matches: &ArgMatches) -> Result<()> {
                 config.clone()
             },
             tx_update_requests.clone(),
-        )?);
+        )?;
+        if common.always_update {
+            always_update.insert(String::from(block.id()));
+        }
+        layout.push(util::BlockLayout {
+            id: String::from(block.id()),
+            group: common.group,
+            separator_color: common.separator_color,
+        });
+        blocks.push(block);
         alternator = !alternator;
     }
 
-    // We save the order of the blocks here,
-    // because they will be passed to an unordered HashMap
-    let order = blocks
-        .iter()
-        .map(|x| String::from(x.id()))
-        .collect::<Vec<_>>();
-
-    let mut scheduler = UpdateScheduler::new(&blocks);
+    let mut scheduler = UpdateScheduler::new(&blocks, always_update);
 
     let mut block_map: HashMap<String, &mut dyn Block> = HashMap::new();
 
@@ -226,7 +251,8 @@ fn run(matches: &ArgMatches) -> Result<()> {
 
     // We wait for signals in a separate thread
     let (tx_signals, rx_signals): (Sender<i32>, Receiver<i32>) = crossbeam_channel::unbounded();
-    process_signals(tx_signals);
+    let pause_when_hidden = matches.is_present("pause-when-hidden");
+    process_signals(tx_signals, pause_when_hidden);
 
     // Time to next update channel.
     // Fires immediately for first updates
@@ -242,7 +268,7 @@ fn run(matches: &ArgMatches) -> Result<()> {
                     for block in block_map.values_mut() {
                         block.click(&event)?;
                     }
-                    util::print_blocks(&order, &block_map, &config)?;
+                    util::print_blocks(&layout, &block_map, &config)?;
             },
             // Receive async update requests
             recv(rx_update_requests) -> request => if let Ok(req) = request {
@@ -251,13 +277,13 @@ fn run(matches: &ArgMatches) -> Result<()> {
                     .get_mut(&req.id)
                     .internal_error("scheduler", "could not get required block")?
                     .update()?;
-                util::print_blocks(&order, &block_map, &config)?;
+                util::print_blocks(&layout, &block_map, &config)?;
             },
             // Receive update timer events
             recv(ttnu) -> _ => {
                 scheduler.do_scheduled_updates(&mut block_map)?;
                 // redraw the blocks, state changed
-                util::print_blocks(&order, &block_map, &config)?;
+                util::print_blocks(&layout, &block_map, &config)?;
             },
             // Receive signal events
             recv(rx_signals) -> res => if let Ok(sig) = res {
@@ -267,13 +293,21 @@ fn run(matches: &ArgMatches) -> Result<()> {
                         for block in block_map.values_mut() {
                             block.update()?;
                         }
-                        util::print_blocks(&order, &block_map, &config)?;
+                        util::print_blocks(&layout, &block_map, &config)?;
                     },
                     signal_hook::SIGUSR2 => {
                         //USR2 signal that should reload the config
                         //TODO not implemented
                         //unimplemented!("SIGUSR2 is meant to be used to reload the config toml, but this feature is yet not implemented");
                     },
+                    signals::SIGTSTP if pause_when_hidden => {
+                        //The bar was hidden
+                        scheduler.pause();
+                    },
+                    signal_hook::SIGCONT if pause_when_hidden => {
+                        //The bar is visible again
+                        scheduler.resume();
+                    },
                     _ => {
                         //Real time signal that updates only the blocks listening
                         //for that signal
@@ -1,6 +1,7 @@
 use crate::blocks::Update;
+use chrono::{Local, Offset};
 use std::cmp;
-use std::collections::{BinaryHeap, HashMap};
+use std::collections::{BinaryHeap, HashMap, HashSet};
 use std::fmt;
 use std::thread;
 use std::time::{Duration, Instant};
@@ -42,10 +43,15 @@ impl cmp::Ord for Task {
 
 pub struct UpdateScheduler {
     schedule: BinaryHeap<Task>,
+    /// Blocks that are updated even while the scheduler is paused
+    always_update: HashSet<String>,
+    paused: bool,
+    /// Tasks that came due while the scheduler was paused
+    deferred: Vec<Task>,
 }
 
 impl UpdateScheduler {
-    pub fn new(blocks: &[Box<dyn Block>]) -> UpdateScheduler {
+    pub fn new(blocks: &[Box<dyn Block>], always_update: HashSet<String>) -> UpdateScheduler {
         let mut schedule = BinaryHeap::new();
 
         let now = Instant::now();
@@ -56,7 +62,29 @@ impl UpdateScheduler {
             });
         }
 
-        UpdateScheduler { schedule }
+        UpdateScheduler {
+            schedule,
+            always_update,
+            paused: false,
+            deferred: Vec::new(),
+        }
+    }
+
+    /// Stops updating the blocks that are not flagged `always_update`, e.g.
+    /// while the bar is hidden.
+    pub fn pause(&mut self) {
+        self.paused = true;
+    }
+
+    /// Resumes updating all blocks. Blocks that missed updates while paused
+    /// are updated once, right away.
+    pub fn resume(&mut self) {
+        self.paused = false;
+        let now = Instant::now();
+        for mut task in self.deferred.drain(..) {
+            task.update_time = now;
+            self.schedule.push(task);
+        }
     }
 
     pub fn time_to_next_update(&self) -> Option<Duration> {
@@ -107,6 +135,11 @@ impl UpdateScheduler {
         let now = Instant::now();
 
         for task in tasks_next {
+            if self.paused && !self.always_update.contains(&task.id) {
+                self.deferred.push(task);
+                continue;
+            }
+
             if let Some(dur) = block_map
                 .get_mut(&task.id)
                 .internal_error("scheduler", "could not get required block")?
@@ -117,6 +150,10 @@ impl UpdateScheduler {
                         id: task.id,
                         update_time: now + d,
                     }),
+                    Update::At(update_time) => self.schedule.push(Task {
+                        id: task.id,
+                        update_time,
+                    }),
                     Update::Once => {} // do not schedule this task again
                 }
             }
@@ -125,3 +162,67 @@ impl UpdateScheduler {
         Ok(())
     }
 }
+
+/// Time from `time` to the next whole multiple of `period`, both counted from
+/// the same origin. A `time` on a boundary waits a full period.
+fn time_to_boundary(time: Duration, period: Duration) -> Duration {
+    let period = period.as_nanos();
+    if period == 0 {
+        return Duration::from_secs(0);
+    }
+    let wait = period - time.as_nanos() % period;
+    Duration::from_nanos(wait as u64)
+}
+
+const DAY: Duration = Duration::from_secs(24 * 60 * 60);
+
+/// The next time at which the local wall clock is a whole multiple of `period`,
+/// e.g. the next full minute for a period of 60 seconds or the next full hour
+/// for 3600 seconds, for blocks that update on clock boundaries. A period of a
+/// day is the next midnight.
+pub fn next_boundary(period: Duration) -> Instant {
+    if period == DAY {
+        return next_midnight();
+    }
+    let now = Local::now();
+    let offset = i64::from(now.offset().fix().local_minus_utc());
+    let local_secs = (now.timestamp() + offset).max(0) as u64;
+    let local_time = Duration::new(local_secs, now.timestamp_subsec_nanos());
+    Instant::now() + time_to_boundary(local_time, period)
+}
+
+/// The next local midnight. Unlike a boundary of one day, this respects days
+/// that are shorter or longer because of daylight saving time.
+pub fn next_midnight() -> Instant {
+    let now = Local::now();
+    let midnight = now.date().succ().and_hms(0, 0, 0);
+    let wait = (midnight - now)
+        .to_std()
+        .unwrap_or_else(|_| Duration::from_secs(0));
+    Instant::now() + wait
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::Duration;
+
+    use crate::scheduler::time_to_boundary;
+
+    #[test]
+    fn test_time_to_boundary() {
+        let minute = Duration::from_secs(60);
+        assert_eq!(
+            time_to_boundary(Duration::from_millis(125_500), minute),
+            Duration::from_millis(54_500)
+        );
+        assert_eq!(time_to_boundary(Duration::from_secs(120), minute), minute);
+        assert_eq!(
+            time_to_boundary(Duration::from_secs(7), Duration::from_secs(5)),
+            Duration::from_secs(3)
+        );
+        assert_eq!(
+            time_to_boundary(Duration::from_secs(7), Duration::from_secs(0)),
+            Duration::from_secs(0)
+        );
+    }
+}
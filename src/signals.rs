@@ -2,8 +2,12 @@ use crate::errors::*;
 use crossbeam_channel::Sender;
 use std::thread;
 
-/// Starts a thread that listens for provided signals and sends these on the provided channel
-pub fn process_signals(sender: Sender<i32>) {
+/// Stop signal requested from the bar with `--pause-when-hidden`, sent when the bar is hidden.
+pub const SIGTSTP: i32 = nix::sys::signal::Signal::SIGTSTP as i32;
+
+/// Starts a thread that listens for provided signals and sends these on the provided channel.
+/// With `pause_when_hidden`, the bar's stop and continue signals are listened for as well.
+pub fn process_signals(sender: Sender<i32>, pause_when_hidden: bool) {
     thread::Builder::new()
         .name("signals".into())
         .spawn(move || {
@@ -17,6 +21,10 @@ pub fn process_signals(sender: Sender<i32>) {
                 let mut signals = (sigmin..sigmax).collect::<Vec<_>>();
                 signals.push(signal_hook::SIGUSR1);
                 signals.push(signal_hook::SIGUSR2);
+                if pause_when_hidden {
+                    signals.push(SIGTSTP);
+                    signals.push(signal_hook::SIGCONT);
+                }
                 let signals = signal_hook::iterator::Signals::new(&signals).unwrap();
                 for sig in signals.forever() {
                     sender.send(sig).unwrap();
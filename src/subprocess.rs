@@ -1,6 +1,11 @@
-use std::io;
-use std::process::{Command, Stdio};
+use std::io::{self, Write};
+use std::os::unix::process::CommandExt;
+use std::process::{Command, Output, Stdio};
 use std::thread;
+use std::time::Duration;
+
+use nix::sys::signal::{killpg, Signal};
+use nix::unistd::Pid;
 
 /// Spawns a new child process. This closes stdin and stdout, and returns to the caller after the
 /// child has been started, while a background thread waits for the child to exit.
@@ -16,3 +21,64 @@ pub fn spawn_child_async(name: &str, args: &[&str]) -> io::Result<()> {
         .unwrap();
     Ok(())
 }
+
+/// Runs a command to completion like `Command::output`, but kills it along with the processes it
+/// started once `timeout` has passed, in which case `None` is returned. The child is waited for
+/// by a background thread, which also reaps it after it has been killed.
+pub fn output_with_timeout(command: &mut Command, timeout: Duration) -> io::Result<Option<Output>> {
+    run_with_timeout(command, None, timeout)
+}
+
+/// Like `output_with_timeout`, but writes `input` to the stdin of the command. The input is
+/// written by a background thread, so that a command printing a lot before it has read all of
+/// its input cannot block on a full pipe.
+pub fn output_with_input(
+    command: &mut Command,
+    input: Vec<u8>,
+    timeout: Duration,
+) -> io::Result<Option<Output>> {
+    run_with_timeout(command, Some(input), timeout)
+}
+
+fn run_with_timeout(
+    command: &mut Command,
+    input: Option<Vec<u8>>,
+    timeout: Duration,
+) -> io::Result<Option<Output>> {
+    // In its own process group, the child can be killed together with its own children
+    let mut child = command
+        .stdin(if input.is_some() {
+            Stdio::piped()
+        } else {
+            Stdio::null()
+        })
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped())
+        .process_group(0)
+        .spawn()?;
+    if let (Some(input), Some(mut stdin)) = (input, child.stdin.take()) {
+        thread::Builder::new()
+            .name("subprocess".into())
+            .spawn(move || {
+                // The command may exit without reading all of its input
+                stdin.write_all(&input).ok();
+            })
+            .unwrap();
+    }
+    let pid = Pid::from_raw(child.id() as i32);
+
+    let (tx, rx) = crossbeam_channel::bounded(1);
+    thread::Builder::new()
+        .name("subprocess".into())
+        .spawn(move || tx.send(child.wait_with_output()).ok())
+        .unwrap();
+
+    match rx.recv_timeout(timeout) {
+        Ok(output) => output.map(Some),
+        // The thread only disconnects without sending if it panicked
+        Err(_) => {
+            killpg(pid, Signal::SIGKILL).ok();
+            Ok(None)
+        }
+    }
+}
@@ -23,6 +23,9 @@ lazy_static! {
         separator_fg: "auto".to_owned(),
         alternating_tint_bg: "#111111".to_owned(),
         alternating_tint_fg: "#111111".to_owned(),
+        group_start: "\u{e0b6}".to_owned(),
+        group_end: "\u{e0b4}".to_owned(),
+        group_bg: "auto".to_owned(),
     };
 
     pub static ref SOLARIZED_DARK: Theme = Theme {
@@ -41,6 +44,9 @@ lazy_static! {
         separator_fg: "auto".to_owned(),
         alternating_tint_bg: "#000000".to_owned(),
         alternating_tint_fg: "#000000".to_owned(),
+        group_start: "\u{e0b6}".to_owned(),
+        group_end: "\u{e0b4}".to_owned(),
+        group_bg: "auto".to_owned(),
     };
 
     pub static ref SOLARIZED_LIGHT: Theme = Theme {
@@ -59,6 +65,9 @@ lazy_static! {
         separator_fg: "auto".to_owned(),
         alternating_tint_bg: "#000000".to_owned(),
         alternating_tint_fg: "#000000".to_owned(),
+        group_start: "\u{e0b6}".to_owned(),
+        group_end: "\u{e0b4}".to_owned(),
+        group_bg: "auto".to_owned(),
     };
 
     pub static ref MODERN: Theme = Theme {
@@ -77,6 +86,9 @@ lazy_static! {
         separator_fg: "auto".to_owned(),
         alternating_tint_bg: "#000000".to_owned(),
         alternating_tint_fg: "#000000".to_owned(),
+        group_start: "\u{e0b6}".to_owned(),
+        group_end: "\u{e0b4}".to_owned(),
+        group_bg: "auto".to_owned(),
     };
 
     pub static ref PLAIN: Theme = Theme {
@@ -95,6 +107,9 @@ lazy_static! {
         separator_fg: "#a9a9a9".to_owned(),
         alternating_tint_bg: "#000000".to_owned(),
         alternating_tint_fg: "#000000".to_owned(),
+        group_start: "\u{e0b6}".to_owned(),
+        group_end: "\u{e0b4}".to_owned(),
+        group_bg: "auto".to_owned(),
     };
 
     pub static ref BAD_WOLF: Theme = Theme {
@@ -113,6 +128,9 @@ lazy_static! {
         separator_fg: "auto".to_owned(),
         alternating_tint_bg: "#000000".to_owned(),
         alternating_tint_fg: "#000000".to_owned(),
+        group_start: "\u{e0b6}".to_owned(),
+        group_end: "\u{e0b4}".to_owned(),
+        group_bg: "auto".to_owned(),
     };
 
     pub static ref GRUVBOX_LIGHT: Theme = Theme {
@@ -131,6 +149,9 @@ lazy_static! {
         separator_fg: "auto".to_owned(),
         alternating_tint_bg: "#000000".to_owned(),
         alternating_tint_fg: "#000000".to_owned(),
+        group_start: "\u{e0b6}".to_owned(),
+        group_end: "\u{e0b4}".to_owned(),
+        group_bg: "auto".to_owned(),
     };
 
     pub static ref GRUVBOX_DARK: Theme = Theme {
@@ -149,6 +170,9 @@ lazy_static! {
         separator_fg: "auto".to_owned(),
         alternating_tint_bg: "#000000".to_owned(),
         alternating_tint_fg: "#000000".to_owned(),
+        group_start: "\u{e0b6}".to_owned(),
+        group_end: "\u{e0b4}".to_owned(),
+        group_bg: "auto".to_owned(),
     };
 
     pub static ref SPACE_VILLAIN: Theme = Theme {
@@ -167,6 +191,9 @@ lazy_static! {
         separator_fg: "auto".to_owned(),
         alternating_tint_bg: "#00000000".to_owned(),
         alternating_tint_fg: "#00000000".to_owned(),
+        group_start: "\u{e0b6}".to_owned(),
+        group_end: "\u{e0b4}".to_owned(),
+        group_bg: "auto".to_owned(),
     };
 }
 
@@ -188,6 +215,15 @@ pub struct Theme {
     pub separator_fg: String,
     pub alternating_tint_bg: String,
     pub alternating_tint_fg: String,
+    /// Cap in front of a group of blocks
+    #[serde(default = "Theme::default_group_start")]
+    pub group_start: String,
+    /// Cap after a group of blocks
+    #[serde(default = "Theme::default_group_end")]
+    pub group_end: String,
+    /// Background of the blocks in a group, "auto" keeps their own backgrounds
+    #[serde(default = "Theme::default_group_bg")]
+    pub group_bg: String,
 }
 
 impl Default for Theme {
@@ -197,6 +233,18 @@ impl Default for Theme {
 }
 
 impl Theme {
+    fn default_group_start() -> String {
+        "\u{e0b6}".to_owned()
+    }
+
+    fn default_group_end() -> String {
+        "\u{e0b4}".to_owned()
+    }
+
+    fn default_group_bg() -> String {
+        "auto".to_owned()
+    }
+
     pub fn from_name(name: &str) -> Option<Theme> {
         match name {
             "slick" => Some(SLICK.clone()),
@@ -249,6 +297,9 @@ pub struct ThemeOverrides {
     separator_fg: Option<String>,
     alternating_tint_bg: Option<String>,
     alternating_tint_fg: Option<String>,
+    group_start: Option<String>,
+    group_end: Option<String>,
+    group_bg: Option<String>,
 }
 
 #[derive(Deserialize, Debug, Default, Clone)]
@@ -288,6 +339,9 @@ impl ThemeConfig {
             theme.alternating_tint_fg = overrides
                 .alternating_tint_fg
                 .unwrap_or(theme.alternating_tint_fg);
+            theme.group_start = overrides.group_start.unwrap_or(theme.group_start);
+            theme.group_end = overrides.group_end.unwrap_or(theme.group_end);
+            theme.group_bg = overrides.group_bg.unwrap_or(theme.group_bg);
         }
         Some(theme)
     }
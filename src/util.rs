@@ -1,20 +1,24 @@
 use num_traits::{clamp, ToPrimitive};
+use std::cell::{Cell, RefCell};
 use std::collections::HashMap;
 use std::fmt::Display;
 use std::fs::{File, OpenOptions};
 use std::io::prelude::*;
-use std::io::BufReader;
+use std::io::{BufReader, SeekFrom};
 use std::path::{Path, PathBuf};
 use std::prelude::v1::String;
 use std::process::Command;
+use std::str::FromStr;
 
 use regex::Regex;
 use serde::de::DeserializeOwned;
+use serde_derive::Deserialize;
 use serde_json::value::Value;
 
 use crate::blocks::Block;
 use crate::config::Config;
 use crate::errors::*;
+use crate::widget::I3BarWidget;
 
 pub const USR_SHARE_PATH: &str = "/usr/share/i3status-rust";
 
@@ -30,6 +34,90 @@ pub fn escape_pango_text(text: String) -> String {
         .collect()
 }
 
+/// How text from outside the bar is made safe for i3bar, which renders it as
+/// pango markup. The text comes from commands, window titles, song tags and the
+/// like, where `<` or `&` would otherwise break the rendering or inject markup.
+///
+/// `FormatTemplate` applies it to every value it substitutes for a placeholder,
+/// with the escaping of the block being updated, see `MarkupEscape::scope`. The
+/// format strings around the values are never escaped.
+#[derive(Deserialize, Debug, Copy, Clone, PartialEq, Eq, Default)]
+#[serde(rename_all = "lowercase")]
+pub enum MarkupEscape {
+    /// Escape the markup characters, so that the text is shown as is.
+    #[default]
+    Pango,
+    /// Remove markup tags and escape the rest of the text.
+    Strip,
+    /// Pass the text on unchanged, e.g. for commands that print markup.
+    None,
+}
+
+thread_local! {
+    /// Escaping of the block that is created or updated on this thread.
+    static CURRENT_MARKUP_ESCAPE: Cell<MarkupEscape> = const { Cell::new(MarkupEscape::Pango) };
+}
+
+impl MarkupEscape {
+    /// The escaping of the block that is created or updated on this thread.
+    pub fn current() -> Self {
+        CURRENT_MARKUP_ESCAPE.with(Cell::get)
+    }
+
+    /// Runs `f` with `self` as the current escaping, so that the values `f`
+    /// renders are escaped accordingly.
+    pub fn scope<T>(self, f: impl FnOnce() -> T) -> T {
+        let previous = CURRENT_MARKUP_ESCAPE.with(|current| current.replace(self));
+        let result = f();
+        CURRENT_MARKUP_ESCAPE.with(|current| current.set(previous));
+        result
+    }
+
+    /// Makes `text` safe to render. In every mode, line breaks and tabs become
+    /// spaces and other control characters are removed, since pango fails on
+    /// them and i3bar shows a single line anyway.
+    pub fn apply(self, text: &str) -> String {
+        let text: String = text
+            .chars()
+            .filter_map(|c| match c {
+                '\n' | '\r' | '\t' => Some(' '),
+                c if c.is_control() => None,
+                c => Some(c),
+            })
+            .collect();
+        match self {
+            MarkupEscape::Pango => escape_pango_text(text),
+            MarkupEscape::Strip => escape_pango_text(strip_markup(&text)),
+            MarkupEscape::None => text,
+        }
+    }
+}
+
+/// Removes markup tags, i.e. a `<` directly followed by a letter or `/` up to
+/// the next `>`. Angle brackets that are not part of a tag are kept.
+fn strip_markup(text: &str) -> String {
+    let mut stripped = String::with_capacity(text.len());
+    let mut rest = text;
+    while let Some(start) = rest.find('<') {
+        let is_tag = matches!(
+            rest[start + 1..].chars().next(),
+            Some(c) if c.is_ascii_alphabetic() || c == '/'
+        );
+        match rest[start..].find('>') {
+            Some(end) if is_tag => {
+                stripped.push_str(&rest[..start]);
+                rest = &rest[start + end + 1..];
+            }
+            _ => {
+                stripped.push_str(&rest[..=start]);
+                rest = &rest[start + 1..];
+            }
+        }
+    }
+    stripped.push_str(rest);
+    stripped
+}
+
 pub fn format_speed(
     bytes_speed: u64,
     total_digits: usize,
@@ -42,27 +130,58 @@ pub fn format_speed(
         bytes_speed
     };
 
-    let (min_unit_value, min_unit_level) = match min_unit {
-        "T" => (raw_value as f64 / 1_000_000_000_000.0, 4),
-        "G" => (raw_value as f64 / 1_000_000_000.0, 3),
-        "M" => (raw_value as f64 / 1_000_000.0, 2),
-        "K" => (raw_value as f64 / 1_000.0, 1),
-        _ => (raw_value as f64, 0),
-    };
+    let min_unit_level = unit_level(min_unit);
 
-    let (magnitude_value, magnitude_level) = match raw_value {
-        x if x > 99_999_999_999 => (raw_value as f64 / 1_000_000_000_000.0, 4),
-        x if x > 99_999_999 => (raw_value as f64 / 1_000_000_000.0, 3),
-        x if x > 99_999 => (raw_value as f64 / 1_000_000.0, 2),
-        x if x > 99 => (raw_value as f64 / 1_000.0, 1),
-        _ => (raw_value as f64, 0),
+    let magnitude_level = match raw_value {
+        x if x > 99_999_999_999 => 4,
+        x if x > 99_999_999 => 3,
+        x if x > 99_999 => 2,
+        x if x > 99 => 1,
+        _ => 0,
     };
 
-    let (value, level) = if magnitude_level < min_unit_level {
-        (min_unit_value, min_unit_level)
+    format_speed_in_level(
+        raw_value,
+        magnitude_level.max(min_unit_level),
+        total_digits,
+        use_bits,
+    )
+}
+
+/// Like `format_speed`, but always in `unit` instead of scaling the unit to
+/// the speed.
+pub fn format_speed_fixed(
+    bytes_speed: u64,
+    total_digits: usize,
+    unit: &str,
+    use_bits: bool,
+) -> String {
+    let raw_value = if use_bits {
+        bytes_speed * 8
     } else {
-        (magnitude_value, magnitude_level)
+        bytes_speed
     };
+    format_speed_in_level(raw_value, unit_level(unit), total_digits, use_bits)
+}
+
+/// The power of 1000 of a unit prefix.
+fn unit_level(unit: &str) -> i32 {
+    match unit {
+        "T" => 4,
+        "G" => 3,
+        "M" => 2,
+        "K" => 1,
+        _ => 0,
+    }
+}
+
+fn format_speed_in_level(
+    raw_value: u64,
+    level: i32,
+    total_digits: usize,
+    use_bits: bool,
+) -> String {
+    let value = raw_value as f64 / 1000f64.powi(level);
 
     let unit = if use_bits {
         match level {
@@ -82,7 +201,8 @@ pub fn format_speed(
         }
     };
 
-    let _decimal_precision = total_digits as i16 - if value >= 10.0 { 2 } else { 1 };
+    let integer_digits = value.max(1.0).log10().floor() as i16 + 1;
+    let _decimal_precision = total_digits as i16 - integer_digits;
     let decimal_precision = if _decimal_precision < 0 {
         0
     } else {
@@ -112,6 +232,14 @@ pub fn xdg_config_home() -> PathBuf {
     PathBuf::from(&config_path)
 }
 
+pub fn xdg_cache_home() -> PathBuf {
+    let cache_path = std::env::var("XDG_CACHE_HOME").unwrap_or(format!(
+        "{}/.cache",
+        std::env::var("HOME").unwrap_or_else(|_| "".to_string())
+    ));
+    PathBuf::from(&cache_path)
+}
+
 pub fn deserialize_file<T>(file: &str) -> Result<T>
 where
     T: DeserializeOwned,
@@ -140,6 +268,57 @@ pub fn read_file(blockname: &str, path: &Path) -> Result<String> {
     Ok(content)
 }
 
+thread_local! {
+    /// Attributes opened by `read_sysfs`, kept open to be re-read on every
+    /// update.
+    static SYSFS_FILES: RefCell<HashMap<PathBuf, File>> = RefCell::new(HashMap::new());
+}
+
+/// Reads a single value from a sysfs attribute, such as
+/// `/sys/class/power_supply/BAT0/capacity`, and parses it after trimming
+/// the surrounding whitespace. Errors name the file that could not be read
+/// or parsed.
+///
+/// The file is opened on the first read and kept open, later reads seek back
+/// to its start, which is how sysfs attributes are refreshed. A file that
+/// fails to read is closed and opened again on the next read.
+pub fn read_sysfs<T>(blockname: &str, path: &Path) -> Result<T>
+where
+    T: FromStr,
+    T::Err: Display,
+{
+    let content = SYSFS_FILES.with(|files| -> Result<String> {
+        let mut files = files.borrow_mut();
+        if !files.contains_key(path) {
+            let file = File::open(path).block_error(
+                blockname,
+                &format!("failed to open file {}", path.to_string_lossy()),
+            )?;
+            files.insert(path.to_path_buf(), file);
+        }
+
+        let file = files.get_mut(path).unwrap();
+        let mut content = String::new();
+        let read = file
+            .seek(SeekFrom::Start(0))
+            .and_then(|_| file.read_to_string(&mut content));
+        if read.is_err() {
+            files.remove(path);
+        }
+        read.block_error(
+            blockname,
+            &format!("failed to read {}", path.to_string_lossy()),
+        )?;
+        Ok(content)
+    })?;
+    content.trim().parse::<T>().map_err(|e| {
+        BlockError(
+            blockname.to_string(),
+            format!("failed to parse {}: {}", path.to_string_lossy(), e),
+        )
+    })
+}
+
 pub fn has_command(block_name: &str, command: &str) -> Result<bool> {
     let exit_status = Command::new("sh")
         .args(&[
@@ -192,6 +371,8 @@ macro_rules! map_to_owned (
 struct PrintState {
     pub last_bg: Option<String>,
     pub has_predecessor: bool,
+    /// Group of the last printed block
+    pub group: Option<String>,
 }
 
 impl PrintState {
@@ -203,76 +384,143 @@ impl PrintState {
     }
 }
 
+/// Position of a block on the bar, along with the common options that affect
+/// how it is joined to its neighbours.
+pub struct BlockLayout {
+    pub id: String,
+    /// Consecutive blocks with the same group are drawn as one pill
+    pub group: Option<String>,
+    /// Color of the separator in front of the block
+    pub separator_color: Option<String>,
+}
+
+/// Renders the end cap of a group or the separator in front of a block.
+fn separator_widget(text: &str, fg: &str, bg: Option<String>) -> Value {
+    json!({
+        "full_text": text,
+        "separator": false,
+        "separator_block_width": 0,
+        "background": match bg {
+            Some(bg) => Value::String(bg),
+            None => Value::Null
+        },
+        "color": fg,
+        "markup": "pango"
+    })
+}
+
 pub fn print_blocks(
-    order: &[String],
+    layout: &[BlockLayout],
     block_map: &HashMap<String, &mut dyn Block>,
     config: &Config,
 ) -> Result<()> {
     let mut state = PrintState {
         has_predecessor: false,
         last_bg: None,
+        group: None,
     };
 
     print!("[");
-    for block_id in order {
+    for block_layout in layout {
         let block = &(*(block_map
-            .get(block_id)
+            .get(&block_layout.id)
             .internal_error("util", "couldn't get block by id")?));
         let widgets = block.view();
         if widgets.is_empty() {
             continue;
         }
-        let first = widgets[0];
-        let color = first.get_rendered()["background"]
-            .as_str()
-            .internal_error("util", "couldn't get background color")?;
-
-        let sep_fg = if config.theme.separator_fg == "auto" {
-            color
-        } else {
-            &config.theme.separator_fg
+        // Widgets of a group are drawn on the group's background, if the theme has one
+        let group_bg = match block_layout.group {
+            Some(_) if config.theme.group_bg != "auto" => Some(&config.theme.group_bg),
+            _ => None,
         };
-
-        let sep_bg = if config.theme.separator_bg == "auto" {
-            state.last_bg.clone()
-        } else {
-            Some(config.theme.separator_bg.clone())
+        let background = |widget: &dyn I3BarWidget| -> Result<String> {
+            match group_bg {
+                Some(bg) => Ok(bg.clone()),
+                None => Ok(widget.get_rendered()["background"]
+                    .as_str()
+                    .internal_error("util", "couldn't get background color")?
+                    .to_owned()),
+            }
         };
+        let color = background(widgets[0])?;
+
+        let leaves_group = state.group.is_some() && state.group != block_layout.group;
+        let enters_group = block_layout.group.is_some() && state.group != block_layout.group;
+        if leaves_group {
+            // Blend the end cap into the next block, unless it is a pill of its own
+            let end_cap = separator_widget(
+                &config.theme.group_end,
+                state.last_bg.as_deref().unwrap_or_default(),
+                if enters_group {
+                    None
+                } else {
+                    Some(color.clone())
+                },
+            );
+            print!(",{}", end_cap);
+        }
 
-        let separator = json!({
-            "full_text": config.theme.separator,
-            "separator": false,
-            "separator_block_width": 0,
-            "background": match sep_bg {
-                Some(bg) => Value::String(bg),
-                None => Value::Null
-            },
-            "color": sep_fg,
-            "markup": "pango"
-        });
-        print!(
-            "{}{},",
-            if state.has_predecessor { "," } else { "" },
-            separator.to_string()
-        );
-        print!("{}", first.to_string());
-        state.set_last_bg(color.to_owned());
-        state.set_predecessor(true);
+        let separator = if enters_group {
+            separator_widget(
+                &config.theme.group_start,
+                &color,
+                if state.group.is_none() {
+                    state.last_bg.clone()
+                } else {
+                    None
+                },
+            )
+        } else {
+            let sep_fg = match block_layout.separator_color {
+                Some(ref sep_fg) => sep_fg,
+                None if config.theme.separator_fg == "auto" => &color,
+                None => &config.theme.separator_fg,
+            };
+
+            let sep_bg = if config.theme.separator_bg == "auto" {
+                state.last_bg.clone()
+            } else {
+                Some(config.theme.separator_bg.clone())
+            };
 
-        for widget in widgets.iter().skip(1) {
+            separator_widget(&config.theme.separator, sep_fg, sep_bg)
+        };
+        // The end cap already separates the block from the previous group
+        if !leaves_group || enters_group {
             print!(
-                "{}{}",
+                "{}{},",
                 if state.has_predecessor { "," } else { "" },
-                widget.to_string()
+                separator
             );
-            state.set_last_bg(String::from(
-                widget.get_rendered()["background"]
-                    .as_str()
-                    .internal_error("util", "couldn't get background color")?,
-            ));
+        } else {
+            print!(",");
+        }
+        state.group = block_layout.group.clone();
+
+        for (i, widget) in widgets.iter().enumerate() {
+            if i > 0 {
+                print!(",");
+            }
+            if group_bg.is_some() {
+                let mut rendered = widget.get_rendered().clone();
+                rendered["background"] = Value::String(background(*widget)?);
+                print!("{}", rendered);
+            } else {
+                print!("{}", widget.to_string());
+            }
+            state.set_last_bg(background(*widget)?);
             state.set_predecessor(true);
         }
     }
+    if state.group.is_some() {
+        let end_cap = separator_widget(
+            &config.theme.group_end,
+            state.last_bg.as_deref().unwrap_or_default(),
+            None,
+        );
+        print!(",{}", end_cap);
+    }
     println!("],");
 
     Ok(())
@@ -296,6 +544,30 @@ pub fn color_to_rgba(color: (u8, u8, u8, u8)) -> String {
     )
 }
 
+/// Exponential moving average, where `smoothing` is the weight of the previous
+/// average. A `smoothing` of 0 returns the sample unchanged.
+pub fn ewma(previous: f64, sample: f64, smoothing: f64) -> f64 {
+    smoothing * previous + (1. - smoothing) * sample
+}
+
+/// Checks that a `smoothing` option is a valid weight for `ewma`.
+pub fn check_smoothing(blockname: &str, smoothing: f64) -> Result<f64> {
+    if (0.0..1.0).contains(&smoothing) {
+        Ok(smoothing)
+    } else {
+        Err(ConfigurationError(
+            blockname.to_string(),
+            (
+                format!(
+                    "smoothing must be at least 0 and less than 1, got {}",
+                    smoothing
+                ),
+                "invalid smoothing".to_string(),
+            ),
+        ))
+    }
+}
+
 pub fn format_percent_bar(percent: f32) -> String {
     let percent = percent.min(100.0);
     let percent = percent.max(0.0);
@@ -429,11 +701,11 @@ impl FormatTemplate {
                 };
             }
             Var(ref key, ref next) => {
-                rendered.push_str(&format!(
+                rendered.push_str(&MarkupEscape::current().apply(&format!(
                     "{}",
                     vars.get(key)
                         .unwrap_or_else(|| panic!("Unknown placeholder in format string: {}", key))
-                ));
+                )));
                 if let Some(ref next) = *next {
                     rendered.push_str(&*next.render(vars));
                 };
@@ -453,13 +725,13 @@ impl FormatTemplate {
                 };
             }
             Var(ref key, ref next) => {
-                rendered.push_str(&format!(
+                rendered.push_str(&MarkupEscape::current().apply(&format!(
                     "{}",
                     vars.get(&**key).internal_error(
                         "util",
                         &format!("Unknown placeholder in format string: {}", key)
                     )?
-                ));
+                )));
                 if let Some(ref next) = *next {
                     rendered.push_str(&*next.render_static_str(vars)?);
                 };
@@ -469,13 +741,75 @@ impl FormatTemplate {
     }
 }
 
+/// A threshold given as an amount of bytes or as a percentage, e.g. `"10 GiB"` or `"5%"`.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Threshold {
+    Bytes(f64),
+    Percent(f64),
+    /// A bare number, interpreted by each block the way it always has been.
+    Unitless(f64),
+}
+
+impl Threshold {
+    /// Returns the threshold in bytes, relative to `total` bytes for
+    /// percentages. `Unitless` values are returned unchanged.
+    pub fn to_bytes(self, total: u64) -> f64 {
+        match self {
+            Threshold::Bytes(bytes) => bytes,
+            Threshold::Percent(percent) => total as f64 * percent / 100.,
+            Threshold::Unitless(value) => value,
+        }
+    }
+}
+
+impl FromStr for Threshold {
+    type Err = String;
+
+    fn from_str(s: &str) -> std::result::Result<Self, Self::Err> {
+        let s = s.trim();
+        let split = s
+            .find(|c: char| !(c.is_ascii_digit() || c == '.'))
+            .unwrap_or(s.len());
+        let (number, unit) = s.split_at(split);
+        let number: f64 = number
+            .parse()
+            .map_err(|_| format!("invalid threshold '{}': expected a number", s))?;
+
+        let multiplier = match unit.trim() {
+            "" => return Ok(Threshold::Unitless(number)),
+            "%" => return Ok(Threshold::Percent(number)),
+            "B" => 1.,
+            "KB" | "kB" => 1e3,
+            "MB" => 1e6,
+            "GB" => 1e9,
+            "TB" => 1e12,
+            "KiB" => 1024.,
+            "MiB" => 1024. * 1024.,
+            "GiB" => 1024. * 1024. * 1024.,
+            "TiB" => 1024. * 1024. * 1024. * 1024.,
+            unit => {
+                return Err(format!(
+                    "invalid threshold '{}': unknown unit '{}', expected %, B, KB, MB, GB, TB, KiB, MiB, GiB or TiB",
+                    s, unit
+                ))
+            }
+        };
+        Ok(Threshold::Bytes(number * multiplier))
+    }
+}
+
 macro_rules! if_debug {
     ($x:block) => (if cfg!(debug_assertions) $x)
 }
 
 #[cfg(test)]
 mod tests {
-    use crate::util::{color_from_rgba, has_command};
+    use crate::util::{
+        color_from_rgba, ewma, format_speed, format_speed_fixed, has_command, read_sysfs,
+        FormatTemplate, MarkupEscape, Threshold,
+    };
+    use assert_fs::prelude::{FileWriteStr, PathChild};
+    use assert_fs::TempDir;
 
     #[test]
     // we assume sh is always available
@@ -494,6 +828,64 @@ mod tests {
         let has_command = has_command.unwrap();
         assert!(!has_command)
     }
+
+    #[test]
+    fn test_format_speed() {
+        assert_eq!(format_speed(1_500, 3, "K", false), "1.50KB");
+        assert_eq!(format_speed(2_500_000, 3, "K", false), "2.50MB");
+        assert_eq!(format_speed(2_500_000, 3, "K", true), "20.0Mb");
+        assert_eq!(format_speed(50, 3, "B", false), "50.0B");
+
+        assert_eq!(format_speed_fixed(2_500_000, 3, "M", false), "2.50MB");
+        assert_eq!(format_speed_fixed(1_500, 3, "M", false), "0.00MB");
+        assert_eq!(format_speed_fixed(250_000_000, 3, "M", true), "2000Mb");
+        assert_eq!(format_speed_fixed(123_456_789, 3, "K", false), "123457KB");
+    }
+
+    #[test]
+    fn test_ewma() {
+        assert_eq!(ewma(10.0, 50.0, 0.0), 50.0);
+        assert_eq!(ewma(10.0, 50.0, 0.5), 30.0);
+        assert_eq!(ewma(10.0, 50.0, 0.75), 20.0);
+    }
+
+    #[test]
+    fn test_read_sysfs() {
+        let dir = TempDir::new().unwrap();
+        dir.child("capacity").write_str("42\n").unwrap();
+        dir.child("voltage").write_str(" 11.4 \n").unwrap();
+        dir.child("status").write_str("Charging\n").unwrap();
+        dir.child("garbage").write_str("N/A\n").unwrap();
+
+        assert_eq!(
+            read_sysfs::<u64>("none", &dir.path().join("capacity")).unwrap(),
+            42
+        );
+        assert_eq!(
+            read_sysfs::<f64>("none", &dir.path().join("voltage")).unwrap(),
+            11.4
+        );
+        assert_eq!(
+            read_sysfs::<String>("none", &dir.path().join("status")).unwrap(),
+            "Charging"
+        );
+
+        // The cached file is re-read from the start
+        dir.child("capacity").write_str("7\n").unwrap();
+        assert_eq!(
+            read_sysfs::<u64>("none", &dir.path().join("capacity")).unwrap(),
+            7
+        );
+
+        let path = dir.path().join("garbage");
+        let error = read_sysfs::<u64>("none", &path).unwrap_err();
+        assert!(error.to_string().contains(&*path.to_string_lossy()));
+
+        let path = dir.path().join("missing");
+        let error = read_sysfs::<u64>("none", &path).unwrap_err();
+        assert!(error.to_string().contains(&*path.to_string_lossy()));
+    }
+
     #[test]
     fn test_color_from_rgba() {
         let valid_rgb = "#AABBCC"; //rgb
@@ -518,4 +910,63 @@ mod tests {
         let rgba = color_from_rgba(invalid);
         assert!(rgba.is_err());
     }
+
+    #[test]
+    fn test_threshold() {
+        assert_eq!("5%".parse(), Ok(Threshold::Percent(5.)));
+        assert_eq!(
+            "10 GiB".parse(),
+            Ok(Threshold::Bytes(10. * 1024. * 1024. * 1024.))
+        );
+        assert_eq!("1.5GB".parse(), Ok(Threshold::Bytes(1.5e9)));
+        assert_eq!("512 B".parse(), Ok(Threshold::Bytes(512.)));
+        assert_eq!("20".parse(), Ok(Threshold::Unitless(20.)));
+        assert_eq!(Threshold::Percent(25.).to_bytes(400), 100.);
+
+        assert!("GiB".parse::<Threshold>().is_err());
+        assert!("10 parsecs".parse::<Threshold>().is_err());
+        assert!("1.2.3 GB".parse::<Threshold>().is_err());
+    }
+
+    #[test]
+    fn test_markup_escape() {
+        let title = "Tom & Jerry <b>Live</b> 'n' more";
+        assert_eq!(
+            MarkupEscape::Pango.apply(title),
+            "Tom &amp; Jerry &lt;b&gt;Live&lt;/b&gt; &#39;n&#39; more"
+        );
+        assert_eq!(
+            MarkupEscape::Strip.apply(title),
+            "Tom &amp; Jerry Live &#39;n&#39; more"
+        );
+        assert_eq!(MarkupEscape::None.apply(title), title);
+
+        assert_eq!(MarkupEscape::Strip.apply("a < b"), "a &lt; b");
+        assert_eq!(MarkupEscape::Strip.apply("a > b"), "a &gt; b");
+        assert_eq!(MarkupEscape::Strip.apply("a < b > c"), "a &lt; b &gt; c");
+        assert_eq!(MarkupEscape::Strip.apply("1<2 <i>x</i>"), "1&lt;2 x");
+    }
+
+    #[test]
+    fn test_markup_escape_control_characters() {
+        let output = "line one\nline\ttwo\r\u{7}\u{1b}[0m";
+        assert_eq!(MarkupEscape::Pango.apply(output), "line one line two [0m");
+        assert_eq!(MarkupEscape::None.apply(output), "line one line two [0m");
+        assert_eq!(MarkupEscape::Pango.apply("\u{0}<&>"), "&lt;&amp;&gt;");
+    }
+
+    #[test]
+    fn test_markup_escape_format_values() {
+        let format = FormatTemplate::from_string("<b>{title}</b>").unwrap();
+        let values = map!("{title}" => "Tom & Jerry");
+        assert_eq!(
+            format.render_static_str(&values).unwrap(),
+            "<b>Tom &amp; Jerry</b>"
+        );
+        assert_eq!(
+            MarkupEscape::None.scope(|| format.render_static_str(&values).unwrap()),
+            "<b>Tom & Jerry</b>"
+        );
+        assert_eq!(MarkupEscape::current(), MarkupEscape::Pango);
+    }
 }
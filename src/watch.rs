@@ -0,0 +1,189 @@
+//! Requests block updates when sysfs devices or files change, so that blocks
+//! showing rarely changing values can react right away and poll rarely.
+
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, Mutex};
+use std::thread;
+use std::time::{Duration, Instant};
+
+use crossbeam_channel::Sender;
+use inotify::{EventMask, Inotify, WatchMask};
+use lazy_static::lazy_static;
+use nix::sys::socket::{
+    bind, recv, socket, AddressFamily, MsgFlags, SockAddr, SockFlag, SockProtocol, SockType,
+};
+
+use crate::errors::*;
+use crate::scheduler::Task;
+
+const SYS_CLASS_PATH: &str = "/sys/class";
+
+/// Netlink multicast group of the uevents sent by the kernel.
+const UEVENT_KERNEL_GROUP: u32 = 1;
+
+/// A device of a class in sysfs, such as `power_supply/BAT0`, that a block
+/// wants to be updated for.
+struct DeviceWatch {
+    subsystem: String,
+    name: String,
+    id: String,
+    tx: Sender<Task>,
+}
+
+lazy_static! {
+    /// The watched devices, or `None` if the uevents of the kernel cannot be
+    /// received.
+    static ref DEVICE_WATCHES: Option<Arc<Mutex<Vec<DeviceWatch>>>> = listen_for_uevents();
+}
+
+/// Requests an update of the block `id` whenever the kernel sends a uevent for
+/// the device at `path`, which must be in /sys/class, e.g.
+/// /sys/class/power_supply/AC. The device does not need to exist yet.
+///
+/// Returns whether the device is watched, blocks should keep polling if not.
+pub fn watch_device(path: &Path, id: &str, tx: &Sender<Task>) -> bool {
+    let (subsystem, name) = match class_device(path) {
+        Some(device) => device,
+        None => return false,
+    };
+    match *DEVICE_WATCHES {
+        Some(ref watches) => {
+            watches.lock().unwrap().push(DeviceWatch {
+                subsystem,
+                name,
+                id: id.to_string(),
+                tx: tx.clone(),
+            });
+            true
+        }
+        None => false,
+    }
+}
+
+/// Splits /sys/class/<subsystem>/<name> into the subsystem and the name.
+fn class_device(path: &Path) -> Option<(String, String)> {
+    let mut components = path.strip_prefix(SYS_CLASS_PATH).ok()?.iter();
+    let subsystem = components.next()?.to_str()?.to_string();
+    let name = components.next()?.to_str()?.to_string();
+    if components.next().is_some() {
+        return None;
+    }
+    Some((subsystem, name))
+}
+
+fn listen_for_uevents() -> Option<Arc<Mutex<Vec<DeviceWatch>>>> {
+    let fd = match socket(
+        AddressFamily::Netlink,
+        SockType::Datagram,
+        SockFlag::SOCK_CLOEXEC,
+        SockProtocol::NetlinkKObjectUEvent,
+    ) {
+        Ok(fd) => fd,
+        Err(e) => {
+            eprintln!(
+                "failed to open the uevent socket, falling back to polling: {}",
+                e
+            );
+            return None;
+        }
+    };
+    if let Err(e) = bind(fd, &SockAddr::new_netlink(0, UEVENT_KERNEL_GROUP)) {
+        eprintln!(
+            "failed to listen for uevents, falling back to polling: {}",
+            e
+        );
+        return None;
+    }
+
+    let watches: Arc<Mutex<Vec<DeviceWatch>>> = Arc::new(Mutex::new(Vec::new()));
+    let thread_watches = watches.clone();
+    thread::Builder::new()
+        .name("uevent".into())
+        .spawn(move || {
+            let mut buf = [0; 8192];
+            while let Ok(len) = recv(fd, &mut buf, MsgFlags::empty()) {
+                // A uevent is a header followed by KEY=value pairs, all null terminated
+                let mut subsystem = None;
+                let mut name = None;
+                for field in buf[..len].split(|&byte| byte == 0) {
+                    let field = String::from_utf8_lossy(field);
+                    if let Some(value) = field.strip_prefix("SUBSYSTEM=") {
+                        subsystem = Some(value.to_string());
+                    } else if let Some(value) = field.strip_prefix("DEVPATH=") {
+                        name = value.rsplit('/').next().map(String::from);
+                    }
+                }
+
+                for watch in thread_watches.lock().unwrap().iter() {
+                    if subsystem.as_ref() == Some(&watch.subsystem)
+                        && name.as_ref() == Some(&watch.name)
+                    {
+                        // The bar may be shutting down
+                        watch
+                            .tx
+                            .send(Task {
+                                id: watch.id.clone(),
+                                update_time: Instant::now(),
+                            })
+                            .ok();
+                    }
+                }
+            }
+        })
+        .expect("failed to start uevent thread");
+    Some(watches)
+}
+
+/// Requests an update of the block `id` whenever the file at `path` is
+/// modified, at most once per `debounce`. Unlike uevents, this notices writes
+/// to sysfs attributes by other programs.
+pub fn watch_file(path: PathBuf, id: String, tx: Sender<Task>, debounce: Duration) -> Result<()> {
+    let mut notify = Inotify::init().block_error("watch", "failed to start inotify")?;
+    notify
+        .add_watch(&path, WatchMask::MODIFY)
+        .block_error("watch", &format!("failed to watch {}", path.display()))?;
+
+    thread::Builder::new()
+        .name("inotify".into())
+        .spawn(move || {
+            let mut buffer = [0; 1024];
+            loop {
+                let mut events = notify
+                    .read_events_blocking(&mut buffer)
+                    .expect("Error while reading inotify events");
+                if events.any(|event| event.mask.contains(EventMask::MODIFY)) {
+                    tx.send(Task {
+                        id: id.clone(),
+                        update_time: Instant::now(),
+                    })
+                    .unwrap();
+                }
+
+                // Avoid update spam.
+                thread::sleep(debounce)
+            }
+        })
+        .unwrap();
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use std::path::Path;
+
+    use crate::watch::class_device;
+
+    #[test]
+    fn test_class_device() {
+        assert_eq!(
+            class_device(Path::new("/sys/class/power_supply/BAT0")),
+            Some(("power_supply".to_string(), "BAT0".to_string()))
+        );
+        assert_eq!(class_device(Path::new("/sys/class/power_supply")), None);
+        assert_eq!(
+            class_device(Path::new("/sys/class/power_supply/BAT0/status")),
+            None
+        );
+        assert_eq!(class_device(Path::new("/sys/devices/platform/AC")), None);
+    }
+}
@@ -17,8 +17,28 @@ pub enum Spacing {
     Hidden,
 }
 
-#[derive(Debug, Copy, Clone, Deserialize)]
+/// Right-to-left isolate, lays out the text up to `POP_DIRECTIONAL_ISOLATE`
+/// right to left without affecting the text around it.
+const RIGHT_TO_LEFT_ISOLATE: char = '\u{2067}';
+const POP_DIRECTIONAL_ISOLATE: char = '\u{2069}';
+
+/// Joins the icon, the text and the trailing space of a widget. For right-to-left
+/// scripts the icon goes to the right of the text, and the text is isolated as
+/// right-to-left so that the fields of composite formats read in order.
+pub fn compose_text(icon: &str, text: &str, trailing: &str, rtl: bool) -> String {
+    if rtl {
+        format!(
+            "{}{}{}{}{}",
+            trailing, RIGHT_TO_LEFT_ISOLATE, text, POP_DIRECTIONAL_ISOLATE, icon
+        )
+    } else {
+        format!("{}{}{}", icon, text, trailing)
+    }
+}
+
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Deserialize, Default)]
 pub enum State {
+    #[default]
     Idle,
     Info,
     Good,
@@ -51,4 +71,7 @@ impl FromStr for State {
 pub trait I3BarWidget {
     fn to_string(&self) -> String;
     fn get_rendered(&self) -> &Value;
+    /// The text of the widget after the block's `format_text`, without the
+    /// icon
+    fn formatted_text(&self) -> &str;
 }
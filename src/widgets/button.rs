@@ -1,6 +1,6 @@
 use serde_json::value::Value;
 
-use super::super::widget::I3BarWidget;
+use super::super::widget::{compose_text, I3BarWidget};
 use crate::config::Config;
 use crate::widget::Spacing;
 use crate::widget::State;
@@ -8,8 +8,14 @@ use crate::widget::State;
 #[derive(Clone, Debug)]
 pub struct ButtonWidget {
     content: Option<String>,
+    /// `content` after the block's `format_text`
+    formatted: String,
+    short_content: Option<String>,
     icon: Option<String>,
     state: State,
+    /// Colors overriding the ones of the state
+    color: Option<String>,
+    background: Option<String>,
     spacing: Spacing,
     id: String,
     rendered: Value,
@@ -21,8 +27,12 @@ impl ButtonWidget {
     pub fn new(config: Config, id: &str) -> Self {
         ButtonWidget {
             content: None,
+            formatted: String::new(),
+            short_content: None,
             icon: None,
             state: State::Idle,
+            color: None,
+            background: None,
             spacing: Spacing::Normal,
             id: String::from(id),
             rendered: json!({
@@ -73,6 +83,12 @@ impl ButtonWidget {
         self.update();
     }
 
+    /// Sets the text shown instead of the full text when the bar is short of space.
+    pub fn set_short_text(&mut self, content: Option<String>) {
+        self.short_content = content;
+        self.update();
+    }
+
     pub fn set_icon(&mut self, name: &str) {
         self.icon = self.config.icons.get(name).cloned();
         self.update();
@@ -88,31 +104,50 @@ impl ButtonWidget {
         self.update();
     }
 
+    /// Overrides the foreground and background colors of the state, `None`
+    /// keeps the color of the state.
+    pub fn set_colors(&mut self, color: Option<String>, background: Option<String>) {
+        self.color = color;
+        self.background = background;
+        self.update();
+    }
+
+    fn compose(&self, formatted: &str) -> String {
+        compose_text(
+            &self.icon.clone().unwrap_or_else(|| match self.spacing {
+                Spacing::Normal => String::from(" "),
+                _ => String::from(""),
+            }),
+            formatted,
+            match self.spacing {
+                Spacing::Hidden => "",
+                _ => " ",
+            },
+            self.config.rtl,
+        )
+    }
+
     fn update(&mut self) {
         let (key_bg, key_fg) = self.state.theme_keys(&self.config.theme);
+        self.formatted = self
+            .config
+            .block
+            .format_text(self.content.as_deref().unwrap_or(""), self.state);
 
         // When rendered inline, remove the leading space
         self.rendered = json!({
-            "full_text": format!("{}{}{}",
-                                self.icon.clone().unwrap_or_else(|| {
-                                    match self.spacing {
-                                        Spacing::Normal => String::from(" "),
-                                        _ => String::from("")
-                                    }
-                                }),
-                                self.content.clone().unwrap_or_else(|| String::from("")),
-                                match self.spacing {
-                                    Spacing::Hidden => String::from(""),
-                                    _ => String::from(" ")
-                                }
-                            ),
+            "full_text": self.compose(&self.formatted),
             "separator": false,
             "name": self.id.clone(),
             "separator_block_width": 0,
-            "background": key_bg,
-            "color": key_fg,
+            "background": self.background.as_ref().unwrap_or(key_bg),
+            "color": self.color.as_ref().unwrap_or(key_fg),
             "markup": "pango"
         });
+        if let Some(ref short_content) = self.short_content {
+            let short_formatted = self.config.block.format_text(short_content, self.state);
+            self.rendered["short_text"] = Value::String(self.compose(&short_formatted));
+        }
 
         self.cached_output = Some(self.rendered.to_string());
     }
@@ -128,4 +163,8 @@ impl I3BarWidget for ButtonWidget {
     fn get_rendered(&self) -> &Value {
         &self.rendered
     }
+
+    fn formatted_text(&self) -> &str {
+        &self.formatted
+    }
 }
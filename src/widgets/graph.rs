@@ -1,7 +1,7 @@
 use num_traits::{clamp, ToPrimitive};
 use serde_json::value::Value;
 
-use super::super::widget::I3BarWidget;
+use super::super::widget::{compose_text, I3BarWidget};
 use crate::config::Config;
 use crate::widget::Spacing;
 use crate::widget::State;
@@ -9,6 +9,8 @@ use crate::widget::State;
 #[derive(Clone, Debug)]
 pub struct GraphWidget {
     content: Option<String>,
+    /// `content` after the block's `format_text`
+    formatted: String,
     icon: Option<String>,
     state: State,
     spacing: Spacing,
@@ -21,6 +23,7 @@ impl GraphWidget {
     pub fn new(config: Config) -> Self {
         GraphWidget {
             content: None,
+            formatted: String::new(),
             icon: None,
             state: State::Idle,
             spacing: Spacing::Normal,
@@ -100,20 +103,25 @@ impl GraphWidget {
 
     fn update(&mut self) {
         let (key_bg, key_fg) = self.state.theme_keys(&self.config.theme);
+        self.formatted = self
+            .config
+            .block
+            .format_text(self.content.as_deref().unwrap_or(""), self.state);
 
         self.rendered = json!({
-            "full_text": format!("{}{}{}",
-                                self.icon.clone().unwrap_or_else(|| {
+            "full_text": compose_text(
+                                &self.icon.clone().unwrap_or_else(|| {
                                     match self.spacing {
                                         Spacing::Normal => String::from(" "),
                                         _ => String::from("")
                                     }
                                 }),
-                                self.content.clone().unwrap_or_else(|| String::from("")),
+                                &self.formatted,
                                 match self.spacing {
-                                    Spacing::Hidden => String::from(""),
-                                    _ => String::from(" ")
-                                }
+                                    Spacing::Hidden => "",
+                                    _ => " "
+                                },
+                                self.config.rtl
                             ),
             "separator": false,
             "separator_block_width": 0,
@@ -135,4 +143,8 @@ impl I3BarWidget for GraphWidget {
     fn get_rendered(&self) -> &Value {
         &self.rendered
     }
+
+    fn formatted_text(&self) -> &str {
+        &self.formatted
+    }
 }
@@ -4,7 +4,31 @@ use serde_json::value::Value;
 
 use crate::config::Config;
 use crate::errors::*;
-use crate::widget::{I3BarWidget, Spacing, State};
+use crate::widget::{compose_text, I3BarWidget, Spacing, State};
+
+/// Splits `text` into the units it rotates by: characters, except that escaped
+/// characters such as `&amp;` are kept whole so that rotating does not cut them.
+fn units(text: &str) -> Vec<&str> {
+    let mut units = Vec::new();
+    let mut rest = text;
+    while let Some(c) = rest.chars().next() {
+        let len = match rest.find(';') {
+            Some(end)
+                if c == '&'
+                    && end > 1
+                    && rest[1..end]
+                        .chars()
+                        .all(|c| c.is_ascii_alphanumeric() || c == '#') =>
+            {
+                end + 1
+            }
+            _ => c.len_utf8(),
+        };
+        units.push(&rest[..len]);
+        rest = &rest[len..];
+    }
+    units
+}
 
 #[derive(Clone, Debug)]
 pub struct RotatingTextWidget {
@@ -15,6 +39,8 @@ pub struct RotatingTextWidget {
     rotation_speed: Duration,
     next_rotation: Option<Instant>,
     content: String,
+    /// `content` after the block's `format_text`, which is what rotates
+    formatted: String,
     icon: Option<String>,
     state: State,
     spacing: Spacing,
@@ -43,6 +69,7 @@ impl RotatingTextWidget {
             rotation_speed: speed,
             next_rotation: None,
             content: String::new(),
+            formatted: String::new(),
             icon: None,
             state: State::Idle,
             spacing: Spacing::Normal,
@@ -68,6 +95,7 @@ impl RotatingTextWidget {
 
     pub fn with_state(mut self, state: State) -> Self {
         self.state = state;
+        self.reformat();
         self.update();
         self
     }
@@ -80,18 +108,15 @@ impl RotatingTextWidget {
 
     pub fn with_text(mut self, content: &str) -> Self {
         self.content = String::from(content);
-        self.rotation_pos = 0;
-        if self.content.chars().count() > self.max_width {
-            self.next_rotation = Some(Instant::now() + self.rotation_interval);
-        } else {
-            self.next_rotation = None;
-        }
+        self.formatted.clear();
+        self.reformat();
         self.update();
         self
     }
 
     pub fn set_state(&mut self, state: State) {
         self.state = state;
+        self.reformat();
         self.update();
     }
 
@@ -101,16 +126,24 @@ impl RotatingTextWidget {
     }
 
     pub fn set_text(&mut self, content: String) {
-        if self.content != content {
-            self.content = content;
+        self.content = content;
+        self.reformat();
+        self.update()
+    }
+
+    /// Applies the block's `format_text` to the content, restarting the
+    /// rotation if the formatted text changed.
+    fn reformat(&mut self) {
+        let formatted = self.config.block.format_text(&self.content, self.state);
+        if formatted != self.formatted {
+            self.formatted = formatted;
             self.rotation_pos = 0;
-            if self.content.chars().count() > self.max_width {
+            if units(&self.formatted).len() > self.max_width {
                 self.next_rotation = Some(Instant::now() + self.rotation_interval);
             } else {
                 self.next_rotation = None;
             }
         }
-        self.update()
     }
 
     pub fn is_empty(&self) -> bool {
@@ -118,28 +151,29 @@ impl RotatingTextWidget {
     }
 
     fn get_rotated_content(&self) -> String {
-        if self.content.chars().count() > self.max_width {
-            let missing =
-                (self.rotation_pos + self.max_width).saturating_sub(self.content.chars().count());
+        let units = units(&self.formatted);
+        if units.len() > self.max_width {
+            let missing = (self.rotation_pos + self.max_width).saturating_sub(units.len());
             if missing == 0 {
-                self.content
-                    .chars()
+                units
+                    .iter()
                     .skip(self.rotation_pos)
                     .take(self.max_width)
+                    .copied()
                     .collect()
             } else {
-                let mut avail: String = self
-                    .content
-                    .chars()
+                let mut avail: String = units
+                    .iter()
                     .skip(self.rotation_pos)
                     .take(self.max_width)
+                    .copied()
                     .collect();
                 avail.push('|');
-                avail.push_str(&self.content.chars().take(missing - 1).collect::<String>());
+                avail.push_str(&units[..missing - 1].concat());
                 avail
             }
         } else {
-            self.content.clone()
+            self.formatted.clone()
         }
     }
 
@@ -152,20 +186,21 @@ impl RotatingTextWidget {
         });
 
         self.rendered = json!({
-            "full_text": format!("{}{}{}",
-                                icon,
-                                self.get_rotated_content(),
+            "full_text": compose_text(
+                                &icon,
+                                &self.get_rotated_content(),
                                 match self.spacing {
-                                    Spacing::Hidden => String::from(""),
-                                    _ => String::from(" ")
-                                }),
+                                    Spacing::Hidden => "",
+                                    _ => " "
+                                },
+                                self.config.rtl),
             "separator": false,
             "separator_block_width": 0,
             "min_width":
-                if self.content == "" {
+                if self.formatted.is_empty() {
                     "".to_string()
                 } else {
-                    let text_width = self.get_rotated_content().chars().count();
+                    let text_width = units(&self.get_rotated_content()).len();
                     let icon_width = icon.chars().count();
                     if self.dynamic_width && text_width < self.max_width {
                         "0".repeat(text_width + icon_width)
@@ -173,7 +208,7 @@ impl RotatingTextWidget {
                         "0".repeat(self.max_width + icon_width + 1)
                     }
                 },
-            "align": "left",
+            "align": if self.config.rtl { "right" } else { "left" },
             "background": key_bg,
             "name": self.id.clone(),
             "color": key_fg
@@ -188,7 +223,7 @@ impl RotatingTextWidget {
             if next_rotation > now {
                 Ok((false, Some(next_rotation - now)))
             } else if self.rotating {
-                if self.rotation_pos < self.content.chars().count() {
+                if self.rotation_pos < units(&self.formatted).len() {
                     self.rotation_pos += 1;
                     self.next_rotation = Some(now + self.rotation_speed);
                     self.update();
@@ -220,4 +255,8 @@ impl I3BarWidget for RotatingTextWidget {
     fn get_rendered(&self) -> &Value {
         &self.rendered
     }
+
+    fn formatted_text(&self) -> &str {
+        &self.formatted
+    }
 }
@@ -1,6 +1,6 @@
 use serde_json::value::Value;
 
-use super::super::widget::I3BarWidget;
+use super::super::widget::{compose_text, I3BarWidget};
 use crate::config::Config;
 use crate::widget::Spacing;
 use crate::widget::State;
@@ -8,6 +8,8 @@ use crate::widget::State;
 #[derive(Clone, Debug)]
 pub struct TextWidget {
     content: Option<String>,
+    /// `content` after the block's `format_text`
+    formatted: String,
     icon: Option<String>,
     state: State,
     spacing: Spacing,
@@ -20,6 +22,7 @@ impl TextWidget {
     pub fn new(config: Config) -> Self {
         TextWidget {
             content: None,
+            formatted: String::new(),
             icon: None,
             state: State::Idle,
             spacing: Spacing::Normal,
@@ -76,20 +79,25 @@ impl TextWidget {
 
     fn update(&mut self) {
         let (key_bg, key_fg) = self.state.theme_keys(&self.config.theme);
+        self.formatted = self
+            .config
+            .block
+            .format_text(self.content.as_deref().unwrap_or(""), self.state);
 
         self.rendered = json!({
-            "full_text": format!("{}{}{}",
-                                self.icon.clone().unwrap_or_else(|| {
+            "full_text": compose_text(
+                                &self.icon.clone().unwrap_or_else(|| {
                                     match self.spacing {
                                         Spacing::Normal => String::from(" "),
                                         _ => String::from("")
                                     }
                                 }),
-                                self.content.clone().unwrap_or_else(|| String::from("")),
+                                &self.formatted,
                                 match self.spacing {
-                                    Spacing::Hidden => String::from(""),
-                                    _ => String::from(" ")
-                                }
+                                    Spacing::Hidden => "",
+                                    _ => " "
+                                },
+                                self.config.rtl
                             ),
             "separator": false,
             "separator_block_width": 0,
@@ -111,4 +119,8 @@ impl I3BarWidget for TextWidget {
     fn get_rendered(&self) -> &Value {
         &self.rendered
     }
+
+    fn formatted_text(&self) -> &str {
+        &self.formatted
+    }
 }